@@ -11,14 +11,14 @@ fail() {
 	exit 1
 }
 
-path="$1"
+name="$1"
 
 log "Note: if the dashboard is not in focus, this script may hang until it becomes so."
 
-if [[ "$path" == "" ]]; then
-	fail "Please provide a surprise path (in the format of \"assets/<surprise_name>\")!"
+if [[ "$name" == "" ]]; then
+	fail "Please provide a surprise name (the file name of its texture, without the folder or extension, like \"nathan\")!"
 fi
 
-printf "$path" | nc -U /tmp/surprises_wbor_studio_dashboard.sock || fail "Could not send the path to the dashboard's socket!"
+printf "$name" | nc -U /tmp/surprises_wbor_studio_dashboard.sock || fail "Could not send the name to the dashboard's socket!"
 
 log "Sent the surprise to the dashboard. Check the dashboard logs to see that the surprise was received."
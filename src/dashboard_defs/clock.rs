@@ -1,5 +1,7 @@
+use std::borrow::Cow;
+
 use crate::{
-	texture::TexturePool,
+	texture::{TexturePool, TextScrollParams},
 
 	window_tree::{
 		Window,
@@ -16,7 +18,10 @@ use crate::{
 		dynamic_optional::DynamicOptional
 	},
 
-	dashboard_defs::shared_window_state::SharedWindowState
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	}
 };
 
 use chrono::{Local, Timelike};
@@ -34,12 +39,13 @@ pub struct ClockHandConfig {
 	x_extent: f32,
 	minor_y_extent: f32,
 	major_y_extent: f32,
+	thickness: u8, // This is the outline thickness, in pixels
 	color: ColorSDL
 }
 
 impl ClockHandConfig {
-	pub const fn new(x_extent: f32, minor_y_extent: f32, major_y_extent: f32, color: ColorSDL) -> Self {
-		Self {x_extent, minor_y_extent, major_y_extent, color}
+	pub const fn new(x_extent: f32, minor_y_extent: f32, major_y_extent: f32, thickness: u8, color: ColorSDL) -> Self {
+		Self {x_extent, minor_y_extent, major_y_extent, thickness, color}
 	}
 
 	fn make_geometry(&self) -> RawClockHand {
@@ -58,18 +64,48 @@ impl ClockHandConfig {
 	}
 }
 
+// This is how the seconds hand moves (the milliseconds hand always sweeps)
+#[derive(serde::Deserialize, Copy, Clone, Default)]
+pub enum SecondHandMotion {
+	Ticking, // This snaps to each second
+
+	#[default]
+	Sweep // This moves smoothly, using the sub-second time
+}
+
 pub struct ClockHandConfigs {
 	pub milliseconds: ClockHandConfig,
 	pub seconds: ClockHandConfig,
 	pub minutes: ClockHandConfig,
-	pub hours: ClockHandConfig
+	pub hours: ClockHandConfig,
+	pub second_hand_motion: SecondHandMotion
+}
+
+/* This draws the 12 hour ticks (and optionally the hour numerals) without a dial texture, so that a plain
+background works as a dial. The radii are fractions of the clock's size (so at most 0.5), and the ticks are
+in the same square space as the hands, so they scale with the window. The numerals are child windows of the
+clock, so they only line up with the ticks when the clock window is square on the screen. */
+pub struct ProceduralDialConfig {
+	pub tick_inner_radius: f32,
+	pub tick_outer_radius: f32,
+	pub tick_thickness: u8,
+	pub tick_color: ColorSDL,
+
+	// If this is given, the numerals are centered at this radius, and drawn in this color
+	pub maybe_numerals: Option<(f32, ColorSDL)>
+}
+
+pub enum ClockDial<'a> {
+	Texture(&'a str),
+	Procedural(ProceduralDialConfig)
 }
 
 pub struct ClockHands {
 	milliseconds: RawClockHand,
 	seconds: RawClockHand,
 	minutes: RawClockHand,
-	hours: RawClockHand
+	hours: RawClockHand,
+	second_hand_motion: SecondHandMotion
 }
 
 impl ClockHands {
@@ -78,10 +114,14 @@ impl ClockHands {
 		top_left: Vec2f,
 		size: Vec2f,
 		hand_configs: ClockHandConfigs,
-		dial_texture_path: &str,
+		dial: ClockDial,
 		texture_pool: &mut TexturePool) -> GenericResult<(Self, Window)> {
 
 		fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+			/* The milliseconds hand stops while the app window is unfocused (since it is likely covered then, and the
+			event loop may be paused between frames), and the seconds hand ticks. The other hands always keep the time. */
+			let sweep_sub_seconds = params.window_is_focused;
+
 			let curr_time = Local::now();
 
 			let time_units: [(u32, u32); NUM_CLOCK_HANDS] = [
@@ -103,15 +143,24 @@ impl ClockHands {
 			let WindowContents::Many(all_contents) = params.window.get_contents_mut()
 			else {panic!("The clock's window contents was expected to be a list!")};
 
-			let WindowContents::Lines(rotated_hands) = &mut all_contents[1]
-			else {panic!("The second item in the clock's window contents was not a set of lines!")};
+			let WindowContents::ThickLines(rotated_hands) = &mut all_contents[1]
+			else {panic!("The second item in the clock's window contents was not a set of thick lines!")};
 
 			//////////
 
 			let mut prev_time_fract = 0.0;
 
-			for ((raw_hand, rotated_hand), time_unit) in
-				clock_hands_as_list.into_iter().zip(rotated_hands.iter_mut().rev()).zip(time_units) {
+			for (hand_index, ((raw_hand, rotated_hand), time_unit)) in
+				clock_hands_as_list.into_iter().zip(rotated_hands.iter_mut().rev()).zip(time_units).enumerate() {
+
+				if hand_index == 0 && !sweep_sub_seconds {
+					continue;
+				}
+
+				// A ticking seconds hand ignores the milliseconds (the hands after it then move in whole-second steps)
+				if hand_index == 1 && (!sweep_sub_seconds || matches!(clock_hands.second_hand_motion, SecondHandMotion::Ticking)) {
+					prev_time_fract = 0.0;
+				}
 
 				let time_fract = (time_unit.0 as f32 + prev_time_fract) / time_unit.1 as f32;
 				prev_time_fract = time_fract;
@@ -119,7 +168,7 @@ impl ClockHands {
 				let angle = time_fract * std::f32::consts::TAU;
 				let (cos_angle, sin_angle) = (angle.cos(), angle.sin());
 
-				rotated_hand.1.iter_mut().zip(&raw_hand.1).for_each(|(dest, raw)| {
+				rotated_hand.2.iter_mut().zip(&raw_hand.1).for_each(|(dest, raw)| {
 					*dest = Vec2f::new(
 						(raw.0 * cos_angle - raw.1 * sin_angle) + CLOCK_CENTER.0,
 						(raw.0 * sin_angle + raw.1 * cos_angle) + CLOCK_CENTER.1
@@ -132,7 +181,10 @@ impl ClockHands {
 
 		//////////
 
-		let texture_contents = WindowContents::make_texture_contents(dial_texture_path, texture_pool)?;
+		let (dial_contents, maybe_numeral_windows) = match dial {
+			ClockDial::Texture(path) => (WindowContents::make_texture_contents(path, texture_pool)?, None),
+			ClockDial::Procedural(config) => make_procedural_dial(&config)?
+		};
 
 		let clock_hand_configs_as_list: [&ClockHandConfig; NUM_CLOCK_HANDS] = [
 			&hand_configs.milliseconds, &hand_configs.seconds,
@@ -141,19 +193,19 @@ impl ClockHands {
 
 		let raw_clock_hands = clock_hand_configs_as_list.map(|config| config.make_geometry());
 
-		let line_contents = WindowContents::Lines(
-			raw_clock_hands.iter().rev().map(|(color, clock_hand)| {
-				(*color, vec![Vec2f::ZERO; clock_hand.len()])
+		let line_contents = WindowContents::ThickLines(
+			raw_clock_hands.iter().zip(clock_hand_configs_as_list).rev().map(|((color, clock_hand), config)| {
+				(*color, config.thickness, vec![Vec2f::ZERO; clock_hand.len()])
 			}).collect());
 
 		let clock_window = Window::new(
 			Some((updater_fn, update_rate)),
 			DynamicOptional::NONE,
-			WindowContents::Many(vec![texture_contents, line_contents]),
+			WindowContents::Many(vec![dial_contents, line_contents]),
 			None,
 			top_left,
 			size,
-			None
+			maybe_numeral_windows
 		);
 
 		Ok((
@@ -161,10 +213,101 @@ impl ClockHands {
 				milliseconds: raw_clock_hands[0].clone(),
 				seconds: raw_clock_hands[1].clone(),
 				minutes: raw_clock_hands[2].clone(),
-				hours: raw_clock_hands[3].clone()
+				hours: raw_clock_hands[3].clone(),
+				second_hand_motion: hand_configs.second_hand_motion
 			},
 
 			clock_window
 		))
 	}
 }
+
+//////////
+
+const NUM_HOURS_ON_DIAL: usize = 12;
+
+// This is the position on the dial for an hour, at a radius from the clock's center
+fn get_dial_position(hour: usize, radius: f32) -> (f32, f32) {
+	let angle = hour as f32 / NUM_HOURS_ON_DIAL as f32 * std::f32::consts::TAU;
+	(CLOCK_CENTER.0 + radius * angle.sin(), CLOCK_CENTER.1 - radius * angle.cos())
+}
+
+// This returns the tick contents, and the numeral windows (if numerals are enabled)
+fn make_procedural_dial(config: &ProceduralDialConfig) -> GenericResult<(WindowContents, Option<Vec<Window>>)> {
+	const MAX_RADIUS: f32 = 0.5;
+	const NUMERAL_WINDOW_SIZE: f32 = 0.14;
+	const NUMERALS: [&str; NUM_HOURS_ON_DIAL] = ["12", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11"];
+
+	let radius_is_valid = |radius: f32| (0.0..=MAX_RADIUS).contains(&radius);
+
+	if !radius_is_valid(config.tick_inner_radius) || !radius_is_valid(config.tick_outer_radius)
+		|| config.tick_inner_radius > config.tick_outer_radius {
+
+		return error_msg!("The procedural clock dial's tick radii should be between 0 and {MAX_RADIUS}, \
+			with the inner radius at most the outer one (they were {} and {})", config.tick_inner_radius, config.tick_outer_radius);
+	}
+
+	if let Some((numeral_radius, _)) = config.maybe_numerals {
+		if !radius_is_valid(numeral_radius) {
+			return error_msg!("The procedural clock dial's numeral radius should be between 0 and {MAX_RADIUS} (it was {numeral_radius})");
+		}
+	}
+
+	let ticks = (0..NUM_HOURS_ON_DIAL).map(|hour| {
+		let [inner, outer] = [config.tick_inner_radius, config.tick_outer_radius].map(|radius| {
+			let (x, y) = get_dial_position(hour, radius);
+			Vec2f::new(x, y)
+		});
+
+		(config.tick_color, config.tick_thickness, vec![inner, outer])
+	}).collect();
+
+	//////////
+
+	#[derive(Clone)]
+	struct ClockNumeralState(&'static str);
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for ClockNumeralState {
+		// The numeral only needs to be made once
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			matches!(updater_params.window.get_contents(), WindowContents::Texture(_))
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), "")
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(self.0)
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			window_contents
+		}
+	}
+
+	let maybe_numeral_windows = config.maybe_numerals.map(|(numeral_radius, numeral_color)| {
+		NUMERALS.iter().enumerate().map(|(hour, &numeral)| {
+			let (x, y) = get_dial_position(hour, numeral_radius);
+			let max_top_left = 1.0 - NUMERAL_WINDOW_SIZE;
+			let to_top_left = |center: f32| (center - NUMERAL_WINDOW_SIZE * 0.5).clamp(0.0, max_top_left);
+
+			let fields = updatable_text_pattern::UpdatableTextWindowFields {
+				inner: ClockNumeralState(numeral),
+				text_color: numeral_color,
+				scroll_fn: |_, _, _| (0.0, false),
+				scroll_params: TextScrollParams::DEFAULT,
+				update_rate: UpdateRate::ALMOST_NEVER,
+				maybe_border_color: None
+			};
+
+			updatable_text_pattern::make_window(fields,
+				Vec2f::new(to_top_left(x), to_top_left(y)),
+				Vec2f::new_scalar(NUMERAL_WINDOW_SIZE),
+				WindowContents::Nothing
+			)
+		}).collect()
+	});
+
+	Ok((WindowContents::ThickLines(ticks), maybe_numeral_windows))
+}
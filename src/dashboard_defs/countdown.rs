@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+use chrono::Timelike;
+
+use crate::{
+	texture::{DisplayText, TextDisplayInfo, TextScrollParams, TextMargins, TextureCreationInfo},
+
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		update_rate::UpdateRate,
+		dynamic_optional::DynamicOptional
+	},
+
+	window_tree::{
+		Window,
+		ColorSDL,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::shared_window_state::SharedWindowState
+};
+
+//////////
+
+/* This is a countdown to the next show change boundary, so that DJs can see how long they have left.
+Shows can only be scheduled on half-hour boundaries (which is also when the Spinitron state looks for a new show),
+so that is the default boundary. The boundaries are counted from local midnight. */
+#[derive(serde::Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct CountdownConfig {
+	boundary_minutes: u32, // E.g. 30 for every half hour, or 60 for the top of each hour
+	warning_secs: u32 // The countdown changes to the warning color when there is less than this much time left
+}
+
+impl Default for CountdownConfig {
+	fn default() -> Self {
+		Self {boundary_minutes: 30, warning_secs: 120}
+	}
+}
+
+impl CountdownConfig {
+	fn get_secs_until_next_boundary(self) -> u32 {
+		let boundary_secs = self.boundary_minutes.max(1) * 60;
+		let secs_since_midnight = chrono::Local::now().num_seconds_from_midnight();
+		boundary_secs - secs_since_midnight % boundary_secs
+	}
+}
+
+pub fn make_countdown_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
+	config: CountdownConfig, text_color: ColorSDL, warning_text_color: ColorSDL) -> Window {
+
+	struct CountdownWindowState {
+		config: CountdownConfig,
+		text_color: ColorSDL,
+		warning_text_color: ColorSDL,
+		maybe_secs_left: Option<u32> // This is what the countdown was made from last
+	}
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let state = params.window.get_state_mut::<CountdownWindowState>();
+		let secs_left = state.config.get_secs_until_next_boundary();
+
+		if state.maybe_secs_left == Some(secs_left) {
+			return Ok(());
+		}
+
+		state.maybe_secs_left = Some(secs_left);
+
+		let text_color = if secs_left <= state.config.warning_secs {state.warning_text_color} else {state.text_color};
+		let text = format!("{}:{:02}", secs_left / 60, secs_left % 60);
+
+		let texture_creation_info = TextureCreationInfo::Text((
+			Cow::Borrowed(inner_shared_state.font_info),
+
+			TextDisplayInfo {
+				text: DisplayText::new(&text),
+				color: text_color.into(),
+				maybe_outline_color: None,
+				pixel_area: params.area_drawn_to_screen,
+				scroll_fn: |_, _, _| (0.0, false),
+				scroll_params: TextScrollParams::DEFAULT,
+				margins: TextMargins::NONE
+			}
+		));
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	Window::new(
+		Some((updater_fn, update_rate)),
+
+		DynamicOptional::new(CountdownWindowState {
+			config,
+			text_color,
+			warning_text_color,
+			maybe_secs_left: None
+		}),
+
+		WindowContents::Nothing,
+		None,
+		top_left,
+		size,
+		None
+	)
+}
@@ -1,8 +1,11 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, process::Command, sync::OnceLock};
 
 use crate::{
+	texture::TextScrollParams,
+
 	utility_types::{
 		vec2f::Vec2f,
+		generic_result::*,
 		update_rate::UpdateRate
 	},
 
@@ -19,10 +22,73 @@ use crate::{
 	}
 };
 
+fn run_git_command(args: &[&str]) -> GenericResult<String> {
+	let output = Command::new("git").args(args).output().context("Could not run git")?;
+
+	if !output.status.success() {
+		return error_msg!("'git {}' failed: '{}'", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+	}
+
+	Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+pub struct GitVersionInfo {
+	pub branch: String,
+	pub commit_count: String
+}
+
+/* This is looked up with git the first time that it is needed, and then cached (since it does not change while running).
+If git is not installed, or if the app is not being run from a git repo, the parts that could not be found are "unknown"
+(and a warning is logged). */
+pub fn get_git_version_info() -> &'static GitVersionInfo {
+	static GIT_VERSION_INFO: OnceLock<GitVersionInfo> = OnceLock::new();
+
+	GIT_VERSION_INFO.get_or_init(|| {
+		let [branch, commit_count] = [["rev-parse", "--abbrev-ref", "HEAD"], ["rev-list", "--count", "HEAD"]].map(|args|
+			run_git_command(&args).unwrap_or_else(|err| {
+				log::warn!("Could not look up part of the git version info, so it will be shown as unknown: '{err}'.");
+				"unknown".to_owned()
+			})
+		);
+
+		GitVersionInfo {branch, commit_count}
+	})
+}
+
+/* This expands the variables in a credit text template (once, when the credit window is made). The variables are:
+- `{branch}` and `{commit_count}`, from git (see above)
+- `{version}`, which is the version of the app
+- `{theme}`, which is the name of the current theme.
+Unknown variables are left empty (and a warning is logged). A `{` without a matching `}` is kept as it is. */
+pub fn expand_credit_template(template: &str, theme_name: &str) -> String {
+	let mut expanded = String::new();
+	let mut rest = template;
+
+	while let Some(var_start) = rest.find('{') {
+		let Some(var_length) = rest[var_start..].find('}') else {break};
+
+		expanded += &rest[..var_start];
+		let var_name = &rest[var_start + 1..var_start + var_length];
+
+		match var_name {
+			"branch" => expanded += &get_git_version_info().branch,
+			"commit_count" => expanded += &get_git_version_info().commit_count,
+			"version" => expanded += env!("CARGO_PKG_VERSION"),
+			"theme" => expanded += theme_name,
+			_ => log::warn!("Unknown variable '{{{var_name}}}' in the credit text template! Leaving it empty.")
+		}
+
+		rest = &rest[var_start + var_length + 1..];
+	}
+
+	expanded + rest
+}
+
 pub fn make_credit_window(top_left: Vec2f, size: Vec2f,
-	border_color: ColorSDL, text_color: ColorSDL, text: &'static str) -> Window {
+	border_color: ColorSDL, text_color: ColorSDL, text: String) -> Window {
 
-	type CreditWindowState = &'static str;
+	#[derive(Clone)]
+	struct CreditWindowState(String);
 
 	impl updatable_text_pattern::UpdatableTextWindowMethods for CreditWindowState {
 		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
@@ -37,7 +103,7 @@ pub fn make_credit_window(top_left: Vec2f, size: Vec2f,
 		}
 
 		fn extract_text(&self) -> Cow<str> {
-			Cow::Borrowed(self)
+			Cow::Borrowed(&self.0)
 		}
 
 		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
@@ -46,9 +112,10 @@ pub fn make_credit_window(top_left: Vec2f, size: Vec2f,
 	}
 
 	let fields = updatable_text_pattern::UpdatableTextWindowFields {
-		inner: text,
+		inner: CreditWindowState(text),
 		text_color,
-		scroll_fn: |seed, _| ((seed * 5.0).sin() * 0.5 + 0.5, false),
+		scroll_fn: |seed, _, _| ((seed * 5.0).sin() * 0.5 + 0.5, false),
+		scroll_params: TextScrollParams::DEFAULT,
 		update_rate: UpdateRate::ALMOST_NEVER,
 		maybe_border_color: Some(border_color)
 	};
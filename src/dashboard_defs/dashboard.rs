@@ -1,14 +1,17 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::OnceLock};
 
 use chrono::Duration;
 use sdl2::{render::BlendMode, ttf::{FontStyle, Hinting}};
 
 use crate::{
-	texture::{FontInfo, TextureCreationInfo, TexturePool},
-	spinitron::{model::SpinitronModelName, state::SpinitronState},
+	palette::Palette,
+	texture::{FontInfo, FontSource, TextFill, TextScrollParams, TextureCreationInfo, TexturePool},
+	spinitron::{model::{self, SpinitronModelName}, state::SpinitronState},
 
 	utility_types::{
 		json_utils,
+		easing_fns,
+		error_state::ErrorState,
 		vec2f::Vec2f,
 		generic_result::*,
 		dynamic_optional::DynamicOptional,
@@ -19,18 +22,40 @@ use crate::{
 		ColorSDL,
 		Window,
 		WindowContents,
+		GradientDirection,
+		WindowBorderRadius,
+		LazyTextureSource,
+		WindowUpdaterParams,
+		FadeThroughTransition,
 		PossibleSharedWindowStateUpdater
 	},
 
 	dashboard_defs::{
-		error::make_error_window,
-		credit::make_credit_window,
-		weather::make_weather_window,
-		shared_window_state::SharedWindowState,
-		twilio::{make_twilio_window, TwilioState},
-		surprise::{make_surprise_window, SurpriseCreationInfo},
-		clock::{ClockHandConfig, ClockHandConfigs, ClockHands},
-		spinitron::{make_spinitron_windows, SpinitronModelWindowInfo, SpinitronModelWindowsInfo}
+		error::{make_error_window, ErrorPulseParams, ErrorWindowAppearance},
+		credit::{make_credit_window, expand_credit_template},
+		motd::{make_motd_window, MotdConfig},
+		countdown::{make_countdown_window, CountdownConfig},
+		json_field::{make_json_field_window, JsonFieldWindowConfig},
+		on_air::{make_on_air_window, OnAirAppearance, ON_AIR_IS_LIVE_FACT},
+		weather::{self, make_weather_window, make_weather_alert_window, WeatherState, WeatherUnits, WeatherExtraFields},
+		staleness::{make_staleness_window, StalenessSource},
+		drifting::make_drifting_window,
+		loading::{make_loading_placeholder_window, LoadingPlaceholder},
+		shared_window_state::{SharedWindowState, SharedFacts},
+		twilio::{make_twilio_window, make_twilio_focus_window, TwilioState, TwilioWindowAppearance},
+		surprise::{make_surprise_window, trigger_surprise_artificially, NumAppearingSurprises, SurpriseChance, SurpriseCreationInfo},
+		clock::{ClockDial, ClockHandConfig, ClockHandConfigs, ClockHands, ProceduralDialConfig, SecondHandMotion},
+		spinitron::{
+			make_spinitron_windows,
+			make_previous_persona_window,
+			make_spin_badges_window,
+			make_spin_progress_window,
+			make_now_playing_ticker_window,
+			SpinTextPart,
+			SpinTextLineInfo,
+			SpinitronModelWindowInfo,
+			SpinitronModelWindowsInfo
+		}
 	}
 };
 
@@ -41,27 +66,349 @@ use crate::{
 - Make plain texture creation less verbose through a wrapper function
 */
 
+/* Any of these can be given as `"${SOME_ENV_VAR}"`, to read it from the environment instead.
+The keys for disabled dashboard components can be left out. */
 #[derive(serde::Deserialize)]
 struct ApiKeys {
+	#[serde(default, deserialize_with = "json_utils::deserialize_with_env_var")]
 	spinitron: String,
-	openweathermap: String,
+
+	// The weather window and weather alerts are empty if this is not given
+	#[serde(default, deserialize_with = "json_utils::deserialize_maybe_with_env_var")]
+	maybe_tomorrow_io: Option<String>,
+
+	#[serde(default, deserialize_with = "json_utils::deserialize_with_env_var")]
 	twilio_account_sid: String,
+
+	#[serde(default, deserialize_with = "json_utils::deserialize_with_env_var")]
 	twilio_auth_token: String
 }
 
+impl ApiKeys {
+	// This is for the keys of enabled components, which are needed
+	fn get_needed<'a>(key: &'a str, key_name: &str) -> GenericResult<&'a str> {
+		if key.is_empty() {
+			return error_msg!("The '{key_name}' API key is needed, since its dashboard component is enabled");
+		}
+
+		Ok(key)
+	}
+}
+
+/* Components can be turned off without recompiling by giving this file (it is optional, and any components
+left out of it are on). A disabled component's windows are never made, and its continual updater thread
+(if it has one) is never started, so it costs nothing at runtime. The components are independent of each other,
+except that the on-air window is a part of the Spinitron component (since it is based on the current playlist).
+The clock, the error window, and the static textures are always shown. */
+const COMPONENTS_CONFIG_PATH: &str = "assets/dashboard_components.json";
+
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct DashboardComponents {
+	spinitron: bool, // The Spinitron model windows, the now-playing ticker, the spin progress bar, the on-air window, and so on
+	twilio: bool, // The message history window
+	weather: bool, // The weather window and the weather alert window
+	surprise: bool, // The surprise window (and its triggering socket)
+	credit: bool,
+	motd: bool, // The message of the day window (which shows nothing until its file has some text, and it is off by default)
+	countdown: bool, // The countdown to the next show change boundary
+	drifting_ness: bool // This makes Ness drift around the bottom of the screen, rather than stand still (it is off by default)
+}
+
+impl Default for DashboardComponents {
+	fn default() -> Self {
+		Self {spinitron: true, twilio: true, weather: true, surprise: true, credit: true, motd: false, countdown: true, drifting_ness: false}
+	}
+}
+
+/* Fonts can be swapped without recompiling by giving their paths in this file (it is optional).
+The default fonts can also be used from the copies embedded in the binary, rather than from their paths. */
+const FONT_OVERRIDE_PATH: &str = "assets/font_override.json";
+
+#[derive(serde::Deserialize)]
+#[serde(default)]
+struct FontOverride {
+	path: String,
+	unusual_chars_fallback_path: String,
+	use_embedded_default_fonts: bool, // If this is set, the two paths above are not used
+
+	// This logs which common Unicode blocks the overriding fonts are missing, when they are first loaded
+	validate_font_coverage: bool
+}
+
+impl Default for FontOverride {
+	fn default() -> Self {
+		Self {
+			path: FONT_PATH.to_owned(),
+			unusual_chars_fallback_path: UNUSUAL_CHARS_FALLBACK_FONT_PATH.to_owned(),
+			use_embedded_default_fonts: false,
+			validate_font_coverage: false
+		}
+	}
+}
+
+// The easing for window fades can be changed without recompiling by naming it in this file (it is optional)
+const TRANSITION_CONFIG_PATH: &str = "assets/transition_config.json";
+
+#[derive(serde::Deserialize)]
+struct TransitionConfig {
+	fade_easing: String // This is the name of an easing function (e.g. "ease_in_out_sine")
+}
+
+/* The spin text can be shown as separately styled song, artist, and release lines (over the bottom of the spin art),
+rather than as one combined line under it, by giving this file (it is optional). The spin title can also be given
+a vertical gradient through it (otherwise, it is a solid color). */
+const SPIN_TEXT_LAYOUT_CONFIG_PATH: &str = "assets/spin_text_layout.json";
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct SpinTextLayoutConfig {
+	separate_lines: bool,
+	gradient_title: bool
+}
+
+/* The weather units and extra fields can be changed through this file (it is optional, and any of its fields can be
+left out). By default, the units are imperial, and no extra fields are shown. */
+const WEATHER_CONFIG_PATH: &str = "assets/weather_config.json";
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct WeatherConfig {
+	units: WeatherUnits,
+	extra_fields: WeatherExtraFields // E.g. `{"feels_like": true, "humidity": false, "wind": true}`
+}
+
+/* The clock's seconds hand motion, and its dial, can be changed without recompiling through this file (it is optional).
+With a procedural dial, the hour ticks and numerals are drawn over the top bar, rather than the watch dial texture. */
+const CLOCK_CONFIG_PATH: &str = "assets/clock_config.json";
+
+#[derive(serde::Deserialize, Default)]
+#[serde(default)]
+struct ClockConfig {
+	second_hand_motion: SecondHandMotion, // This is either "Ticking" or "Sweep" (which is the default)
+	procedural_dial: bool
+}
+
+// The message of the day's file path and refresh interval can be changed through this file (it is optional)
+const MOTD_CONFIG_PATH: &str = "assets/motd_config.json";
+
+// The countdown's boundary and warning threshold can be changed through this file (it is optional)
+const COUNTDOWN_CONFIG_PATH: &str = "assets/countdown_config.json";
+
+/* Windows that show a field from a JSON endpoint (like a donation total) can be added through this file (it is optional).
+It is a list of JSON field window configs (see `json_field.rs`), and the windows are drawn over the other main windows. */
+const JSON_FIELD_WINDOWS_CONFIG_PATH: &str = "assets/json_field_windows.json";
+
+// The credit text can be changed without recompiling by giving a template for it in this file (it is optional)
+const CREDIT_CONFIG_PATH: &str = "assets/credit_config.json";
+
+#[derive(serde::Deserialize)]
+struct CreditConfig {
+	template: String // See `expand_credit_template` for the variables that this can have
+}
+
+const DEFAULT_CREDIT_TEMPLATE: &str = "By: Caspian Ahlberg ({branch} #{commit_count})";
+
+pub const THEME_NAME: &str = "standard";
+
+// Surprises can be triggered by sending their name to this socket
+const SURPRISE_TRIGGERING_SOCKET_PATH: &str = "/tmp/surprises_wbor_studio_dashboard.sock";
+
+////////// Asset paths (all of these are listed in `get_required_assets`, so that they are checked before the dashboard is made)
+
+const FONT_PATH: &str = "assets/unifont/unifont-15.1.05.otf";
+const UNUSUAL_CHARS_FALLBACK_FONT_PATH: &str = "assets/unifont/unifont_upper-15.1.05.otf";
+const FALLBACK_TEXTURE_PATH: &str = "assets/no_texture_available.png";
+const TEXT_BUBBLE_PATH: &str = "assets/text_bubble.png";
+const WATCH_DIAL_PATH: &str = "assets/watch_dial.png";
+
+const LOGO_PATH: &str = "assets/logo.png";
+const SOUP_PATH: &str = "assets/soup.png";
+const NESS_PATH: &str = "assets/ness.bmp";
+const LOADING_SPINNER_PATH: &str = "assets/polar_headphones_logo.png";
+
+// These are embedded in the binary (so they are not listed in `get_required_assets`), since the dashboard is built around them
+const BOOKSHELF_BYTES: &[u8] = include_bytes!("../../assets/dashboard_bookshelf.png");
+const FOREGROUND_BYTES: &[u8] = include_bytes!("../../assets/dashboard_foreground.png");
+
+// These are the default fonts, which are only used from here if the font override asks for it
+const FONT_BYTES: &[u8] = include_bytes!("../../assets/unifont/unifont-15.1.05.otf");
+const UNUSUAL_CHARS_FALLBACK_FONT_BYTES: &[u8] = include_bytes!("../../assets/unifont/unifont_upper-15.1.05.otf");
+
+const NATHAN_SURPRISE_PATH: &str = "assets/nathan.png";
+const JUMPSCARE_SURPRISE_PATH: &str = "assets/jumpscare.png";
+const HORRIBLE_SURPRISE_PATH: &str = "assets/horrible.webp";
+
 //////////
 
+fn load_components() -> GenericResult<DashboardComponents> {
+	if std::path::Path::new(COMPONENTS_CONFIG_PATH).exists() {
+		json_utils::load_from_file(COMPONENTS_CONFIG_PATH)
+	}
+	else {
+		Ok(DashboardComponents::default())
+	}
+}
+
+fn load_maybe_font_override() -> GenericResult<Option<FontOverride>> {
+	if std::path::Path::new(FONT_OVERRIDE_PATH).exists() {
+		json_utils::load_from_file(FONT_OVERRIDE_PATH).map(Some)
+	}
+	else {
+		Ok(None)
+	}
+}
+
+/* This lists every asset that the dashboard loads from a fixed path (with the current font override and components),
+so that they can all be checked to exist at once. Assets for disabled components are left out. */
+pub fn get_required_assets() -> GenericResult<Vec<String>> {
+	let components = load_components()?;
+
+	let mut paths = match load_maybe_font_override()? {
+		Some(font_override) if font_override.use_embedded_default_fonts => Vec::new(),
+		Some(font_override) => vec![font_override.path, font_override.unusual_chars_fallback_path],
+		None => vec![FONT_PATH.to_owned(), UNUSUAL_CHARS_FALLBACK_FONT_PATH.to_owned()]
+	};
+
+	let enabled_paths: [(bool, &[&str]); 4] = [
+		(true, &[FALLBACK_TEXTURE_PATH, WATCH_DIAL_PATH, LOGO_PATH, SOUP_PATH, NESS_PATH]),
+		(components.twilio, &[TEXT_BUBBLE_PATH, LOADING_SPINNER_PATH]),
+		(components.spinitron, &model::PLACEHOLDER_IMAGE_PATHS),
+		(components.surprise, &[NATHAN_SURPRISE_PATH, JUMPSCARE_SURPRISE_PATH, HORRIBLE_SURPRISE_PATH])
+	];
+
+	paths.extend(enabled_paths.into_iter().filter(|(enabled, _)| *enabled)
+		.flat_map(|(_, paths)| paths.iter().map(|path| path.to_string())));
+
+	if components.weather {
+		paths.extend(weather::get_weather_icon_paths());
+	}
+
+	Ok(paths)
+}
+
+/* This bounds how many Spinitron model textures are requested at once. It can only be set at startup
+(before the dashboard is made), and otherwise, it is set to its default when the dashboard is first made. */
+static MAX_CONCURRENT_SPINITRON_TEXTURE_REQUESTS: OnceLock<usize> = OnceLock::new();
+
+pub fn limit_concurrent_spinitron_texture_requests(max_concurrent_texture_requests: usize) -> MaybeError {
+	if max_concurrent_texture_requests == 0 {
+		return error_msg!("The maximum number of concurrent Spinitron texture requests should be above zero");
+	}
+
+	MAX_CONCURRENT_SPINITRON_TEXTURE_REQUESTS.set(max_concurrent_texture_requests).ok()
+		.context("The concurrent Spinitron texture request limit was already set")?;
+
+	log::info!("At most {max_concurrent_texture_requests} Spinitron textures will be requested at once.");
+	Ok(())
+}
+
+pub fn trigger_surprise(surprise_name: &str) -> MaybeError {
+	trigger_surprise_artificially(SURPRISE_TRIGGERING_SOCKET_PATH, surprise_name)
+}
+
+/* This is used to keep the screen dimmer from covering surprises. This and the functions below allow for
+the shared window state to be empty, which is the case while a theme that failed to be made is retried. */
+pub fn is_surprise_appearing(shared_window_state: &DynamicOptional) -> bool {
+	shared_window_state.try_get::<SharedWindowState>().is_some_and(|inner| inner.num_appearing_surprises.get() != 0)
+}
+
+#[derive(Copy, Clone)]
+pub enum RefreshableSource {
+	Spinitron,
+	Twilio
+}
+
+/* This makes a data source fetch its data again right away. Until the new data arrives,
+`is_refresh_pending` is true, and the shared window state should be updated on every frame. */
+pub fn request_refresh(shared_window_state: &mut DynamicOptional, source: RefreshableSource) -> MaybeError {
+	let inner_shared_state = shared_window_state.try_get_mut::<SharedWindowState>().context("The dashboard has not been made")?;
+
+	match source {
+		RefreshableSource::Spinitron => inner_shared_state.maybe_spinitron_state.as_mut()
+			.context("Spinitron is not enabled")?.request_refresh(),
+
+		RefreshableSource::Twilio => inner_shared_state.maybe_twilio_state.as_mut()
+			.context("Twilio is not enabled")?.request_refresh()
+	}
+
+	Ok(())
+}
+
+pub fn is_refresh_pending(shared_window_state: &DynamicOptional) -> bool {
+	let Some(inner_shared_state) = shared_window_state.try_get::<SharedWindowState>() else {return false};
+
+	inner_shared_state.maybe_spinitron_state.as_ref().is_some_and(SpinitronState::is_refresh_pending)
+		|| inner_shared_state.maybe_twilio_state.as_ref().is_some_and(TwilioState::is_refresh_pending)
+}
+
+const SPINITRON_JSON_DUMP_DIRECTORY: &str = "spinitron_json_dumps";
+
+/* This saves the last raw Spinitron response for each model type to a timestamped text file, and returns its path.
+The responses are only there if raw JSON retention was turned on at startup (and each one may be cut off). */
+pub fn save_spinitron_json_dump(shared_window_state: &DynamicOptional) -> GenericResult<String> {
+	let inner_shared_state = shared_window_state.try_get::<SharedWindowState>().context("The dashboard has not been made")?;
+	let spinitron_state = inner_shared_state.maybe_spinitron_state.as_ref().context("Spinitron is not enabled")?;
+
+	let model_names = [
+		(SpinitronModelName::Spin, "Spin"), (SpinitronModelName::Playlist, "Playlist"),
+		(SpinitronModelName::Persona, "Persona"), (SpinitronModelName::Show, "Show")
+	];
+
+	let dump = model_names.into_iter().map(|(model_name, description)| {
+		let raw_json = spinitron_state.get_last_raw_model_json(model_name);
+		format!("========== {description} ==========\n{}\n", raw_json.as_deref().unwrap_or("<Not kept>"))
+	}).collect::<Vec<_>>().join("\n");
+
+	std::fs::create_dir_all(SPINITRON_JSON_DUMP_DIRECTORY)?;
+
+	let path = format!("{SPINITRON_JSON_DUMP_DIRECTORY}/spinitron_json_{}.txt", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+	std::fs::write(&path, dump)?;
+
+	Ok(path)
+}
+
+/* The fallback textures are what is used when everything else fails, so they are all
+checked to be loadable when the dashboard is made (rather than failing later on) */
+fn validate_fallback_texture_creation_infos(fallback_texture_creation_infos: &[TextureCreationInfo]) -> MaybeError {
+	use sdl2::image::LoadSurface;
+
+	if fallback_texture_creation_infos.is_empty() {
+		return error_msg!("At least one fallback texture is needed");
+	}
+
+	for fallback_texture_creation_info in fallback_texture_creation_infos {
+		let TextureCreationInfo::Path(path) = fallback_texture_creation_info else {
+			return error_msg!("Fallback textures should be loaded from paths");
+		};
+
+		sdl2::surface::Surface::from_file(path as &str).to_generic()
+			.context(format!("Could not load the fallback texture at '{path}'"))?;
+	}
+
+	Ok(())
+}
+
+fn choose_fallback_texture_creation_info<'a>(fallback_texture_creation_infos: &'a [TextureCreationInfo<'a>],
+	rand_generator: &mut rand::rngs::ThreadRng) -> GenericResult<&'a TextureCreationInfo<'a>> {
+
+	use rand::seq::SliceRandom;
+	fallback_texture_creation_infos.choose(rand_generator).context("There are no fallback textures to choose from")
+}
+
 // This returns a top-level window, shared window state, and a shared window state updater
 pub fn make_dashboard(
 	texture_pool: &mut TexturePool,
-	update_rate_creator: UpdateRateCreator)
+	update_rate_creator: UpdateRateCreator,
+	palette: Palette,
+	mut prev_shared_window_state: DynamicOptional)
 	-> GenericResult<(Window, DynamicOptional, PossibleSharedWindowStateUpdater)> {
 
 	////////// Defining some shared global variables
 
 	const FONT_INFO: FontInfo = FontInfo {
-		path: "assets/unifont/unifont-15.1.05.otf",
-		unusual_chars_fallback_path: "assets/unifont/unifont_upper-15.1.05.otf",
+		source: FontSource::Path(Cow::Borrowed(FONT_PATH)),
+		unusual_chars_fallback_source: FontSource::Path(Cow::Borrowed(UNUSUAL_CHARS_FALLBACK_FONT_PATH)),
 
 		/* Providing this function instead of the variant below since
 		`font.find_glyph` is buggy for the Rust sdl2::ttf bindings */
@@ -70,16 +417,78 @@ pub fn make_dashboard(
 
 		style: FontStyle::NORMAL,
 		hinting: Hinting::Normal,
-		maybe_outline_width: None
+		maybe_outline_width: None,
+		validate_font_coverage: false
+	};
+
+	/* The shared window state needs a static reference to the font info, so the overriding font info is made once,
+	and kept for the rest of the program (so changing the font override needs a restart, rather than a theme switch) */
+	static OVERRIDING_FONT_INFO: OnceLock<FontInfo> = OnceLock::new();
+
+	let font_info: &'static FontInfo = if let Some(font_override) = load_maybe_font_override()? {
+		OVERRIDING_FONT_INFO.get_or_init(|| {
+			let (source, unusual_chars_fallback_source) = if font_override.use_embedded_default_fonts {
+				log::info!("Using the default fonts embedded in the binary.");
+				(FontSource::Bytes(FONT_BYTES), FontSource::Bytes(UNUSUAL_CHARS_FALLBACK_FONT_BYTES))
+			}
+			else {
+				log::info!("Using the font at '{}' (with the fallback font at '{}').", font_override.path, font_override.unusual_chars_fallback_path);
+				(FontSource::Path(Cow::Owned(font_override.path)), FontSource::Path(Cow::Owned(font_override.unusual_chars_fallback_path)))
+			};
+
+			FontInfo {
+				source, unusual_chars_fallback_source,
+				validate_font_coverage: font_override.validate_font_coverage,
+				..FONT_INFO
+			}
+		})
+	}
+	else {
+		&FONT_INFO
 	};
 
+	// One of these is chosen at random whenever a fallback texture is needed (more variants can be added here)
+	const FALLBACK_TEXTURE_CREATION_INFOS: [TextureCreationInfo<'static>; 1] = [
+		TextureCreationInfo::Path(Cow::Borrowed(FALLBACK_TEXTURE_PATH))
+	];
+
+	validate_fallback_texture_creation_infos(&FALLBACK_TEXTURE_CREATION_INFOS)?;
+
+	let mut rand_generator = rand::thread_rng();
+	let fallback_texture_creation_info = choose_fallback_texture_creation_info(&FALLBACK_TEXTURE_CREATION_INFOS, &mut rand_generator)?;
+	texture_pool.set_fallback_texture_creation_info(fallback_texture_creation_info);
+
 	let top_bar_window_size_y = 0.1;
 	let main_windows_gap_size = 0.01;
 
-	let theme_color_1 = ColorSDL::RGB(249, 236, 210);
+	// The dark palette is for after sunset, so it is dimmer, but with the same hues
+	let (theme_color_1, top_bar_gradient_start, top_bar_gradient_end) = match palette {
+		Palette::Light => (ColorSDL::RGB(249, 236, 210), ColorSDL::RGB(128, 0, 32), ColorSDL::RGB(90, 0, 22)),
+		Palette::Dark => (ColorSDL::RGB(165, 155, 135), ColorSDL::RGB(70, 0, 18), ColorSDL::RGB(45, 0, 11))
+	};
 	let shared_update_rate = update_rate_creator.new_instance(15.0);
 	let api_keys: ApiKeys = json_utils::load_from_file("assets/api_keys.json")?;
 
+	let components = load_components()?;
+
+	/* When the dashboard is remade (like for a palette change), the Spinitron and weather states are kept from
+	the previous shared window state (so Spinitron's blocking requests are not made again, and no data is lost).
+	They hold no textures, so the texture pool being cleared does not affect them. The Twilio state does hold
+	textures (with the old text color), so it is made again. */
+	let (maybe_prev_spinitron_state, maybe_prev_weather_state) = prev_shared_window_state.try_get_mut::<SharedWindowState>()
+		.map_or((None, None), |prev| (prev.maybe_spinitron_state.take(), prev.maybe_weather_state.take()));
+
+	// The rest of it is dropped now, so that its resources are freed before being remade
+	drop(prev_shared_window_state);
+
+	let fade_easing_fn = if std::path::Path::new(TRANSITION_CONFIG_PATH).exists() {
+		let transition_config: TransitionConfig = json_utils::load_from_file(TRANSITION_CONFIG_PATH)?;
+		easing_fns::get_by_name(&transition_config.fade_easing).context("Invalid transition config")?
+	}
+	else {
+		easing_fns::ease_in_out_sine
+	};
+
 	////////// Defining the Spinitron window extents
 
 	// Note: `tl` = top left
@@ -98,6 +507,22 @@ pub fn make_dashboard(
 	let show_size = Vec2f::new_scalar(1.0 - show_tl.x() - main_windows_gap_size);
 
 	let show_text_tl = Vec2f::translate(&(spin_tl + spin_size), 0.03, -0.2);
+
+	let spin_text_layout_config = if std::path::Path::new(SPIN_TEXT_LAYOUT_CONFIG_PATH).exists() {
+		json_utils::load_from_file::<SpinTextLayoutConfig>(SPIN_TEXT_LAYOUT_CONFIG_PATH)?
+	}
+	else {
+		SpinTextLayoutConfig::default()
+	};
+
+	let use_separate_spin_text_lines = spin_text_layout_config.separate_lines;
+
+	let spin_title_text_fill = if spin_text_layout_config.gradient_title {
+		TextFill::Gradient {top: theme_color_1, bottom: ColorSDL::RGB(210, 180, 140)}
+	}
+	else {
+		TextFill::Solid(theme_color_1)
+	};
 	let show_text_size = Vec2f::new(0.37, 0.05);
 
 	// TODO: make a type for the top-left/size combo (and add useful utility functions from there)
@@ -107,115 +532,310 @@ pub fn make_dashboard(
 	let all_model_windows_info = [
 		SpinitronModelWindowsInfo {
 			model_name: SpinitronModelName::Spin,
-			text_color: theme_color_1,
+			text_fill: spin_title_text_fill,
 
 			texture_window: Some(SpinitronModelWindowInfo {
 				tl: spin_tl,
 				size: spin_size,
-				border_color: Some(theme_color_1)
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: Some(0.04),
+				maybe_background_color: None
 			}),
 
-			text_window: Some(SpinitronModelWindowInfo {
+			text_window: (!use_separate_spin_text_lines).then_some(SpinitronModelWindowInfo {
 				tl: Vec2f::translate_y(&spin_tl, spin_size.y()),
 				size: Vec2f::new(spin_size.x(), spin_text_height),
-				border_color: Some(theme_color_1)
-			})
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: None,
+				maybe_background_color: None
+			}),
+
+			// These are stacked over the bottom of the spin art, going from the biggest line to the smallest
+			spin_text_lines: if use_separate_spin_text_lines {
+				[
+					(SpinTextPart::Song, 0.04, spin_title_text_fill),
+					(SpinTextPart::Artist, 0.03, theme_color_1.into()),
+					(SpinTextPart::Release, 0.025, ColorSDL::RGB(210, 180, 140).into())
+				].into_iter().scan(spin_size.y() - 0.1, |line_y, (part, line_height, text_fill)| {
+					let line = SpinTextLineInfo {
+						part,
+
+						window: SpinitronModelWindowInfo {
+							tl: Vec2f::translate(&spin_tl, 0.01, *line_y),
+							size: Vec2f::new(spin_size.x() - 0.02, line_height),
+							border_color: None,
+							maybe_corner_radius: None,
+							maybe_background_color: None
+						},
+
+						text_fill
+					};
+
+					*line_y += line_height;
+					Some(line)
+				}).collect()
+			}
+			else {
+				Vec::new()
+			}
 		},
 
 		SpinitronModelWindowsInfo {
 			model_name: SpinitronModelName::Playlist,
-			text_color: theme_color_1,
+			text_fill: theme_color_1.into(),
 			texture_window: None,
-			text_window: None
+			text_window: None,
+			spin_text_lines: Vec::new()
 		},
 
 		// Putting show before persona here so that the persona text is drawn over
 		SpinitronModelWindowsInfo {
 			model_name: SpinitronModelName::Show,
-			text_color: theme_color_1,
+			text_fill: theme_color_1.into(),
 
 			texture_window: Some(SpinitronModelWindowInfo {
 				tl: show_tl,
 				size: show_size,
-				border_color: Some(theme_color_1)
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: None,
+				maybe_background_color: Some(top_bar_gradient_end)
 			}),
 
 			text_window: Some(SpinitronModelWindowInfo {
 				tl: show_text_tl,
 				size: show_text_size,
-				border_color: Some(theme_color_1)
-			})
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: None,
+				maybe_background_color: None
+			}),
+
+			spin_text_lines: Vec::new()
 		},
 
 		SpinitronModelWindowsInfo {
 			model_name: SpinitronModelName::Persona,
-			text_color: theme_color_1,
+			text_fill: theme_color_1.into(),
 
 			texture_window: Some(SpinitronModelWindowInfo {
 				tl: persona_tl,
 				size: persona_size,
-				border_color: Some(theme_color_1)
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: None,
+				maybe_background_color: Some(top_bar_gradient_end)
 			}),
 
 			text_window: Some(SpinitronModelWindowInfo {
 				tl: persona_text_tl,
 				size: Vec2f::new(persona_size.x(), persona_text_height),
-				border_color: Some(theme_color_1)
-			})
+				border_color: Some(theme_color_1),
+				maybe_corner_radius: None,
+				maybe_background_color: None
+			}),
+
+			spin_text_lines: Vec::new()
 		}
 	];
 
 	// The Spinitron windows update at the same rate as the shared update rate
-	let spinitron_windows = make_spinitron_windows(
+	let maybe_spinitron_windows = components.spinitron.then(|| make_spinitron_windows(
 		&all_model_windows_info, shared_update_rate
-	);
+	));
+
+	// This shows the outgoing DJ under the current persona's text, during a handoff
+	let maybe_previous_persona_window = components.spinitron.then(|| make_previous_persona_window(
+		Vec2f::translate_y(&persona_text_tl, persona_text_height + main_windows_gap_size),
+		Vec2f::new_scalar(persona_size.x() * 0.5),
+		shared_update_rate, Some(theme_color_1)
+	));
+
+	// This sits right under the spin text, and it updates often enough for the fill to look smooth
+	let maybe_spin_progress_window = components.spinitron.then(|| make_spin_progress_window(
+		Vec2f::translate_y(&spin_tl, spin_size.y() + spin_text_height),
+		Vec2f::new(spin_size.x(), 0.004),
+		update_rate_creator.new_instance(0.5),
+		ColorSDL::RGBA(0, 0, 0, 100), theme_color_1
+	));
+
+	// This sits in the top-left corner of the spin art, over it
+	let maybe_spin_badges_window = components.spinitron.then(|| make_spin_badges_window(
+		Vec2f::translate(&spin_tl, 0.01, 0.01),
+		Vec2f::new(0.05, 0.025), 0.01,
+		shared_update_rate, ColorSDL::RGBA(0, 0, 0, 170), theme_color_1
+	));
+
+	// This runs along the bottom of the screen, between the error and credit windows
+	let maybe_now_playing_ticker_window = components.spinitron.then(|| make_now_playing_ticker_window(
+		Vec2f::new(0.16, 0.955), Vec2f::new(0.68, 0.035),
+		shared_update_rate, theme_color_1, None,
+		TextScrollParams::DEFAULT
+	));
 
 	////////// Making a Twilio window
 
-	let twilio_state = TwilioState::new(
-		&api_keys.twilio_account_sid,
-		&api_keys.twilio_auth_token,
-		6,
-		Duration::days(5),
-		false
-	);
+	let (maybe_twilio_state, maybe_twilio_window) = if components.twilio {
+		let twilio_state = TwilioState::new(
+			ApiKeys::get_needed(&api_keys.twilio_account_sid, "twilio_account_sid")?,
+			ApiKeys::get_needed(&api_keys.twilio_auth_token, "twilio_auth_token")?,
+			6,
+			Duration::days(5),
+			false
+		);
 
-	let twilio_window = make_twilio_window(
-		&twilio_state,
+		let twilio_window = make_twilio_window(
+			&twilio_state,
 
-		// This is how often the history windows check for new messages (this is low so that it'll be fast in the beginning)
-		update_rate_creator.new_instance(0.25),
+			// This is how often the history windows check for new messages (this is low so that it'll be fast in the beginning)
+			update_rate_creator.new_instance(0.25),
 
-		Vec2f::new(0.58, 0.45), Vec2f::new(0.4, 0.27),
+			Vec2f::new(0.58, 0.45), Vec2f::new(0.4, 0.27),
 
-		0.025,
-		WindowContents::Color(ColorSDL::RGB(0, 200, 0)),
+			TwilioWindowAppearance {
+				top_box_height: 0.025,
+				top_box_contents: WindowContents::Gradient {start: ColorSDL::RGB(0, 200, 0), end: ColorSDL::RGB(0, 150, 0), direction: GradientDirection::Vertical},
 
-		Vec2f::new(0.1, 0.45),
-		theme_color_1, theme_color_1,
+				message_background_contents: WindowContents::make_texture_contents(TEXT_BUBBLE_PATH, texture_pool)?,
+				message_background_contents_text_crop_factor: Vec2f::new(0.1, 0.45),
 
-		WindowContents::make_texture_contents("assets/text_bubble.png", texture_pool)?
-	);
+				// Messages that are too long scroll for 3/4 of each cycle, and then pause at their start
+				message_scroll_params: TextScrollParams {speed: 1.0, pause_fraction: 0.25},
+
+				overall_border_color: theme_color_1,
+				maybe_history_border_radius: Some(WindowBorderRadius::Pixels(8)),
+				text_color: theme_color_1
+			}
+		);
+
+		(Some(twilio_state), Some(twilio_window))
+	}
+	else {
+		(None, None)
+	};
+
+	////////// Making windows that show how long ago Spinitron and Twilio were updated
+
+	let staleness_label_size = Vec2f::new(0.15, 0.02);
+	let stale_threshold = std::time::Duration::from_secs(60);
+
+	// These are only made for enabled components, so their states are always there
+	let maybe_spinitron_staleness_window = components.spinitron.then(|| make_staleness_window(
+		Vec2f::new(spin_tl.x(), spin_tl.y() + spin_size.y() + spin_text_height + 0.005),
+		staleness_label_size, update_rate_creator, theme_color_1,
+
+		StalenessSource {
+			name: "Spinitron",
+			get_time_since_update: |state| state.maybe_spinitron_state.as_ref().map_or(std::time::Duration::ZERO, SpinitronState::time_since_last_update),
+			stale_threshold
+		},
+
+		fade_easing_fn
+	));
+
+	let maybe_twilio_staleness_window = components.twilio.then(|| make_staleness_window(
+		Vec2f::new(0.58, 0.725),
+		staleness_label_size, update_rate_creator, theme_color_1,
+
+		StalenessSource {
+			name: "Twilio",
+			get_time_since_update: |state| state.maybe_twilio_state.as_ref().map_or(std::time::Duration::ZERO, TwilioState::time_since_last_update),
+			stale_threshold
+		},
+
+		fade_easing_fn
+	));
+
+	////////// Making a loading placeholder for the Twilio window
+
+	/* The Twilio state starts out empty, and its first request is made on its own thread, so an empty message
+	history is shown until that finishes. This spins over the middle of the history until then. The Spinitron
+	state does not need one of these, since it makes its first requests before the dashboard is shown. */
+	let maybe_twilio_loading_window = components.twilio.then(|| make_loading_placeholder_window(
+		Vec2f::new(0.74, 0.545), Vec2f::new_scalar(0.08),
+		UpdateRate::ONCE_PER_FRAME,
+
+		LoadingPlaceholder {
+			contents: WindowContents::LazyTexture(LazyTextureSource::Path(Cow::Borrowed(LOADING_SPINNER_PATH))),
+			maybe_spin_degrees_per_sec: Some(180.0)
+		},
+
+		|state| state.maybe_twilio_state.as_ref().is_none_or(TwilioState::has_loaded)
+	));
 
 	////////// Making an error window
 
+	const MAX_NUM_SHOWN_ERRORS: usize = 3;
+
 	let error_window = make_error_window(
 		Vec2f::new(0.0, 0.95),
 		Vec2f::new(0.15, 0.05),
-		update_rate_creator.new_instance(2.0),
-		WindowContents::Color(ColorSDL::RGBA(255, 0, 0, 190)),
-		ColorSDL::GREEN
+		UpdateRate::ONCE_PER_FRAME, // This is once per frame so that the background pulses smoothly
+		MAX_NUM_SHOWN_ERRORS,
+
+		ErrorWindowAppearance {
+			background_contents: WindowContents::Color(ColorSDL::RGBA(255, 0, 0, 190)),
+			text_color: ColorSDL::GREEN,
+			pulse_params: ErrorPulseParams::DEFAULT,
+			scroll_params: TextScrollParams::DEFAULT,
+			fade_easing_fn
+		}
 	);
 
 	////////// Making a credit window
 
-	let credit_window = make_credit_window(
-		Vec2f::new(0.85, 0.97),
-		Vec2f::new(0.15, 0.03),
-		ColorSDL::RED,
-		ColorSDL::RGB(210, 180, 140),
-		"By: Caspian Ahlberg"
-	);
+	let maybe_credit_window = if components.credit {
+		let credit_template = if std::path::Path::new(CREDIT_CONFIG_PATH).exists() {
+			json_utils::load_from_file::<CreditConfig>(CREDIT_CONFIG_PATH)?.template
+		}
+		else {
+			DEFAULT_CREDIT_TEMPLATE.to_owned()
+		};
+
+		Some(make_credit_window(
+			Vec2f::new(0.85, 0.97),
+			Vec2f::new(0.15, 0.03),
+			ColorSDL::RED,
+			ColorSDL::RGB(210, 180, 140),
+			expand_credit_template(&credit_template, THEME_NAME)
+		))
+	}
+	else {
+		None
+	};
+
+	////////// Making a message of the day window
+
+	// Its position is set in its config, since the free space depends on which other components are on
+	let maybe_motd_window = if components.motd {
+		let motd_config = if std::path::Path::new(MOTD_CONFIG_PATH).exists() {
+			json_utils::load_from_file(MOTD_CONFIG_PATH)?
+		}
+		else {
+			MotdConfig::default()
+		};
+
+		Some(make_motd_window(
+			update_rate_creator, motd_config,
+			WindowContents::Color(ColorSDL::RGBA(0, 0, 0, 170)),
+			theme_color_1
+		)?)
+	}
+	else {
+		None
+	};
+
+	////////// Making the JSON field windows
+
+	let json_field_window_configs: Vec<JsonFieldWindowConfig> = if std::path::Path::new(JSON_FIELD_WINDOWS_CONFIG_PATH).exists() {
+		json_utils::load_from_file(JSON_FIELD_WINDOWS_CONFIG_PATH)?
+	}
+	else {
+		Vec::new()
+	};
+
+	let json_field_windows = json_field_window_configs.into_iter().map(|config|
+		make_json_field_window(config, update_rate_creator.new_instance(1.0), theme_color_1)
+	).collect::<GenericResult<Vec<_>>>()?;
+
+	const ON_AIR_LIVE_COLOR: ColorSDL = ColorSDL::RGB(220, 20, 20);
 
 	////////// Making a clock window
 
@@ -223,74 +843,178 @@ pub fn make_dashboard(
 	let clock_tl = Vec2f::new(1.0 - clock_size_x, 0.0);
 	let clock_size = Vec2f::new(clock_size_x, 1.0);
 
+	let clock_config = if std::path::Path::new(CLOCK_CONFIG_PATH).exists() {
+		json_utils::load_from_file::<ClockConfig>(CLOCK_CONFIG_PATH)?
+	}
+	else {
+		ClockConfig::default()
+	};
+
 	let (clock_hands, clock_window) = ClockHands::new_with_window(
 		UpdateRate::ONCE_PER_FRAME,
 		clock_tl,
 		clock_size,
 
 		ClockHandConfigs {
-			milliseconds: ClockHandConfig::new(0.01, 0.2, 0.5, ColorSDL::RGBA(255, 0, 0, 100)), // Milliseconds
-			seconds: ClockHandConfig::new(0.01, 0.02, 0.48, ColorSDL::WHITE), // Seconds
-			minutes: ClockHandConfig::new(0.01, 0.02, 0.35, ColorSDL::YELLOW), // Minutes
-			hours: ClockHandConfig::new(0.01, 0.02, 0.2, ColorSDL::BLACK) // Hours
+			milliseconds: ClockHandConfig::new(0.01, 0.2, 0.5, 1, ColorSDL::RGBA(255, 0, 0, 100)), // Milliseconds
+			seconds: ClockHandConfig::new(0.01, 0.02, 0.48, 2, ColorSDL::WHITE), // Seconds
+			minutes: ClockHandConfig::new(0.01, 0.02, 0.35, 3, ColorSDL::YELLOW), // Minutes
+			hours: ClockHandConfig::new(0.01, 0.02, 0.2, 3, ColorSDL::BLACK), // Hours
+			second_hand_motion: clock_config.second_hand_motion
+		},
+
+		if clock_config.procedural_dial {
+			ClockDial::Procedural(ProceduralDialConfig {
+				tick_inner_radius: 0.4,
+				tick_outer_radius: 0.48,
+				tick_thickness: 2,
+				tick_color: theme_color_1,
+				maybe_numerals: Some((0.3, theme_color_1))
+			})
+		}
+		else {
+			ClockDial::Texture(WATCH_DIAL_PATH)
 		},
 
-		"assets/watch_dial.png",
 		texture_pool
 	)?;
 
 	////////// Making a weather window
 
-	let weather_window = make_weather_window(
-		Vec2f::ZERO,
-		Vec2f::new(0.4, 0.3),
-		update_rate_creator,
-		&api_keys.openweathermap,
-		"Brunswick",
-		"ME",
-		"US"
-	);
+	let weather_config = if std::path::Path::new(WEATHER_CONFIG_PATH).exists() {
+		json_utils::load_from_file::<WeatherConfig>(WEATHER_CONFIG_PATH)?
+	}
+	else {
+		WeatherConfig::default()
+	};
+
+	let maybe_weather_window = components.weather.then(|| make_weather_window(
+		Vec2f::ZERO, Vec2f::new(0.4, 0.3),
+		shared_update_rate,
+		ColorSDL::WHITE,
+		weather_config.units,
+		weather_config.extra_fields
+	));
+
+	////////// Making an on-air window
+
+	// This sits in the top bar, to the left of the clock
+	let maybe_on_air_window = components.spinitron.then(|| make_on_air_window(
+		Vec2f::new(clock_tl.x() - 0.12, 0.2), Vec2f::new(0.11, 0.6),
+		shared_update_rate,
+
+		OnAirAppearance {
+			live_text: "ON AIR",
+			live_background_color: ON_AIR_LIVE_COLOR,
+			automation_text: "AUTOMATION",
+			automation_background_color: ColorSDL::RGBA(60, 60, 60, 200),
+			text_color: ColorSDL::WHITE
+		}
+	));
+
+	////////// Making a countdown window
+
+	// This sits in the top bar, to the left of the on-air window
+	let maybe_countdown_window = if components.countdown {
+		let countdown_config = if std::path::Path::new(COUNTDOWN_CONFIG_PATH).exists() {
+			json_utils::load_from_file(COUNTDOWN_CONFIG_PATH)?
+		}
+		else {
+			CountdownConfig::default()
+		};
+
+		Some(make_countdown_window(
+			Vec2f::new(clock_tl.x() - 0.24, 0.2), Vec2f::new(0.11, 0.6),
+			update_rate_creator.new_instance(1.0), countdown_config,
+			ColorSDL::WHITE, ColorSDL::RGB(255, 90, 90)
+		))
+	}
+	else {
+		None
+	};
+
+	////////// Making a weather alert window
+
+	// This is the latitude and longitude of Brunswick, ME
+	let maybe_weather_state = components.weather.then(|| maybe_prev_weather_state.unwrap_or_else(||
+		WeatherState::new(api_keys.maybe_tomorrow_io.as_deref(), 43.9109, -69.9631, weather_config.units)
+	));
+
+	let maybe_weather_alert_window = components.weather.then(|| make_weather_alert_window(
+		Vec2f::new(0.16, 0.915), Vec2f::new(0.68, 0.035),
+		shared_update_rate,
+		WindowContents::Gradient {start: ColorSDL::RGBA(255, 140, 0, 230), end: ColorSDL::RGBA(255, 175, 40, 230), direction: GradientDirection::Horizontal},
+		ColorSDL::BLACK,
+		TextScrollParams::DEFAULT,
+		fade_easing_fn
+	));
 
 	////////// Making some static texture windows
 
-	// Texture path, top left, size (TODO: make animated textures possible)
-	let main_static_texture_info = [
-		("assets/dashboard_bookshelf.png", Vec2f::ZERO, Vec2f::ONE, false),
-		("assets/logo.png", Vec2f::new(0.6, 0.75), Vec2f::new(0.1, 0.05), false),
-		("assets/soup.png", Vec2f::new(0.45, 0.72), Vec2f::new(0.06666666, 0.1), false),
-		("assets/ness.bmp", Vec2f::new(0.28, 0.73), Vec2f::new_scalar(0.08), false)
+	let from_path = |path: &'static str| LazyTextureSource::Path(Cow::Borrowed(path));
+
+	/* Texture source (a path, or embedded bytes), top left, size, skipping aspect ratio correction,
+	rotation (TODO: make animated textures possible) */
+	let mut main_static_texture_info = vec![
+		(LazyTextureSource::Bytes(BOOKSHELF_BYTES), Vec2f::ZERO, Vec2f::ONE, false, None),
+		(from_path(LOGO_PATH), Vec2f::new(0.6, 0.75), Vec2f::new(0.1, 0.05), false, None),
+		(from_path(SOUP_PATH), Vec2f::new(0.45, 0.72), Vec2f::new(0.06666666, 0.1), false, None)
 	];
 
+	// If Ness drifts, it starts where it would otherwise stand (its area is the bottom left of the screen)
+	let maybe_drifting_ness_window = if components.drifting_ness {
+		Some(make_drifting_window(
+			Vec2f::new(0.16, 0.7), Vec2f::new(0.28, 0.14),
+			WindowContents::LazyTexture(from_path(NESS_PATH)),
+			Vec2f::new(0.4285714, 0.2142857), Vec2f::new(0.2857143, 0.5714286),
+			(0.15, 0.1)
+		))
+	}
+	else {
+		main_static_texture_info.push((from_path(NESS_PATH), Vec2f::new(0.28, 0.73), Vec2f::new_scalar(0.08), false, None));
+		None
+	};
+
 	let foreground_static_texture_info = [
-		("assets/dashboard_foreground.png", Vec2f::ZERO, Vec2f::ONE, true)
+		(LazyTextureSource::Bytes(FOREGROUND_BYTES), Vec2f::ZERO, Vec2f::ONE, true, None)
 	];
 
 	let background_static_texture_info = [
 		// "assets/dashboard_background.png"
 	];
 
+	// These are loaded lazily, once they are first drawn
 	let add_static_texture_set =
-		|set: &mut Vec<Window>, all_info: &[(&'static str, Vec2f, Vec2f, bool)], texture_pool: &mut TexturePool| {
+		|set: &mut Vec<Window>, all_info: &[(LazyTextureSource, Vec2f, Vec2f, bool, Option<f64>)]| {
 
-		set.extend(all_info.iter().map(|&(path, tl, size, skip_ar_correction)| {
+		set.extend(all_info.iter().map(|(source, tl, size, skip_ar_correction, maybe_rotation_degrees)| {
 			let mut window = Window::new(
 				None,
 				DynamicOptional::NONE,
-				WindowContents::make_texture_contents(path, texture_pool).unwrap(),
+				WindowContents::LazyTexture(source.clone()),
 				None,
-				tl,
-				size,
+				*tl,
+				*size,
 				None
 			);
 
-			window.set_aspect_ratio_correction_skipping(skip_ar_correction);
+			window.set_aspect_ratio_correction_skipping(*skip_ar_correction);
+			window.set_rotation(*maybe_rotation_degrees);
 			window
 		}))
 	};
 
-	let mut all_main_windows = vec![twilio_window, error_window, credit_window];
-	all_main_windows.extend(spinitron_windows);
-	add_static_texture_set(&mut all_main_windows, &main_static_texture_info, texture_pool);
+	let mut all_main_windows: Vec<Window> = [
+		maybe_twilio_window, Some(error_window), maybe_credit_window, maybe_now_playing_ticker_window,
+		maybe_previous_persona_window, maybe_weather_alert_window, maybe_spinitron_staleness_window,
+		maybe_twilio_staleness_window, maybe_spin_progress_window, maybe_twilio_loading_window, maybe_motd_window
+	].into_iter().flatten().collect();
+
+	all_main_windows.extend(maybe_spinitron_windows.into_iter().flatten());
+	all_main_windows.extend(maybe_spin_badges_window); // This goes after the Spinitron windows, so that it is drawn over the spin art
+	all_main_windows.extend(json_field_windows);
+	add_static_texture_set(&mut all_main_windows, &main_static_texture_info);
+	all_main_windows.extend(maybe_drifting_ness_window);
 
 	////////// Making all of the main windows
 
@@ -300,14 +1024,24 @@ pub fn make_dashboard(
 
 	let top_bar_tl = Vec2f::new_scalar(main_windows_gap_size);
 
+	// The top bar gets a border in the on-air color while a DJ is live (this is set by the on-air window)
+	fn top_bar_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let is_live = params.shared_window_state.get::<SharedWindowState>()
+			.shared_facts.get::<bool>(ON_AIR_IS_LIVE_FACT).copied().unwrap_or(false);
+
+		let live_border_color = *params.window.get_state::<ColorSDL>();
+		params.window.set_border_color(is_live.then_some(live_border_color));
+		Ok(())
+	}
+
 	let top_bar_window = Window::new(
-		None,
-		DynamicOptional::NONE,
-		WindowContents::Color(ColorSDL::RGB(128, 0, 32)),
+		Some((top_bar_updater_fn, shared_update_rate)),
+		DynamicOptional::new(ON_AIR_LIVE_COLOR),
+		WindowContents::Gradient {start: top_bar_gradient_start, end: top_bar_gradient_end, direction: GradientDirection::Diagonal},
 		None,
 		top_bar_tl,
 		Vec2f::new(x_width_from_main_window_gap_size, top_bar_window_size_y),
-		Some(vec![clock_window, weather_window])
+		Some([Some(clock_window), maybe_weather_window, maybe_on_air_window, maybe_countdown_window].into_iter().flatten().collect())
 	);
 
 	let mut main_window = Window::new(
@@ -330,62 +1064,94 @@ pub fn make_dashboard(
 
 	////////// Making a surprise window
 
-	let surprise_window = make_surprise_window(
-		Vec2f::ZERO, Vec2f::ONE, "/tmp/surprises_wbor_studio_dashboard.sock",
+	let num_appearing_surprises = NumAppearingSurprises::default();
+
+	let maybe_surprise_window = components.surprise.then(|| make_surprise_window(
+		Vec2f::ZERO, Vec2f::ONE, SURPRISE_TRIGGERING_SOCKET_PATH,
 
 		&[
 			SurpriseCreationInfo {
-				texture_path: "assets/nathan.png",
+				texture_path: NATHAN_SURPRISE_PATH,
 				texture_blend_mode: BlendMode::None,
 
 				update_rate: Duration::seconds(15),
 				num_update_steps_to_appear_for: 1,
-				chance_of_appearing_when_updating: 0.0007,
+				chance_of_appearing: SurpriseChance::PerMinute(0.0028), // This is about the same as the old chance of 0.0007 per 15-second update
 
 				local_hours_24_start: 8,
 				local_hours_24_end: 22,
+				allowed_weekdays: Vec::new(),
 
-				flicker_window: false
+				flicker_window: false,
+				maybe_fade: None,
+				maybe_cooldown: Some(Duration::minutes(30))
 			},
 
 			SurpriseCreationInfo {
-				texture_path: "assets/jumpscare.png",
+				texture_path: JUMPSCARE_SURPRISE_PATH,
 				texture_blend_mode: BlendMode::Add,
 
 				update_rate: Duration::milliseconds(35),
 				num_update_steps_to_appear_for: 20,
-				chance_of_appearing_when_updating: 0.000003,
+				chance_of_appearing: SurpriseChance::PerMinute(0.005), // This is about the same as the old chance of 0.000003 per 35-millisecond update
 
 				local_hours_24_start: 0,
 				local_hours_24_end: 5,
+				allowed_weekdays: Vec::new(),
 
-				flicker_window: true
+				flicker_window: true,
+				maybe_fade: None,
+				maybe_cooldown: Some(Duration::hours(1))
 			},
 
 			SurpriseCreationInfo {
-				texture_path: "assets/horrible.webp",
+				texture_path: HORRIBLE_SURPRISE_PATH,
 				texture_blend_mode: BlendMode::Add,
 
 				update_rate: Duration::milliseconds(100),
 				num_update_steps_to_appear_for: 9,
-				chance_of_appearing_when_updating: 0.0, // This one can only be triggered artificially
+				chance_of_appearing: SurpriseChance::PerUpdate(0.0), // This one can only be triggered artificially
 
 				local_hours_24_start: 0,
 				local_hours_24_end: 23,
+				allowed_weekdays: Vec::new(),
 
-				flicker_window: true
+				flicker_window: true,
+				maybe_fade: None,
+				maybe_cooldown: None
 			}
 		],
 
+		num_appearing_surprises.clone(),
 		update_rate_creator,
 		texture_pool
-	)?;
+	)).transpose()?;
 
 	////////// Making the highest-level window
 
 	let mut all_windows = vec![top_bar_window, main_window];
-	add_static_texture_set(&mut all_windows, &foreground_static_texture_info, texture_pool);
-	all_windows.push(surprise_window);
+
+	// Clicking a Twilio message shows it here in full (this is only reachable when click handling is on)
+	if components.twilio {
+		let mut twilio_focus_window = make_twilio_focus_window(
+			Vec2f::new(0.15, 0.3), Vec2f::new(0.7, 0.4),
+			update_rate_creator.new_instance(0.1),
+			WindowContents::Color(ColorSDL::RGBA(20, 20, 20, 235)),
+			theme_color_1, 5, std::time::Duration::from_secs(20)
+		);
+
+		// This is above the foreground, but below surprises
+		twilio_focus_window.set_z_index(1);
+		all_windows.push(twilio_focus_window);
+	}
+
+	if let Some(mut surprise_window) = maybe_surprise_window {
+		// Surprises are drawn over everything else
+		surprise_window.set_z_index(1);
+		all_windows.push(surprise_window);
+	}
+
+	add_static_texture_set(&mut all_windows, &foreground_static_texture_info);
 
 	let all_windows_window = Window::new(
 		None,
@@ -399,58 +1165,71 @@ pub fn make_dashboard(
 
 	////////// Defining the shared state
 
-	// TODO: make it possible to get different variants of this texture (randomly chosen)
-	const FALLBACK_TEXTURE_CREATION_INFO: TextureCreationInfo<'static> =
-		TextureCreationInfo::Path(Cow::Borrowed("assets/no_texture_available.png"));
-
 	let initial_spin_window_size_guess = (1000, 1000);
 	let spin_expiry_duration = Duration::minutes(20);
-
-	let spinitron_state = SpinitronState::new(
-		(&api_keys.spinitron, spin_expiry_duration,
-		&FALLBACK_TEXTURE_CREATION_INFO, initial_spin_window_size_guess)
-	)?;
+	let previous_playlist_retention_duration = Duration::minutes(10);
+	let max_concurrent_spinitron_texture_requests = *MAX_CONCURRENT_SPINITRON_TEXTURE_REQUESTS.get_or_init(|| 2);
+
+	let mut maybe_spinitron_state = components.spinitron.then(|| maybe_prev_spinitron_state.map_or_else(|| SpinitronState::new(
+		(ApiKeys::get_needed(&api_keys.spinitron, "spinitron")?, spin_expiry_duration,
+		&FALLBACK_TEXTURE_CREATION_INFOS, initial_spin_window_size_guess,
+		Some(previous_playlist_retention_duration), max_concurrent_spinitron_texture_requests)
+	), Ok)).transpose()?;
+
+	/* Model images (like album art) fade through black when they change, since consecutive
+	ones are often very different. Text is changed right away. */
+	if let Some(spinitron_state) = &mut maybe_spinitron_state {
+		spinitron_state.set_image_transition(Some(FadeThroughTransition {
+			duration: 1.0,
+			dark_fraction: 0.1,
+			easing_fn: easing_fns::ease_in_out_sine,
+			maybe_through_color: Some(ColorSDL::BLACK)
+		}));
+	}
 
 	let boxed_shared_state = DynamicOptional::new(
 		SharedWindowState {
 			clock_hands,
-			spinitron_state,
-			twilio_state,
-			font_info: &FONT_INFO,
-			fallback_texture_creation_info: &FALLBACK_TEXTURE_CREATION_INFO,
-			curr_dashboard_error: None,
-			rand_generator: rand::thread_rng()
+			maybe_spinitron_state,
+			maybe_twilio_state,
+			maybe_weather_state,
+			font_info,
+			fallback_texture_creation_info,
+			fallback_texture_creation_infos: &FALLBACK_TEXTURE_CREATION_INFOS,
+			curr_dashboard_errors: Vec::new(),
+			num_appearing_surprises,
+			shared_facts: SharedFacts::default(),
+			rand_generator
 		}
 	);
 
-	fn shared_window_state_updater(state: &mut DynamicOptional, texture_pool: &mut TexturePool) -> MaybeError {
-		let state = state.get_mut::<SharedWindowState>();
+	fn shared_window_state_updater(state: &mut DynamicOptional,
+		texture_pool: &mut TexturePool, error_state: &mut ErrorState) -> MaybeError {
 
-		let mut error = None;
+		let state = state.get_mut::<SharedWindowState>();
 
-		// More continual updaters can be added here
-		let success_states_and_names = [
-			(state.spinitron_state.update()?, "Spinitron"),
-			(state.twilio_state.update(texture_pool)?, "Twilio (messaging)")
+		// More continual updaters can be added here (these are `None` for disabled components)
+		let maybe_success_states_and_names = [
+			(state.maybe_spinitron_state.as_mut().map(SpinitronState::update).transpose()?, "Spinitron"),
+			(state.maybe_twilio_state.as_mut().map(|twilio_state| twilio_state.update(texture_pool)).transpose()?, "Twilio (messaging)"),
+			(state.maybe_weather_state.as_mut().map(WeatherState::update).transpose()?, "Weather")
 		];
 
-		for (succeeded, name) in success_states_and_names {
-			if !succeeded {
-				if let Some(already_error) = &mut error {
-					*already_error += ", and ";
-					*already_error += name;
-				}
-				else {
-					error = Some(format!("Internal dashboard error from {name}"))
-				}
+		/* A success here may also mean that the continual updater is still waiting on its thread,
+		so errors from the continual updaters are left to expire, rather than being cleared */
+		for (maybe_succeeded, name) in maybe_success_states_and_names {
+			if maybe_succeeded == Some(false) {
+				error_state.report(name, "Internal dashboard error".to_owned());
 			}
 		}
 
-		if let Some(inner_error) = &mut error {
-			*inner_error += "!";
-		}
+		state.curr_dashboard_errors = error_state.get_recent_errors(MAX_NUM_SHOWN_ERRORS).into_iter().map(|(source, message, error)|
+			format!("{source}: {message} (since {})", error.first_seen.format("%H:%M:%S"))
+		).collect();
 
-		state.curr_dashboard_error = error;
+		// Textures that fail to load after this will use a different fallback
+		state.fallback_texture_creation_info = choose_fallback_texture_creation_info(state.fallback_texture_creation_infos, &mut state.rand_generator)?;
+		texture_pool.set_fallback_texture_creation_info(state.fallback_texture_creation_info);
 
 		Ok(())
 	}
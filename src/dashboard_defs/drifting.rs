@@ -0,0 +1,66 @@
+use crate::{
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		update_rate::UpdateRate,
+		dynamic_optional::DynamicOptional
+	},
+
+	window_tree::{
+		Window,
+		WindowContents,
+		WindowUpdaterParams
+	}
+};
+
+//////////
+
+// This is in units of the drifting area's size per second (and either sign)
+type DriftVelocity = (f32, f32);
+
+/* This makes a decoration that drifts around within an area (given by `area_top_left` and `area_size`),
+and bounces off of its edges. The decoration's own top left and size are relative to that area.
+The decoration is assumed to face right, and it is mirrored while it moves left. */
+pub fn make_drifting_window(area_top_left: Vec2f, area_size: Vec2f,
+	contents: WindowContents, top_left: Vec2f, size: Vec2f, velocity: DriftVelocity) -> Window {
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let velocity = *params.window.get_state::<DriftVelocity>();
+		let (hit_x_edge, hit_y_edge) = params.window.move_by(velocity, &params.frame_counter);
+
+		if hit_x_edge || hit_y_edge {
+			let velocity = params.window.get_state_mut::<DriftVelocity>();
+			if hit_x_edge {velocity.0 = -velocity.0;}
+			if hit_y_edge {velocity.1 = -velocity.1;}
+
+			if hit_x_edge {
+				let moving_left = velocity.0 < 0.0;
+				params.window.set_flip(moving_left, false);
+			}
+		}
+
+		Ok(())
+	}
+
+	let mut drifting_window = Window::new(
+		Some((updater_fn, UpdateRate::ONCE_PER_FRAME)), // This is once per frame so that the motion is smooth
+		DynamicOptional::new(velocity),
+		contents,
+		None,
+		top_left,
+		size,
+		None
+	);
+
+	drifting_window.set_flip(velocity.0 < 0.0, false);
+
+	Window::new(
+		None,
+		DynamicOptional::NONE,
+		WindowContents::Nothing,
+		None,
+		area_top_left,
+		area_size,
+		Some(vec![drifting_window])
+	)
+}
@@ -1,9 +1,14 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, time::Instant};
 
 use crate::{
+	texture::{DisplayText, TextDisplayInfo, TextScrollParams, TextMargins, TextureCreationInfo, scroll_with_pause},
+
 	utility_types::{
 		vec2f::Vec2f,
-		update_rate::UpdateRate
+		easing_fns::{self, EasingFn},
+		generic_result::*,
+		dynamic_optional::DynamicOptional,
+		update_rate::{Seconds, UpdateRate}
 	},
 
 	window_tree::{
@@ -13,77 +18,259 @@ use crate::{
 		WindowUpdaterParams
 	},
 
-	dashboard_defs::{
-		updatable_text_pattern,
-		shared_window_state::SharedWindowState
-	}
+	dashboard_defs::shared_window_state::SharedWindowState
 };
 
-// TODO: maybe replace this with the SDL message box?
-pub fn make_error_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
-	background_contents: WindowContents, text_color: ColorSDL) -> Window {
+//////////
 
-	type ErrorWindowState = Option<String>; // This is the previous error
+/* When a new error appears, the error window's background alpha pulses for a bit, and then settles.
+The pulses get weaker over the duration, so that the settling is not abrupt. */
+#[derive(Clone)]
+pub struct ErrorPulseParams {
+	pub amplitude: f64, // This is the fraction of the background alpha that is taken away at the peak of a pulse
+	pub period: Seconds,
+	pub duration: Seconds
+}
 
-	impl updatable_text_pattern::UpdatableTextWindowMethods for ErrorWindowState {
-		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
-			let inner_shared_state = updater_params.shared_window_state.get::<SharedWindowState>();
+impl ErrorPulseParams {
+	pub const DEFAULT: Self = Self {amplitude: 0.7, period: 0.6, duration: 4.0};
+}
 
-			let wrapped_individual_state = updater_params.window.get_state_mut
-				::<updatable_text_pattern::UpdatableTextWindowFields<ErrorWindowState>>();
+pub struct ErrorWindowAppearance {
+	pub background_contents: WindowContents,
+	pub text_color: ColorSDL,
+	pub pulse_params: ErrorPulseParams,
+	pub scroll_params: TextScrollParams,
+	pub fade_easing_fn: EasingFn
+}
 
-			let prev_error = &wrapped_individual_state.inner;
+struct ErrorWindowState {
+	prev_errors: Vec<String>,
+	prev_showing_multiple_lines: bool,
+	max_num_errors: usize,
+	text_color: ColorSDL,
+	scroll_params: TextScrollParams,
+	fade_easing_fn: EasingFn,
 
-			let (curr_error, cached_error) = (
-				&inner_shared_state.curr_dashboard_error, prev_error
-			);
+	maybe_base_background_color: Option<ColorSDL>, // The background only pulses if it is a plain color
+	pulse_params: ErrorPulseParams,
+	maybe_pulse_start_time: Option<Instant>
+}
 
-			// This means that the error changed (or disappeared)!
-			if curr_error != cached_error {
-				let skip_update = curr_error.is_none();
-				wrapped_individual_state.inner.clone_from(curr_error);
-				updater_params.window.set_draw_skipping(skip_update);
-				skip_update
-			}
-			else {
-				true
-			}
-		}
+impl ErrorWindowState {
+	/* In multi-line mode, each error gets its own line. If the lines would be shorter than this
+	(in pixels), the errors are instead shown one after another, on a single scrolling line. */
+	const MIN_LINE_HEIGHT_PIXELS: u32 = 14;
 
-		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
-			(Cow::Borrowed(inner_shared_state.font_info), " ")
-		}
+	const COMPACT_MODE_SEPARATOR: &'static str = " | ";
 
-		fn extract_text(&self) -> Cow<str> {
-			Cow::Borrowed(self.as_ref().unwrap())
-		}
+	// This returns the fraction of the background alpha to draw with, and if the pulse is done
+	fn evaluate_pulse(&self, pulse_start_time: Instant) -> (f64, bool) {
+		let params = &self.pulse_params;
+		let secs_since_start = pulse_start_time.elapsed().as_secs_f64();
 
-		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
-			let WindowContents::Many(all_contents) = window_contents
-			else {panic!("The error window contents was expected to be a list!")};
-			&mut all_contents[1]
+		if secs_since_start >= params.duration {
+			return (1.0, true);
 		}
+
+		// This goes from 0 to 1, and back to 0, once per period
+		let phase = (secs_since_start % params.period) / params.period;
+		let wave = easing_fns::ease_in_out_sine(1.0 - (2.0 * phase - 1.0).abs());
+		let decay = 1.0 - secs_since_start / params.duration;
+
+		(1.0 - params.amplitude * decay * wave, false)
 	}
+}
 
-	let fields = updatable_text_pattern::UpdatableTextWindowFields {
-		inner: None,
-		text_color,
+//////////
+
+fn error_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+	const FADE_DURATION_SECS: Seconds = 0.5;
+
+	let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+	let individual_state = params.window.get_state::<ErrorWindowState>();
 
-		scroll_fn: |seed, _| {
-			let repeat_rate_secs = 2.0;
-			((seed % repeat_rate_secs) / repeat_rate_secs, true)
-		},
+	let curr_errors = &inner_shared_state.curr_dashboard_errors[
+		..inner_shared_state.curr_dashboard_errors.len().min(individual_state.max_num_errors)
+	];
+
+	let line_height_pixels = params.area_drawn_to_screen.1 / individual_state.max_num_errors as u32;
+	let showing_multiple_lines = curr_errors.len() > 1 && line_height_pixels >= ErrorWindowState::MIN_LINE_HEIGHT_PIXELS;
+
+	let errors_changed = curr_errors != individual_state.prev_errors;
+	let (errors_disappeared, errors_appeared) = (curr_errors.is_empty(), individual_state.prev_errors.is_empty());
+	let new_error_appeared = curr_errors.iter().any(|error| !individual_state.prev_errors.contains(error));
+	let layout_changed = showing_multiple_lines != individual_state.prev_showing_multiple_lines;
+	let should_redo_text = !errors_disappeared && (errors_changed || layout_changed);
+
+	/* This runs every frame, so nothing is touched unless something changed, or the background is pulsing
+	(since getting the state or contents mutably marks the window as changed, which makes the screen redraw) */
+	if !errors_changed && !layout_changed && individual_state.maybe_pulse_start_time.is_none() {
+		return Ok(());
+	}
+
+	let individual_state = params.window.get_state_mut::<ErrorWindowState>();
+
+	if errors_changed {
+		individual_state.prev_errors = curr_errors.to_vec();
+	}
+
+	individual_state.prev_showing_multiple_lines = showing_multiple_lines;
+
+	////////// Pulsing the background
+
+	// A new distinct error restarts the pulse, and the pulse stops once all errors clear
+	if new_error_appeared {
+		individual_state.maybe_pulse_start_time = Some(Instant::now());
+	}
+	else if errors_disappeared {
+		individual_state.maybe_pulse_start_time = None;
+	}
+
+	let maybe_pulse_alpha_fract = individual_state.maybe_pulse_start_time.map(|pulse_start_time| {
+		let (alpha_fract, pulse_is_done) = individual_state.evaluate_pulse(pulse_start_time);
+		if pulse_is_done {individual_state.maybe_pulse_start_time = None;}
+		alpha_fract
+	});
+
+	// When the pulse stops, the background goes back to its full alpha
+	let maybe_background_alpha_fract = maybe_pulse_alpha_fract.or(errors_changed.then_some(1.0));
+	let (maybe_base_background_color, text_color, scroll_params, fade_easing_fn) = (
+		individual_state.maybe_base_background_color, individual_state.text_color,
+		individual_state.scroll_params, individual_state.fade_easing_fn
+	);
+
+	let window = params.window;
+
+	if let (Some(alpha_fract), Some(base_color)) = (maybe_background_alpha_fract, maybe_base_background_color) {
+		*window.get_contents_mut() = WindowContents::Color(
+			ColorSDL::RGBA(base_color.r, base_color.g, base_color.b, (base_color.a as f64 * alpha_fract) as u8)
+		);
+	}
 
-		update_rate,
-		maybe_border_color: None
+	////////// Fading the whole window in or out
+
+	/* When the errors disappear, the last error text fades out (a window with zero opacity is not drawn at all).
+	The text children are faded along with the background, since children do not inherit their parent's opacity. */
+	if errors_changed && (errors_disappeared || errors_appeared) {
+		let target_opacity = if errors_disappeared {0.0} else {1.0};
+
+		let fade = |window: &mut Window| {
+			// A window's drawing is skipped before its first error
+			let start_opacity = if window.drawing_is_skipped() {0.0} else {window.get_opacity()};
+			window.animate_opacity(start_opacity, target_opacity, FADE_DURATION_SECS, fade_easing_fn);
+		};
+
+		fade(window);
+		if errors_appeared {window.set_draw_skipping(false);}
+		window.get_children_mut().into_iter().flatten().for_each(fade);
+	}
+
+	if !should_redo_text {
+		return Ok(());
+	}
+
+	////////// Updating the error text
+
+	// The first child is for the compact mode, and the rest are for the lines in multi-line mode
+	let compact_text = curr_errors.join(ErrorWindowState::COMPACT_MODE_SEPARATOR);
+
+	let all_children = window.get_children_mut().context("The error window was expected to have children")?;
+	let (compact_child, line_children) = all_children.split_first_mut().context("The error window was expected to have a compact mode child")?;
+
+	let text_and_children = std::iter::once((
+		(!showing_multiple_lines).then_some(compact_text.as_str()), params.area_drawn_to_screen, compact_child
+	)).chain(line_children.iter_mut().enumerate().map(|(index, line_child)| (
+		curr_errors.get(index).filter(|_| showing_multiple_lines).map(String::as_str),
+		(params.area_drawn_to_screen.0, line_height_pixels),
+		line_child
+	)));
+
+	for (maybe_text, pixel_area, child) in text_and_children {
+		let Some(text) = maybe_text else {
+			child.set_draw_skipping(true);
+			continue;
+		};
+
+		let texture_creation_info = TextureCreationInfo::Text((
+			Cow::Borrowed(inner_shared_state.font_info),
+
+			TextDisplayInfo {
+				text: DisplayText::new(text).with_padding("", ErrorWindowState::COMPACT_MODE_SEPARATOR),
+				color: text_color.into(),
+				maybe_outline_color: None,
+				pixel_area,
+
+				scroll_fn: |seed, text_fits, pause_fraction| {
+					if text_fits {
+						return (0.0, false);
+					}
+
+					(scroll_with_pause(seed, 2.0, pause_fraction), true)
+				},
+
+				scroll_params,
+				margins: TextMargins::NONE
+			}
+		));
+
+		child.set_draw_skipping(false);
+
+		child.get_contents_mut().update_as_texture(true, params.texture_pool,
+			&texture_creation_info, inner_shared_state.fallback_texture_creation_info)?;
+	}
+
+	Ok(())
+}
+
+/* TODO: maybe replace this with the SDL message box?
+This shows up to `max_num_errors` of the newest errors. If the window is tall enough, each error gets its own line;
+otherwise, they are all shown on a single scrolling line. */
+pub fn make_error_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
+	max_num_errors: usize, appearance: ErrorWindowAppearance) -> Window {
+
+	let ErrorWindowAppearance {background_contents, text_color, pulse_params, scroll_params, fade_easing_fn} = appearance;
+
+	let maybe_base_background_color = match &background_contents {
+		WindowContents::Color(color) => Some(*color),
+		_ => None
 	};
 
-	let mut window = updatable_text_pattern::make_window(
-		fields, top_left, size,
-		WindowContents::Many(vec![background_contents, WindowContents::Nothing])
+	let make_text_child = |top_left, size| {
+		let mut child = Window::new(None, DynamicOptional::NONE, WindowContents::Nothing, None, top_left, size, None);
+		child.set_draw_skipping(true);
+		child
+	};
+
+	let line_size = Vec2f::new(1.0, 1.0 / max_num_errors as f32);
+
+	let text_children = std::iter::once(make_text_child(Vec2f::ZERO, Vec2f::ONE)).chain(
+		(0..max_num_errors).map(|index| make_text_child(Vec2f::new(0.0, line_size.y() * index as f32), line_size))
+	).collect();
+
+	let individual_state = ErrorWindowState {
+		prev_errors: Vec::new(),
+		prev_showing_multiple_lines: false,
+		max_num_errors,
+		text_color,
+		scroll_params,
+		fade_easing_fn,
+
+		maybe_base_background_color,
+		pulse_params,
+		maybe_pulse_start_time: None
+	};
+
+	let mut window = Window::new(
+		Some((error_updater_fn, update_rate)),
+		DynamicOptional::new(individual_state),
+		background_contents,
+		None,
+		top_left,
+		size,
+		Some(text_children)
 	);
 
 	window.set_draw_skipping(true);
 	window
-
 }
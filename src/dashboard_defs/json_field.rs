@@ -0,0 +1,221 @@
+use std::{borrow::Cow, sync::Arc, time::{Duration, Instant}};
+
+use crate::{
+	request,
+	texture::{DisplayText, TextDisplayInfo, TextScrollParams, TextMargins, TextureCreationInfo, scroll_with_pause},
+
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		dynamic_optional::DynamicOptional,
+		thread_task::{ContinuallyUpdated, Updatable},
+		update_rate::{UpdateRate, Seconds}
+	},
+
+	window_tree::{
+		Window,
+		ColorSDL,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::shared_window_state::SharedWindowState
+};
+
+//////////
+
+/* This is for one-off JSON endpoints (like donation totals, or event schedules). The endpoint is polled every so often,
+and one field is picked out of each response, and shown as text through a template. The field path is like a simple
+JSONPath, with dots between object keys and brackets around array indices (e.g. `data.totals[0].amount`, with an optional
+leading `$.`). The template's `{value}` is replaced with the field (strings are shown without their quotes). The fallback
+text is shown until the first response arrives, and whenever the request fails or the field is missing (and those
+problems are also reported as dashboard errors, under the window's name). */
+#[derive(serde::Deserialize)]
+pub struct JsonFieldWindowConfig {
+	name: String,
+	url: String,
+	refresh_interval_secs: Seconds,
+	field_path: String,
+	template: String,
+	fallback_text: String,
+	top_left: (f32, f32),
+	size: (f32, f32)
+}
+
+// This turns a field path into a JSON pointer (e.g. `$.data.totals[0].amount` into `/data/totals/0/amount`)
+fn field_path_to_json_pointer(field_path: &str) -> String {
+	let field_path = field_path.strip_prefix('$').unwrap_or(field_path);
+
+	field_path.replace('[', ".").replace(']', "").split('.').filter(|part| !part.is_empty())
+		.map(|part| format!("/{}", part.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+//////////
+
+#[derive(Clone)]
+struct JsonFieldData {
+	url: Arc<String>,
+	refresh_interval: Duration,
+	maybe_last_fetch_time: Option<Instant>,
+	maybe_json: Option<serde_json::Value>,
+	num_fetches: u64 // This lets the window tell when a new response arrived
+}
+
+impl Updatable for JsonFieldData {
+	type Param = ();
+
+	fn update(&mut self, _: &Self::Param) -> MaybeError {
+		if let Some(last_fetch_time) = self.maybe_last_fetch_time {
+			if last_fetch_time.elapsed() < self.refresh_interval {
+				return Ok(());
+			}
+		}
+
+		// The fetch time is set first, so that a failing endpoint is only retried at the refresh interval
+		self.maybe_last_fetch_time = Some(Instant::now());
+		self.maybe_json = Some(request::as_type(request::get(&self.url))?);
+		self.num_fetches += 1;
+		Ok(())
+	}
+}
+
+//////////
+
+pub fn make_json_field_window(config: JsonFieldWindowConfig,
+	update_rate: UpdateRate, text_color: ColorSDL) -> GenericResult<Window> {
+
+	struct JsonFieldWindowState {
+		name: &'static str,
+		json_pointer: String,
+		field_path: String,
+		template: String,
+		fallback_text: String,
+		text_color: ColorSDL,
+
+		continually_updated: ContinuallyUpdated<JsonFieldData>,
+		num_fetches_seen: u64,
+		request_failed: bool,
+		maybe_text: Option<String> // This is the previous text
+	}
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let state = params.window.get_state_mut::<JsonFieldWindowState>();
+
+		let succeeded = state.continually_updated.update(&())?;
+		let data = state.continually_updated.get_data();
+
+		if !succeeded {
+			state.request_failed = true;
+			params.error_state.report(state.name, "The request failed (see the log for why)".to_owned());
+		}
+		else if data.num_fetches != state.num_fetches_seen {
+			state.request_failed = false;
+			state.num_fetches_seen = data.num_fetches;
+		}
+
+		let maybe_field = data.maybe_json.as_ref().filter(|_| !state.request_failed)
+			.map(|json| json.pointer(&state.json_pointer));
+
+		let text = match maybe_field {
+			Some(Some(field)) => {
+				// Both the request and the field lookup worked, so any earlier errors from this window are resolved
+				params.error_state.clear(state.name);
+
+				let value = match field {
+					serde_json::Value::String(string) => string.clone(),
+					other => other.to_string()
+				};
+
+				state.template.replace("{value}", &value)
+			},
+
+			Some(None) => {
+				params.error_state.report(state.name, format!("The field '{}' is missing from the response", state.field_path));
+				state.fallback_text.clone()
+			},
+
+			None => state.fallback_text.clone()
+		};
+
+		if state.maybe_text.as_ref() == Some(&text) {
+			return Ok(());
+		}
+
+		state.maybe_text = Some(text.clone());
+		let text_color = state.text_color;
+
+		let texture_creation_info = TextureCreationInfo::Text((
+			Cow::Borrowed(inner_shared_state.font_info),
+
+			TextDisplayInfo {
+				text: DisplayText::new(&text),
+				color: text_color.into(),
+				maybe_outline_color: None,
+				pixel_area: params.area_drawn_to_screen,
+
+				// Text that fits is left still, and text that is too long scrolls
+				scroll_fn: |seed, text_fits, pause_fraction| {
+					if text_fits {
+						return (0.0, false);
+					}
+
+					(scroll_with_pause(seed, 12.0, pause_fraction), true)
+				},
+
+				scroll_params: TextScrollParams::DEFAULT,
+				margins: TextMargins::NONE
+			}
+		));
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	//////////
+
+	let in_unit_interval = |(x, y): (f32, f32)| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y);
+
+	if !in_unit_interval(config.top_left) || !in_unit_interval(config.size) {
+		return error_msg!("The top left and size of the '{}' JSON field window should be between 0 and 1", config.name);
+	}
+
+	// The name is used for the continual updater, which is kept for as long as the theme is (like the font info)
+	let name: &'static str = Box::leak(config.name.into_boxed_str());
+
+	let data = JsonFieldData {
+		url: Arc::new(config.url),
+		refresh_interval: Duration::from_secs_f64(config.refresh_interval_secs.max(1.0)),
+		maybe_last_fetch_time: None,
+		maybe_json: None,
+		num_fetches: 0
+	};
+
+	let state = JsonFieldWindowState {
+		name,
+		json_pointer: field_path_to_json_pointer(&config.field_path),
+		field_path: config.field_path,
+		template: config.template,
+		fallback_text: config.fallback_text,
+		text_color,
+
+		continually_updated: ContinuallyUpdated::new(&data, &(), name),
+		num_fetches_seen: 0,
+		request_failed: false,
+		maybe_text: None
+	};
+
+	Ok(Window::new(
+		Some((updater_fn, update_rate)),
+		DynamicOptional::new(state),
+		WindowContents::Nothing,
+		Some(text_color),
+		Vec2f::new(config.top_left.0, config.top_left.1),
+		Vec2f::new(config.size.0, config.size.1),
+		None
+	))
+}
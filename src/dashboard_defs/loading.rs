@@ -0,0 +1,76 @@
+use crate::{
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		update_rate::UpdateRate,
+		dynamic_optional::DynamicOptional
+	},
+
+	window_tree::{
+		Window,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::shared_window_state::SharedWindowState
+};
+
+//////////
+
+// This returns true once a data source has gotten its first real data
+pub type HasLoadedGetter = fn(&SharedWindowState) -> bool;
+
+pub struct LoadingPlaceholder {
+	pub contents: WindowContents,
+	pub maybe_spin_degrees_per_sec: Option<f64> // If this is given, the contents spin (like a loading spinner)
+}
+
+struct LoadingWindowState {
+	has_loaded: HasLoadedGetter,
+	maybe_spin_degrees_per_sec: Option<f64>,
+	done: bool
+}
+
+/* This shows a placeholder over a data-backed window until its data source first loads, so that it is clear that
+the dashboard is still starting up (rather than broken). Once the data loads, the placeholder is hidden for good. */
+pub fn make_loading_placeholder_window(top_left: Vec2f, size: Vec2f,
+	update_rate: UpdateRate, placeholder: LoadingPlaceholder, has_loaded: HasLoadedGetter) -> Window {
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let state = params.window.get_state_mut::<LoadingWindowState>();
+
+		if state.done {
+			return Ok(());
+		}
+
+		if (state.has_loaded)(inner_shared_state) {
+			state.done = true;
+			params.window.set_draw_skipping(true);
+			return Ok(());
+		}
+
+		if let Some(spin_degrees_per_sec) = state.maybe_spin_degrees_per_sec {
+			let degrees = (params.frame_counter.total_seconds() * spin_degrees_per_sec) % 360.0;
+			params.window.set_rotation(Some(degrees));
+		}
+
+		Ok(())
+	}
+
+	Window::new(
+		Some((updater_fn, update_rate)),
+
+		DynamicOptional::new(LoadingWindowState {
+			has_loaded,
+			maybe_spin_degrees_per_sec: placeholder.maybe_spin_degrees_per_sec,
+			done: false
+		}),
+
+		placeholder.contents,
+		None,
+		top_left,
+		size,
+		None
+	)
+}
@@ -1,9 +1,16 @@
 mod clock;
+mod countdown;
 mod error;
 mod credit;
+mod motd;
+mod json_field;
 mod twilio;
 mod weather;
 mod surprise;
+mod on_air;
+mod loading;
+mod staleness;
+mod drifting;
 mod spinitron;
 mod shared_window_state;
 mod updatable_text_pattern;
@@ -0,0 +1,146 @@
+use std::{borrow::Cow, io::ErrorKind};
+
+use crate::{
+	texture::{TextScrollParams, scroll_with_pause},
+
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		update_rate::{UpdateRateCreator, Seconds}
+	},
+
+	window_tree::{
+		Window,
+		ColorSDL,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	}
+};
+
+//////////
+
+/* This is for studio announcements that don't come from an API. The message of the day is read from a text file,
+which is re-read every so often, so that it can be edited while the dashboard is running. Each non-empty line of the
+file is one announcement. Nothing is shown while the file is missing or empty. The window's top left and size
+are relative to the main window, and by default, it sits in the space under the logo. */
+#[derive(serde::Deserialize)]
+#[serde(default)]
+pub struct MotdConfig {
+	path: String,
+	refresh_interval_secs: Seconds,
+	top_left: (f32, f32),
+	size: (f32, f32)
+}
+
+impl Default for MotdConfig {
+	fn default() -> Self {
+		Self {
+			path: "assets/motd.txt".to_owned(), refresh_interval_secs: 30.0,
+			top_left: (0.58, 0.85), size: (0.4, 0.04)
+		}
+	}
+}
+
+const LINE_SEPARATOR: &str = " | ";
+
+// This returns `None` if the file is missing or has no text (and it keeps the previous text if the file can't be read)
+fn read_motd(path: &str, maybe_prev_text: &Option<String>) -> Option<String> {
+	match std::fs::read_to_string(path) {
+		Ok(contents) => {
+			let lines: Vec<&str> = contents.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+			(!lines.is_empty()).then(|| lines.join(LINE_SEPARATOR))
+		},
+
+		Err(err) if err.kind() == ErrorKind::NotFound => None,
+
+		Err(err) => {
+			log::warn!("Could not read the message of the day from '{path}', so the previous one will be kept: '{err}'.");
+			maybe_prev_text.clone()
+		}
+	}
+}
+
+pub fn make_motd_window(update_rate_creator: UpdateRateCreator, config: MotdConfig,
+	background_contents: WindowContents, text_color: ColorSDL) -> GenericResult<Window> {
+
+	let in_unit_interval = |(x, y): (f32, f32)| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y);
+
+	if !in_unit_interval(config.top_left) || !in_unit_interval(config.size) {
+		return error_msg!("The top left and size of the message of the day window should be between 0 and 1");
+	}
+
+	#[derive(Clone)]
+	struct MotdWindowState {
+		path: String,
+		maybe_text: Option<String> // This is the previous text
+	}
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for MotdWindowState {
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			let wrapped_individual_state = updater_params.window.get_state_mut
+				::<updatable_text_pattern::UpdatableTextWindowFields<MotdWindowState>>();
+
+			let state = &mut wrapped_individual_state.inner;
+			let curr_text = read_motd(&state.path, &state.maybe_text);
+
+			if curr_text == state.maybe_text {
+				return true;
+			}
+
+			let skip_update = curr_text.is_none();
+			state.maybe_text = curr_text;
+			updater_params.window.set_draw_skipping(skip_update);
+
+			skip_update
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), LINE_SEPARATOR)
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(self.maybe_text.as_ref().unwrap())
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			let WindowContents::Many(all_contents) = window_contents
+			else {panic!("The message of the day window contents was expected to be a list!")};
+			&mut all_contents[1]
+		}
+	}
+
+	log::info!("The message of the day will be read from '{}' every {} seconds.", config.path, config.refresh_interval_secs);
+
+	let fields = updatable_text_pattern::UpdatableTextWindowFields {
+		inner: MotdWindowState {path: config.path, maybe_text: None},
+		text_color,
+
+		// Text that fits is left still, and text that is too long scrolls
+		scroll_fn: |seed, text_fits, pause_fraction| {
+			if text_fits {
+				return (0.0, false);
+			}
+
+			(scroll_with_pause(seed, 12.0, pause_fraction), true)
+		},
+
+		scroll_params: TextScrollParams::DEFAULT,
+		update_rate: update_rate_creator.new_instance(config.refresh_interval_secs),
+		maybe_border_color: Some(text_color)
+	};
+
+	let mut window = updatable_text_pattern::make_window(
+		fields,
+		Vec2f::new(config.top_left.0, config.top_left.1),
+		Vec2f::new(config.size.0, config.size.1),
+		WindowContents::Many(vec![background_contents, WindowContents::Nothing])
+	);
+
+	window.set_draw_skipping(true);
+	Ok(window)
+}
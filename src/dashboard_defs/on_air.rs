@@ -0,0 +1,109 @@
+use std::borrow::Cow;
+
+use crate::{
+	texture::TextScrollParams,
+
+	utility_types::{
+		vec2f::Vec2f,
+		update_rate::UpdateRate
+	},
+
+	window_tree::{
+		Window,
+		ColorSDL,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	}
+};
+
+//////////
+
+// This shared fact is set to whether a DJ is live (as a `bool`) on each update of the on-air window
+pub const ON_AIR_IS_LIVE_FACT: &str = "on_air_is_live";
+
+// This is what the on-air indicator looks like when a DJ is live, and when the station is under automation
+#[derive(Clone)]
+pub struct OnAirAppearance {
+	pub live_text: &'static str,
+	pub live_background_color: ColorSDL,
+
+	pub automation_text: &'static str,
+	pub automation_background_color: ColorSDL,
+
+	pub text_color: ColorSDL
+}
+
+/* This is lit up while a DJ is live, and dimmed while the station is under automation. The current playlist's
+automation flag from Spinitron decides which one it is, so this should update at the Spinitron update rate. */
+pub fn make_on_air_window(top_left: Vec2f, size: Vec2f,
+	update_rate: UpdateRate, appearance: OnAirAppearance) -> Window {
+
+	#[derive(Clone)]
+	struct OnAirWindowState {
+		appearance: OnAirAppearance,
+		maybe_is_live: Option<bool> // This is `None` before the first update
+	}
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for OnAirWindowState {
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			let inner_shared_state = updater_params.shared_window_state.get_mut::<SharedWindowState>();
+			let Some(spinitron_state) = &inner_shared_state.maybe_spinitron_state else {return true};
+			let is_live = !spinitron_state.get_playlist().is_automated();
+			inner_shared_state.shared_facts.set(ON_AIR_IS_LIVE_FACT, is_live);
+
+			let state = &mut updater_params.window.get_state_mut
+				::<updatable_text_pattern::UpdatableTextWindowFields<Self>>().inner;
+
+			if state.maybe_is_live == Some(is_live) {
+				return true;
+			}
+
+			state.maybe_is_live = Some(is_live);
+
+			let background_color = if is_live {state.appearance.live_background_color}
+				else {state.appearance.automation_background_color};
+
+			let WindowContents::Many(all_contents) = updater_params.window.get_contents_mut()
+			else {panic!("The on-air window contents was expected to be a list!")};
+
+			all_contents[0] = WindowContents::Color(background_color);
+
+			false
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), "")
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(if self.maybe_is_live == Some(true) {self.appearance.live_text} else {self.appearance.automation_text})
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			let WindowContents::Many(all_contents) = window_contents
+			else {panic!("The on-air window contents was expected to be a list!")};
+			&mut all_contents[1]
+		}
+	}
+
+	let initial_contents = WindowContents::Many(vec![
+		WindowContents::Color(appearance.automation_background_color),
+		WindowContents::Nothing
+	]);
+
+	let fields = updatable_text_pattern::UpdatableTextWindowFields {
+		text_color: appearance.text_color,
+		inner: OnAirWindowState {appearance, maybe_is_live: None},
+		scroll_fn: |_, _, _| (0.0, false),
+		scroll_params: TextScrollParams::DEFAULT,
+		update_rate,
+		maybe_border_color: None
+	};
+
+	updatable_text_pattern::make_window(fields, top_left, size, initial_contents)
+}
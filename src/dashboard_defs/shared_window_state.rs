@@ -1,20 +1,56 @@
+use std::collections::HashMap;
+
 use crate::{
     spinitron::state::SpinitronState,
+    utility_types::dynamic_optional::DynamicOptional,
     texture::{FontInfo, TextureCreationInfo},
-    dashboard_defs::{twilio::TwilioState, clock::ClockHands}
+    dashboard_defs::{twilio::TwilioState, weather::WeatherState, clock::ClockHands, surprise::NumAppearingSurprises}
 };
 
+/* This is a small key/value store that any updater can read and write, for coordinating windows without adding
+a named field to the shared window state for each interaction. Each value is stored in a `DynamicOptional`,
+so a key should always be used with the same type (reading it with another type gives `None`, rather than
+panicking). Values are owned by the store, and they can only be borrowed for as long as the shared window state is,
+so updaters should copy or clone what they need out of it. Setting a fact replaces its old value. The keys are
+best kept as constants next to the code that sets them. */
+#[derive(Default)]
+pub struct SharedFacts {
+	facts: HashMap<&'static str, DynamicOptional>
+}
+
+impl SharedFacts {
+	pub fn get<T: 'static>(&self, key: &str) -> Option<&T> {
+		self.facts.get(key).and_then(DynamicOptional::try_get)
+	}
+
+	pub fn set<T: 'static>(&mut self, key: &'static str, value: T) {
+		self.facts.insert(key, DynamicOptional::new(value));
+	}
+}
+
 pub struct SharedWindowState<'a> {
 	pub clock_hands: ClockHands,
-	pub spinitron_state: SpinitronState,
-	pub twilio_state: TwilioState<'a>,
+
+	/* Each of these is `None` if its dashboard component is disabled (and then no windows that use it are made,
+	and its continual updater thread is never started) */
+	pub maybe_spinitron_state: Option<SpinitronState>,
+	pub maybe_twilio_state: Option<TwilioState<'a>>,
+	pub maybe_weather_state: Option<WeatherState>,
 
 	pub font_info: &'a FontInfo,
 
-	// This is used whenever a texture can't be loaded
+	/* This is used whenever a texture can't be loaded. It is chosen at random from
+	the fallback texture creation infos below, each time the shared window state is updated. */
 	pub fallback_texture_creation_info: &'a TextureCreationInfo<'a>,
+	pub fallback_texture_creation_infos: &'a [TextureCreationInfo<'a>],
+
+	// These are the newest errors first (each one is a single line of text, with its source and time first seen)
+	pub curr_dashboard_errors: Vec<String>,
+
+	// This is shared with the surprise windows (and it stays at zero if surprises are disabled)
+	pub num_appearing_surprises: NumAppearingSurprises,
 
-	pub curr_dashboard_error: Option<String>,
+	pub shared_facts: SharedFacts,
 
 	pub rand_generator: rand::rngs::ThreadRng
 
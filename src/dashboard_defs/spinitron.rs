@@ -1,14 +1,25 @@
 use std::borrow::Cow;
 
 use crate::{
-	dashboard_defs::shared_window_state::SharedWindowState,
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	},
 
-	spinitron::model::{Spin, SpinitronModelName, NUM_SPINITRON_MODEL_TYPES},
+	spinitron::{
+		state::SpinitronState,
+		wrapper_types::MaybeSpinitronModelId,
+		model::{Spin, SpinitronModel, SpinitronModelName, NUM_SPINITRON_MODEL_TYPES}
+	},
 
 	texture::{
+		TextFill,
 		DisplayText,
 		TextDisplayInfo,
-		TextureCreationInfo
+		TextScrollParams,
+		TextMargins,
+		TextureCreationInfo,
+		scroll_with_pause
 	},
 
 	utility_types::{
@@ -22,6 +33,7 @@ use crate::{
 		Window,
 		ColorSDL,
 		WindowContents,
+		WindowBorderRadius,
 		WindowUpdaterParams,
 		PossibleWindowUpdater
 	}
@@ -29,24 +41,69 @@ use crate::{
 
 struct SpinitronModelWindowState {
 	model_name: SpinitronModelName,
-	maybe_text_color: Option<ColorSDL> // If this is `None`, it is not a text window
+	maybe_text_fill: Option<TextFill>, // If this is `None`, it is not a text window
+	maybe_spin_text_part: Option<SpinTextPart> // If this is given, only this part of the spin is shown
+}
+
+#[derive(Copy, Clone)]
+pub enum SpinTextPart {
+	Song,
+	Artist,
+	Release
+}
+
+// This is one line of a spin's text, with its own window and styling
+pub struct SpinTextLineInfo {
+	pub part: SpinTextPart,
+	pub window: SpinitronModelWindowInfo,
+	pub text_fill: TextFill
 }
 
 pub struct SpinitronModelWindowInfo {
 	pub tl: Vec2f,
 	pub size: Vec2f,
-	pub border_color: Option<ColorSDL>
+	pub border_color: Option<ColorSDL>,
+	pub maybe_corner_radius: Option<f32>, // This rounds the texture and the border, as a fraction of their smaller sides
+	pub maybe_background_color: Option<ColorSDL> // This fills the letterboxed area around textures with other aspect ratios
 }
 
 pub struct SpinitronModelWindowsInfo {
 	pub model_name: SpinitronModelName,
 	pub texture_window: Option<SpinitronModelWindowInfo>,
 	pub text_window: Option<SpinitronModelWindowInfo>,
-	pub text_color: ColorSDL
+	pub text_fill: TextFill,
+
+	/* These are only made for spins, and they show the song, artist, and release as separately styled lines
+	(e.g. for a "now playing" card). They can be given alongside the combined text window, or instead of it. */
+	pub spin_text_lines: Vec<SpinTextLineInfo>
 }
 
 //////////
 
+fn get_model_text(spinitron_state: &SpinitronState, model_name: SpinitronModelName) -> Cow<str> {
+	if spinitron_state.is_spin_and_just_expired(model_name) {
+		Cow::Borrowed(Spin::to_string_when_spin_is_expired())
+	}
+	else {
+		Cow::Owned(spinitron_state.get_model_by_name(model_name).to_string())
+	}
+}
+
+// When the spin has expired, only the song line is shown (with the expired spin text)
+fn get_spin_text_part(spinitron_state: &SpinitronState, part: SpinTextPart) -> Option<&str> {
+	if spinitron_state.is_spin_and_just_expired(SpinitronModelName::Spin) {
+		return matches!(part, SpinTextPart::Song).then_some(Spin::to_string_when_spin_is_expired());
+	}
+
+	let spin = spinitron_state.get_spin();
+
+	Some(match part {
+		SpinTextPart::Song => spin.get_song(),
+		SpinTextPart::Artist => spin.get_artist(),
+		SpinTextPart::Release => spin.get_release()
+	})
+}
+
 pub fn make_spinitron_windows(
 	all_model_windows_info: &[SpinitronModelWindowsInfo; NUM_SPINITRON_MODEL_TYPES],
 	model_update_rate: UpdateRate) -> Vec<Window> {
@@ -58,7 +115,7 @@ pub fn make_spinitron_windows(
 	of `area_drawn_to_screen`. */
 	fn spinitron_model_window_updater_fn(params: WindowUpdaterParams) -> MaybeError {
 		let inner_shared_state = params.shared_window_state.get_mut::<SharedWindowState>();
-		let spinitron_state = &mut inner_shared_state.spinitron_state;
+		let spinitron_state = inner_shared_state.maybe_spinitron_state.as_mut().context("Expected Spinitron to be enabled")?;
 
 		let individual_window_state = params.window.get_state::<SpinitronModelWindowState>();
 		let model_name = individual_window_state.model_name;
@@ -74,12 +131,18 @@ pub fn make_spinitron_windows(
 
 		//////////
 
-		let texture_creation_info = if let Some(text_color) = individual_window_state.maybe_text_color {
-			let text = if spinitron_state.is_spin_and_just_expired(model_name) {
-				Cow::Borrowed(Spin::to_string_when_spin_is_expired())
+		let texture_creation_info = if let Some(text_fill) = individual_window_state.maybe_text_fill {
+			let text = if let Some(part) = individual_window_state.maybe_spin_text_part {
+				let Some(text) = get_spin_text_part(spinitron_state, part) else {
+					params.window.set_draw_skipping(true);
+					return Ok(());
+				};
+
+				params.window.set_draw_skipping(false);
+				Cow::Borrowed(text)
 			}
 			else {
-				Cow::Owned(spinitron_state.get_model_by_name(model_name).to_string())
+				get_model_text(spinitron_state, model_name)
 			};
 
 			TextureCreationInfo::Text((
@@ -87,7 +150,8 @@ pub fn make_spinitron_windows(
 
 				TextDisplayInfo {
 					text: DisplayText::new(&text),
-					color: text_color,
+					color: text_fill,
+					maybe_outline_color: None,
 					pixel_area: window_size_pixels, // TODO: why does cutting the max pixel width in half still work?
 
 					/* TODO:
@@ -95,19 +159,43 @@ pub fn make_spinitron_windows(
 					- Make a scroll fn util file
 					- Why doesn't this scroll when the text is short enough? Good, but not programmed in...
 					*/
-					scroll_fn: |seed, _| (seed.sin() * 0.5 + 0.5, false)
+					scroll_fn: |seed, _, _| (seed.sin() * 0.5 + 0.5, false),
+					scroll_params: TextScrollParams::DEFAULT,
+					margins: TextMargins::NONE
 
 				}
 			))
 		}
 		else {
-			// Registering the aspect-ratio-corrected spin window size
-			if matches!(model_name, SpinitronModelName::Spin) {
-				let size = window_size_pixels.0.min(window_size_pixels.1);
-				spinitron_state.register_spin_window_size((size, size));
+			/* Registering the model window size. Only spin textures (album art) are forced to be square,
+			so the aspect-ratio-corrected spin window size is used for them. The other models use their true window size. */
+			let size = if matches!(model_name, SpinitronModelName::Spin) {
+				let min_side = window_size_pixels.0.min(window_size_pixels.1);
+				(min_side, min_side)
 			}
+			else {
+				window_size_pixels
+			};
+
+			spinitron_state.register_model_window_size(model_name, size);
+
+			let texture_creation_info = spinitron_state.get_cached_texture_creation_info(model_name);
 
-			spinitron_state.get_cached_texture_creation_info(model_name)
+			// The transition is set for the whole Spinitron state, and it may be changed at any time
+			return match spinitron_state.get_image_transition() {
+				Some(transition) => params.window.update_texture_with_fade_through(
+					params.texture_pool,
+					&texture_creation_info,
+					inner_shared_state.fallback_texture_creation_info,
+					transition
+				),
+
+				None => params.window.update_texture_without_transition(
+					params.texture_pool,
+					&texture_creation_info,
+					inner_shared_state.fallback_texture_creation_info
+				)
+			};
 		};
 
 		params.window.get_contents_mut().update_as_texture(
@@ -126,16 +214,17 @@ pub fn make_spinitron_windows(
 	all_model_windows_info.iter().flat_map(|general_info| {
 		let mut output_windows = Vec::new();
 
-		let mut maybe_make_model_window =
-			|maybe_info: &Option<SpinitronModelWindowInfo>, maybe_text_color: Option<ColorSDL>| {
+		let mut maybe_make_model_window = |maybe_info: Option<&SpinitronModelWindowInfo>,
+			maybe_text_fill: Option<TextFill>, maybe_spin_text_part: Option<SpinTextPart>| {
 
 			if let Some(info) = maybe_info {
-				output_windows.push(Window::new(
+				let mut window = Window::new(
 					spinitron_model_window_updater,
 
 					DynamicOptional::new(SpinitronModelWindowState {
 						model_name: general_info.model_name,
-						maybe_text_color
+						maybe_text_fill,
+						maybe_spin_text_part
 					}),
 
 					WindowContents::Nothing,
@@ -143,13 +232,275 @@ pub fn make_spinitron_windows(
 					info.tl,
 					info.size,
 					None
-				));
+				);
+
+				window.set_texture_corner_radius(info.maybe_corner_radius);
+				window.set_border_radius(info.maybe_corner_radius.map(WindowBorderRadius::FractionOfSmallerSide));
+				window.set_background_color(info.maybe_background_color);
+				output_windows.push(window);
 			}
 		};
 
-		maybe_make_model_window(&general_info.texture_window, None);
-		maybe_make_model_window(&general_info.text_window, Some(general_info.text_color));
+		maybe_make_model_window(general_info.texture_window.as_ref(), None, None);
+		maybe_make_model_window(general_info.text_window.as_ref(), Some(general_info.text_fill), None);
+
+		if matches!(general_info.model_name, SpinitronModelName::Spin) {
+			for line in &general_info.spin_text_lines {
+				maybe_make_model_window(Some(&line.window), Some(line.text_fill), Some(line.part));
+			}
+		}
 
 		output_windows
 	}).collect()
 }
+
+/* This is a single scrolling line with the current spin's text, which is independent
+of the spin texture and text windows. It should update at the same rate as the Spinitron windows. */
+pub fn make_now_playing_ticker_window(top_left: Vec2f, size: Vec2f,
+	update_rate: UpdateRate, text_color: ColorSDL, maybe_border_color: Option<ColorSDL>,
+	scroll_params: TextScrollParams) -> Window {
+
+	type NowPlayingTickerState = String; // This is the current spin text
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for NowPlayingTickerState {
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			let Some(spinitron_state) = &updater_params.shared_window_state.get::<SharedWindowState>().maybe_spinitron_state
+			else {return true};
+
+			let text = &mut updater_params.window.get_state_mut
+				::<updatable_text_pattern::UpdatableTextWindowFields<NowPlayingTickerState>>().inner;
+
+			let should_update = spinitron_state.model_was_updated(SpinitronModelName::Spin) || text.is_empty();
+
+			if should_update {
+				*text = format!("Now playing: {}", get_model_text(spinitron_state, SpinitronModelName::Spin));
+			}
+
+			!should_update
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), "   ")
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(self)
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			window_contents
+		}
+	}
+
+	let fields = updatable_text_pattern::UpdatableTextWindowFields {
+		inner: String::new(),
+		text_color,
+
+		// This scrolls all the way across the line every 15 seconds (at the default speed), wrapping around at the end
+		scroll_fn: |seed, _, pause_fraction| (scroll_with_pause(seed, 15.0, pause_fraction), true),
+		scroll_params,
+
+		update_rate,
+		maybe_border_color
+	};
+
+	updatable_text_pattern::make_window(fields, top_left, size, WindowContents::Nothing)
+}
+
+/* This shows the previous persona (i.e. the outgoing DJ) for a grace period after the playlist changes.
+It should update at the same rate as the Spinitron windows. Outside of that period, it is not drawn. */
+pub fn make_previous_persona_window(top_left: Vec2f, size: Vec2f,
+	update_rate: UpdateRate, maybe_border_color: Option<ColorSDL>) -> Window {
+
+	type PreviousPersonaWindowState = MaybeSpinitronModelId; // This is the ID of the previous playlist shown
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let spinitron_state = inner_shared_state.maybe_spinitron_state.as_ref().context("Expected Spinitron to be enabled")?;
+
+		let maybe_previous_playlist_id = spinitron_state.get_previous_playlist().map(|playlist| playlist.get_id());
+		let shown_previous_playlist_id = params.window.get_state::<PreviousPersonaWindowState>();
+
+		if maybe_previous_playlist_id == *shown_previous_playlist_id {
+			return Ok(());
+		}
+
+		*params.window.get_state_mut::<PreviousPersonaWindowState>() = maybe_previous_playlist_id;
+
+		let (Some(previous_persona), Some(texture_creation_info)) = (
+			spinitron_state.get_previous_persona(),
+			spinitron_state.get_previous_persona_texture_creation_info()
+		) else {
+			// The grace period ended, so the texture is freed (and a new one is made for the next handoff)
+			params.window.set_draw_skipping(true);
+
+			if let WindowContents::Texture(texture) = std::mem::replace(params.window.get_contents_mut(), WindowContents::Nothing) {
+				params.texture_pool.free_texture(texture)?;
+			}
+
+			return Ok(());
+		};
+
+		log::info!("Showing the previous persona during a handoff: '{}'", previous_persona.to_string());
+		params.window.set_draw_skipping(false);
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	let mut window = Window::new(
+		Some((updater_fn, update_rate)),
+		DynamicOptional::new(None as PreviousPersonaWindowState),
+		WindowContents::Nothing,
+		maybe_border_color,
+		top_left,
+		size,
+		None
+	);
+
+	window.set_draw_skipping(true);
+	window
+}
+
+/* This is a thin bar that fills up as the current spin plays. It is not drawn
+when the spin has no duration, or when the spin is expired. */
+pub fn make_spin_progress_window(top_left: Vec2f, size: Vec2f,
+	update_rate: UpdateRate, track_color: ColorSDL, fill_color: ColorSDL) -> Window {
+
+	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let spinitron_state = params.shared_window_state.get::<SharedWindowState>()
+			.maybe_spinitron_state.as_ref().context("Expected Spinitron to be enabled")?;
+
+		let maybe_progress_fraction = if spinitron_state.spin_is_expired() {None}
+			else {spinitron_state.get_spin().get_progress_fraction()?};
+
+		let Some(progress_fraction) = maybe_progress_fraction else {
+			params.window.set_draw_skipping(true);
+			return Ok(());
+		};
+
+		params.window.set_draw_skipping(false);
+
+		let fill_window = &mut params.window.get_children_mut().context("Expected a fill window")?[0];
+		fill_window.set_size(Vec2f::new(progress_fraction as f32, 1.0));
+
+		Ok(())
+	}
+
+	let fill_window = Window::new(
+		None,
+		DynamicOptional::NONE,
+		WindowContents::Color(fill_color),
+		None,
+		Vec2f::ZERO,
+		Vec2f::new(0.0, 1.0),
+		None
+	);
+
+	let mut window = Window::new(
+		Some((updater_fn, update_rate)),
+		DynamicOptional::NONE,
+		WindowContents::Color(track_color),
+		None,
+		top_left,
+		size,
+		Some(vec![fill_window])
+	);
+
+	window.set_draw_skipping(true);
+	window
+}
+
+/* This shows small badges for the current spin's flags (new releases, and various-artists releases), in a row of
+slots going right from the top-left corner. Badges fill the slots in order, so any combination of flags fits without
+any overlap, and the unused slots are not drawn. It should update at the same rate as the Spinitron windows. */
+pub fn make_spin_badges_window(top_left: Vec2f, slot_size: Vec2f, slot_gap: f32,
+	update_rate: UpdateRate, badge_color: ColorSDL, text_color: ColorSDL) -> Window {
+
+	const NUM_SLOTS: usize = 2;
+
+	struct BadgeSlotState {
+		index: usize,
+		text_color: ColorSDL,
+		maybe_shown_label: Option<&'static str>
+	}
+
+	fn slot_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let spinitron_state = inner_shared_state.maybe_spinitron_state.as_ref().context("Expected Spinitron to be enabled")?;
+
+		let slot_state = params.window.get_state::<BadgeSlotState>();
+
+		let maybe_label = if spinitron_state.spin_is_expired() {None}
+			else {spinitron_state.get_spin().get_badge_labels().get(slot_state.index).copied()};
+
+		if maybe_label == slot_state.maybe_shown_label {
+			return Ok(());
+		}
+
+		let text_color = slot_state.text_color;
+		params.window.get_state_mut::<BadgeSlotState>().maybe_shown_label = maybe_label;
+
+		let Some(label) = maybe_label else {
+			params.window.set_draw_skipping(true);
+			return Ok(());
+		};
+
+		params.window.set_draw_skipping(false);
+
+		let texture_creation_info = TextureCreationInfo::Text((
+			Cow::Borrowed(inner_shared_state.font_info),
+
+			TextDisplayInfo {
+				text: DisplayText::new(label),
+				color: text_color.into(),
+				maybe_outline_color: None,
+				pixel_area: params.area_drawn_to_screen,
+				scroll_fn: |_, _, _| (0.0, false),
+				scroll_params: TextScrollParams::DEFAULT,
+				margins: TextMargins::NONE
+			}
+		));
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	// The slots are laid out within a row window that is just big enough for all of them
+	let row_size = Vec2f::new(NUM_SLOTS as f32 * slot_size.x() + (NUM_SLOTS - 1) as f32 * slot_gap, slot_size.y());
+	let slot_size_in_row = Vec2f::new(slot_size.x() / row_size.x(), 1.0);
+
+	let slot_windows = (0..NUM_SLOTS).map(|index| {
+		let mut slot_window = Window::new(
+			Some((slot_updater_fn, update_rate)),
+			DynamicOptional::new(BadgeSlotState {index, text_color, maybe_shown_label: None}),
+			WindowContents::Nothing,
+			None,
+			Vec2f::new(index as f32 * (slot_size.x() + slot_gap) / row_size.x(), 0.0),
+			slot_size_in_row,
+			None
+		);
+
+		slot_window.set_background_color(Some(badge_color));
+		slot_window.set_draw_skipping(true);
+		slot_window
+	}).collect();
+
+	Window::new(
+		None,
+		DynamicOptional::NONE,
+		WindowContents::Nothing,
+		None,
+		top_left,
+		row_size,
+		Some(slot_windows)
+	)
+}
@@ -0,0 +1,126 @@
+use std::{borrow::Cow, time::Duration};
+
+use crate::{
+	texture::TextScrollParams,
+
+	utility_types::{
+		vec2f::Vec2f,
+		easing_fns::EasingFn,
+		update_rate::{Seconds, UpdateRateCreator}
+	},
+
+	window_tree::{
+		Window,
+		ColorSDL,
+		WindowContents,
+		WindowUpdaterParams
+	},
+
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	}
+};
+
+//////////
+
+pub type TimeSinceUpdateGetter = fn(&SharedWindowState) -> Duration;
+
+// This is a data source to show the staleness of (it is stale once the time since its last update reaches the threshold)
+#[derive(Clone)]
+pub struct StalenessSource {
+	pub name: &'static str,
+	pub get_time_since_update: TimeSinceUpdateGetter,
+	pub stale_threshold: Duration
+}
+
+// This rounds down to the biggest unit that fits (e.g. 90 seconds is '1m')
+fn format_time_since_update(time_since_update: Duration) -> String {
+	let secs = time_since_update.as_secs();
+
+	match secs {
+		0..=59 => format!("{secs}s"),
+		60..=3599 => format!("{}m", secs / 60),
+		3600..=86399 => format!("{}h", secs / 3600),
+		_ => format!("{}d", secs / 86400)
+	}
+}
+
+/* This shows how long ago a data source was last successfully updated. It stays subtle
+(dimmed) while the data is fresh, and becomes fully opaque once the data goes stale. */
+pub fn make_staleness_window(top_left: Vec2f, size: Vec2f,
+	update_rate_creator: UpdateRateCreator, text_color: ColorSDL,
+	source: StalenessSource, fade_easing_fn: EasingFn) -> Window {
+
+	#[derive(Clone)]
+	struct StalenessWindowState {
+		source: StalenessSource,
+		fade_easing_fn: EasingFn,
+		text: String,
+		is_stale: bool
+	}
+
+	const FRESH_OPACITY: f64 = 0.4;
+	const FADE_DURATION_SECS: Seconds = 0.5;
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for StalenessWindowState {
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			let inner_shared_state = updater_params.shared_window_state.get::<SharedWindowState>();
+
+			let state = &mut updater_params.window.get_state_mut
+				::<updatable_text_pattern::UpdatableTextWindowFields<Self>>().inner;
+
+			let time_since_update = (state.source.get_time_since_update)(inner_shared_state);
+			let text = format!("{} updated {} ago", state.source.name, format_time_since_update(time_since_update));
+
+			if text == state.text {
+				return true;
+			}
+
+			state.text = text;
+
+			let is_stale = time_since_update >= state.source.stale_threshold;
+			let stale_state_changed = is_stale != state.is_stale;
+			state.is_stale = is_stale;
+
+			if stale_state_changed {
+				let fade_easing_fn = state.fade_easing_fn;
+				let window = &mut updater_params.window;
+				let curr_opacity = window.get_opacity();
+				let target_opacity = if is_stale {1.0} else {FRESH_OPACITY};
+				window.animate_opacity(curr_opacity, target_opacity, FADE_DURATION_SECS, fade_easing_fn);
+			}
+
+			false
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), " ")
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(&self.text)
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			window_contents
+		}
+	}
+
+	let fields = updatable_text_pattern::UpdatableTextWindowFields {
+		inner: StalenessWindowState {
+			source,
+			fade_easing_fn,
+			text: String::new(),
+			is_stale: true // This starts as opaque, and then fades to being dimmed if the data is fresh
+		},
+
+		text_color,
+		scroll_fn: |_, _, _| (0.0, false),
+		scroll_params: TextScrollParams::DEFAULT,
+		update_rate: update_rate_creator.new_instance(1.0),
+		maybe_border_color: None
+	};
+
+	updatable_text_pattern::make_window(fields, top_left, size, WindowContents::Nothing)
+}
@@ -1,20 +1,23 @@
 use std::{
 	rc::Rc,
+	path::Path,
 	borrow::Cow,
-	cell::RefCell,
-	collections::HashSet,
-	io::{BufRead, BufReader}
+	cell::Cell,
+	time::{Duration, Instant},
+	collections::HashMap,
+	io::{Write, BufRead, BufReader}
 };
 
-use chrono::Timelike;
+use sdl2::render::BlendMode;
+use chrono::{Weekday, Datelike, Timelike};
 
 use interprocess::local_socket::{
 	ToFsName,
 	GenericFilePath,
 	ListenerOptions,
-	traits::Listener,
 	ListenerNonblockingMode,
-	prelude::LocalSocketListener
+	traits::{Listener, Stream},
+	prelude::{LocalSocketListener, LocalSocketStream}
 };
 
 use crate::{
@@ -26,87 +29,256 @@ use crate::{
 
 	utility_types::{
 		generic_result::*,
+		easing_fns::EasingFn,
 		dynamic_optional::DynamicOptional,
 		vec2f::{Vec2f, assert_in_unit_interval},
-		update_rate::{Seconds, UpdateRateCreator}
+		update_rate::{Seconds, UpdateRate, UpdateRateCreator}
 	},
 
 	texture::{TexturePool, TextureCreationInfo},
 	dashboard_defs::shared_window_state::SharedWindowState
 };
 
-/* Note: some surprises may take somewhat long to be
-triggered if their update rates are relatively infrequent.
-TODO: make a separate updater for just getting the artificial
-triggering going (this will be the socket-polling updater). */
+/* Surprises can be triggered artificially by sending their name to a socket
+(the name of a surprise is its texture path's file stem, like 'horrible' for
+'assets/horrible.webp'). The socket is polled every frame, so artificially
+triggered surprises appear right away, and then last for their usual duration.
+
+Surprises are only rolled for randomly when no other surprise is appearing, and when they
+are not in their cooldown period (if they have one). Artificially triggered surprises skip both checks. */
 
 type NumAppearanceSteps = u16;
 type SurpriseAppearanceChance = f64; // 0 to 1
 
+/* A per-minute chance is the chance of a surprise appearing over a minute of real time. It is scaled by the real time
+since the surprise last rolled for appearing, so it stays the same no matter the update rate (or any slow frames).
+A per-update chance is the older behavior, where the chance is rolled as-is once per update (so the chance
+over time depends on the update rate). */
+#[derive(Copy, Clone)]
+pub enum SurpriseChance {
+	PerUpdate(SurpriseAppearanceChance),
+	PerMinute(SurpriseAppearanceChance)
+}
+
+impl SurpriseChance {
+	const fn get_inner(self) -> SurpriseAppearanceChance {
+		match self {
+			Self::PerUpdate(chance) | Self::PerMinute(chance) => chance
+		}
+	}
+
+	// This is the chance of appearing for one roll, given the time since the last one
+	fn get_chance_for_roll(self, secs_since_last_roll: Seconds) -> SurpriseAppearanceChance {
+		match self {
+			Self::PerUpdate(chance) => chance,
+
+			// This is the chance of appearing at least once over the time since the last roll
+			Self::PerMinute(chance) => 1.0 - (1.0 - chance).powf(secs_since_last_roll / 60.0)
+		}
+	}
+
+	// This is the average number of seconds between appearances
+	fn get_approx_secs_between_appearances(self, update_rate_secs: Seconds) -> Seconds {
+		match self {
+			Self::PerUpdate(chance) => update_rate_secs / chance,
+			Self::PerMinute(chance) => 60.0 / chance
+		}
+	}
+}
+
+/* With this, a surprise fades in when it appears, and fades out after its usual duration
+(rather than snapping in and out). Fading works through the window's opacity, which is applied
+as an alpha mod; so with an additive blend mode, the added brightness fades. Since SDL ignores
+the alpha mod for textures without blending, those are blended normally instead. */
+#[derive(Copy, Clone)]
+pub struct SurpriseFade {
+	pub duration: Seconds,
+	pub easing_fn: EasingFn
+}
+
 pub struct SurpriseCreationInfo<'a> {
 	pub texture_path: &'a str,
-	pub texture_blend_mode: sdl2::render::BlendMode,
+	pub texture_blend_mode: BlendMode,
 
 	pub update_rate: chrono::Duration,
 	pub num_update_steps_to_appear_for: NumAppearanceSteps,
-	pub chance_of_appearing_when_updating: SurpriseAppearanceChance,
+	pub chance_of_appearing: SurpriseChance,
 
+	/* If the start hour is after the end hour, the hour range wraps around midnight.
+	The allowed weekdays are the days that the hour range starts on (so for an hour range
+	of 22 to 2 on Fridays, the surprise can appear from Friday at 22:00 to Saturday at 2:59).
+	If no weekdays are given, every day is allowed. */
 	pub local_hours_24_start: u8,
 	pub local_hours_24_end: u8,
+	pub allowed_weekdays: Vec<Weekday>,
+
+	pub flicker_window: bool,
+	pub maybe_fade: Option<SurpriseFade>,
 
-	pub flicker_window: bool
+	// If this is given, the surprise will not randomly appear again until this long after it last disappeared
+	pub maybe_cooldown: Option<chrono::Duration>
 }
 
 // TODO: display DJ tips as surprises
 
 //////////
 
+// This triggers a surprise through the same socket that external processes use
+pub fn trigger_surprise_artificially(artificial_triggering_socket_path: &str, surprise_name: &str) -> MaybeError {
+	let socket_name = artificial_triggering_socket_path.to_fs_name::<GenericFilePath>()?;
+	let mut stream = LocalSocketStream::connect(socket_name)?;
+	writeln!(stream, "{surprise_name}").to_generic()
+}
+
+fn get_surprise_name(texture_path: &str) -> GenericResult<&str> {
+	Path::new(texture_path).file_stem().and_then(|stem| stem.to_str())
+		.context(format!("Could not get a surprise name from the texture path '{texture_path}'"))
+}
+
+/* A surprise can only randomly appear when no surprises are appearing, and when it is not in its cooldown
+(which is the case if it has no cooldown, has not disappeared yet, or disappeared at least a cooldown ago). */
+fn surprise_can_randomly_appear(num_appearing_surprises: usize,
+	maybe_cooldown: Option<Duration>, maybe_time_since_last_disappearance: Option<Duration>) -> bool {
+
+	let in_cooldown = maybe_cooldown.zip(maybe_time_since_last_disappearance)
+		.is_some_and(|(cooldown, time_since_last_disappearance)| time_since_last_disappearance < cooldown);
+
+	num_appearing_surprises == 0 && !in_cooldown
+}
+
+// This is the number of surprises that are appearing at the moment (it is shared between all of the surprises)
+pub type NumAppearingSurprises = Rc<Cell<usize>>;
+
+//////////
+
 pub fn make_surprise_window(
 	top_left: Vec2f, size: Vec2f,
 	artificial_triggering_socket_path: &str,
 	surprise_creation_info: &[SurpriseCreationInfo],
+	num_appearing_surprises: NumAppearingSurprises,
 	update_rate_creator: UpdateRateCreator,
 	texture_pool: &mut TexturePool) -> GenericResult<Window> {
 
 	////////// Some internally used types
 
-	type SurprisePath=Rc<String>;
-
-	struct SharedSurpriseInfo {
-		surprise_path_set: HashSet<SurprisePath>,
-		queued_surprise_paths: Vec<SurprisePath>, // A multiset would be better here...
-		surprise_stream_listener: LocalSocketListener,
-		surprise_stream_path_buffer: String
+	struct SurpriseTriggeringInfo {
+		surprise_indices_by_name: HashMap<String, usize>,
+		surprise_stream_listener: LocalSocketListener
 	}
 
 	struct SurpriseInfo {
-		path: SurprisePath,
+		name: String,
 
 		num_update_steps_to_appear_for: NumAppearanceSteps,
-		chance_of_appearing_when_updating: SurpriseAppearanceChance, // 0 to 1
+		chance_of_appearing: SurpriseChance,
+		update_rate_secs: Seconds,
+		maybe_last_roll_time: Option<Instant>,
 		curr_num_steps_when_appeared: Option<NumAppearanceSteps>, // if this is `None`, we are not in the appearance period
 
 		local_hours_24_start: u8,
 		local_hours_24_end: u8,
+		allowed_weekdays: Vec<Weekday>,
 		flicker_window: bool,
+		maybe_fade: Option<SurpriseFade>,
+
+		maybe_cooldown: Option<Duration>,
+		maybe_last_disappearance_time: Option<Instant>,
+
+		num_appearing_surprises: NumAppearingSurprises
+	}
+
+	impl SurpriseInfo {
+		// If this surprise is already appearing, this restarts its appearance
+		fn start_appearing(&mut self) {
+			if self.curr_num_steps_when_appeared.replace(0).is_none() {
+				self.num_appearing_surprises.set(self.num_appearing_surprises.get() + 1);
+			}
+		}
+
+		fn stop_appearing(&mut self) {
+			self.curr_num_steps_when_appeared = None;
+			self.maybe_last_disappearance_time = Some(Instant::now());
+			self.num_appearing_surprises.set(self.num_appearing_surprises.get() - 1);
+		}
 
-		// This is wrapped in a `Rc<RefCell<_>>` because the info is shared and mutable
-		shared_info: Rc<RefCell<SharedSurpriseInfo>>
+		fn can_randomly_appear(&self) -> bool {
+			surprise_can_randomly_appear(self.num_appearing_surprises.get(), self.maybe_cooldown,
+				self.maybe_last_disappearance_time.map(|last_disappearance_time| last_disappearance_time.elapsed()))
+		}
 	}
 
 	////////// Some utility functions
 
-	fn appearance_was_randomly_triggered(surprise_info: &SurpriseInfo, rand_generator: &mut rand::rngs::ThreadRng) -> bool {
-		let local_hour = chrono::Local::now().hour();
+	fn in_acceptable_time_range(surprise_info: &SurpriseInfo) -> bool {
+		let local_time = chrono::Local::now();
+		let local_hour = local_time.hour();
+
+		let (start, end) = (surprise_info.local_hours_24_start.into(), surprise_info.local_hours_24_end.into());
 
-		let in_acceptable_hour_range =
-			local_hour >= surprise_info.local_hours_24_start.into()
-			&& local_hour <= surprise_info.local_hours_24_end.into();
+		let (in_acceptable_hour_range, range_started_yesterday) = if start <= end {
+			(local_hour >= start && local_hour <= end, false)
+		}
+		else {
+			(local_hour >= start || local_hour <= end, local_hour <= end)
+		};
+
+		let weekday_when_range_started = if range_started_yesterday {local_time.weekday().pred()} else {local_time.weekday()};
+
+		let on_acceptable_weekday = surprise_info.allowed_weekdays.is_empty()
+			|| surprise_info.allowed_weekdays.contains(&weekday_when_range_started);
+
+		in_acceptable_hour_range && on_acceptable_weekday
+	}
+
+	fn appearance_was_randomly_triggered(surprise_info: &SurpriseInfo,
+		rand_generator: &mut rand::rngs::ThreadRng, secs_since_last_roll: Seconds) -> bool {
 
 		use rand::Rng; // TODO: can I use the system's rand generator instead? Less dependencies that way...
 		let rand_num = rand_generator.gen::<SurpriseAppearanceChance>();
 
-		in_acceptable_hour_range && rand_num < surprise_info.chance_of_appearing_when_updating
+		in_acceptable_time_range(surprise_info) && rand_num < surprise_info.chance_of_appearing.get_chance_for_roll(secs_since_last_roll)
+	}
+
+	////////// The updater function that polls the socket for artificially triggered surprises
+
+	fn artificial_triggering_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		const SURPRISE_NAME_BUFFER_INITIAL_SIZE: usize = 64;
+
+		let triggering_info = params.window.get_state::<SurpriseTriggeringInfo>();
+
+		/* TODO: include some error handling here (should I care
+		about the "resource temporarily unavailable" thing?) */
+		let Ok(stream) = triggering_info.surprise_stream_listener.accept() else {return Ok(())};
+
+		let mut surprise_name = String::with_capacity(SURPRISE_NAME_BUFFER_INITIAL_SIZE);
+		let _ = BufReader::new(stream).read_line(&mut surprise_name);
+		let surprise_name = surprise_name.trim();
+
+		let Some(&surprise_index) = triggering_info.surprise_indices_by_name.get(surprise_name) else {
+			log::warn!("Tried to trigger a surprise with a name of '{surprise_name}', but no surprise has that name!");
+			return Ok(());
+		};
+
+		let surprise_window = &mut params.window.get_children_mut().context("Expected surprise windows")?[surprise_index];
+		let surprise_info = surprise_window.get_state_mut::<SurpriseInfo>();
+		let maybe_fade = surprise_info.maybe_fade;
+
+		if surprise_info.curr_num_steps_when_appeared.is_some() {
+			log::info!("Tried to trigger the surprise '{surprise_name}' artificially, but it is already appearing.");
+			return Ok(());
+		}
+
+		/* The surprise's own updater continues from here (so it flickers if it should,
+		and disappears after its usual duration). Its blend mode was set when its texture was made. */
+		log::info!("Trigger surprise '{surprise_name}' artificially!");
+		surprise_info.start_appearing();
+		surprise_window.set_draw_skipping(false);
+
+		if let Some(fade) = maybe_fade {
+			surprise_window.animate_opacity(0.0, 1.0, fade.duration, fade.easing_fn);
+		}
+
+		Ok(())
 	}
 
 	////////// The core updater function that runs once every N milliseconds for each surprise
@@ -114,56 +286,52 @@ pub fn make_surprise_window(
 	fn updater_fn(params: WindowUpdaterParams) -> MaybeError {
 		let surprise_info = params.window.get_state_mut::<SurpriseInfo>();
 		let rand_generator = &mut params.shared_window_state.get_mut::<SharedWindowState>().rand_generator;
+		let maybe_fade = surprise_info.maybe_fade;
 
-		let not_currently_active = surprise_info.curr_num_steps_when_appeared.is_none();
+		/* The time since the last roll is tracked even when not rolling, so that a surprise
+		coming out of its cooldown does not get a roll covering all of the cooldown */
+		let now = Instant::now();
 
-		// The braces are here to keep the borrow checker happy
-		let trigger_appearance_artificially = not_currently_active && {
-			let mut shared_info = surprise_info.shared_info.borrow_mut();
+		let secs_since_last_roll = surprise_info.maybe_last_roll_time.map_or(surprise_info.update_rate_secs,
+			|last_roll_time| now.duration_since(last_roll_time).as_secs_f64());
 
-			/* TODO: include some error handling here (should I care
-			about the "resource temporarily unavailable" thing?) */
-			if let Some(Ok(stream)) = shared_info.surprise_stream_listener.next() {
-				let mut reader = BufReader::new(stream);
-				let _ = reader.read_line(&mut shared_info.surprise_stream_path_buffer);
+		surprise_info.maybe_last_roll_time = Some(now);
 
-				if let Some(matching_path) = shared_info.surprise_path_set.get(&shared_info.surprise_stream_path_buffer) {
-					let rc_cloned_matching_path = matching_path.clone();
-					shared_info.queued_surprise_paths.push(rc_cloned_matching_path);
-				}
-				else {
-					log::warn!("Tried to trigger a surprise with a path of '{}', but no surprise has that path!",
-						shared_info.surprise_stream_path_buffer);
-				}
+		/* This only rolls for the surprise when it is allowed to appear (so the chance
+		of it appearing only applies over the updates where it could) */
+		let trigger_appearance_by_chance = surprise_info.can_randomly_appear()
+			&& appearance_was_randomly_triggered(surprise_info, rand_generator, secs_since_last_roll);
 
-				shared_info.surprise_stream_path_buffer.clear();
-			}
+		if trigger_appearance_by_chance {
+			log::info!("Trigger surprise '{}'!", surprise_info.name);
+			surprise_info.start_appearing();
 
-			// This runs if the path of the current surprise (per this updater call) is in the queue
-			if let Some(index_in_queue) = shared_info.queued_surprise_paths.iter().position(|s| s == &surprise_info.path) {
-				shared_info.queued_surprise_paths.remove(index_in_queue);
-				true
-			}
-			else {
-				false
+			if let Some(fade) = maybe_fade {
+				params.window.animate_opacity(0.0, 1.0, fade.duration, fade.easing_fn);
 			}
-		};
-
-		let trigger_appearance_by_chance = appearance_was_randomly_triggered(surprise_info, rand_generator);
-
-		if (trigger_appearance_by_chance || trigger_appearance_artificially) && not_currently_active {
-			log::info!("Trigger surprise with path '{}'!", surprise_info.path);
-			surprise_info.curr_num_steps_when_appeared = Some(0);
 		}
 
+		let surprise_info = params.window.get_state_mut::<SurpriseInfo>();
+
 		if let Some(num_steps_when_appeared) = &mut surprise_info.curr_num_steps_when_appeared {
 			*num_steps_when_appeared += 1;
 
 			let stop_showing = *num_steps_when_appeared == surprise_info.num_update_steps_to_appear_for + 1;
 
 			let should_skip_drawing = if stop_showing {
-				surprise_info.curr_num_steps_when_appeared = None;
-				true
+				surprise_info.stop_appearing();
+
+				/* When fading, the window stays drawn while it fades out
+				(and once its opacity reaches zero, it is not drawn anyway) */
+				match maybe_fade {
+					Some(fade) => {
+						let curr_opacity = params.window.get_opacity();
+						params.window.animate_opacity(curr_opacity, 0.0, fade.duration, fade.easing_fn);
+						false
+					},
+
+					None => true
+				}
 			}
 			else if surprise_info.flicker_window {
 				!params.window.drawing_is_skipped()
@@ -178,18 +346,16 @@ pub fn make_surprise_window(
 		Ok(())
 	}
 
-	////////// First, checking for duplicate paths, and failing if this is the case
+	////////// First, checking for duplicate names, and failing if this is the case
 
-	let surprise_paths: Vec<SurprisePath> = surprise_creation_info.iter().map(|info| info.texture_path.to_string().into()).collect();
-	let surprise_path_set: HashSet<SurprisePath> = surprise_paths.iter().map(Rc::clone).collect();
+	let surprise_names = surprise_creation_info.iter().map(|info| get_surprise_name(info.texture_path)).collect::<GenericResult<Vec<_>>>()?;
+	let surprise_indices_by_name: HashMap<String, usize> = surprise_names.iter().enumerate().map(|(index, name)| (name.to_string(), index)).collect();
 
-	if surprise_path_set.len() != surprise_creation_info.len() {
-		return error_msg!("There are duplicate paths in the set of surprises");
+	if surprise_indices_by_name.len() != surprise_creation_info.len() {
+		return error_msg!("There are duplicate names in the set of surprises");
 	}
 
-	////////// Setting up the shared surprise info that can be triggered via signals
-
-	const SURPRISE_STREAM_PATH_BUFFER_INITIAL_SIZE: usize = 64;
+	////////// Setting up the listener for triggering surprises artificially
 
 	let options = ListenerOptions::new().name(artificial_triggering_socket_path.to_fs_name::<GenericFilePath>()?);
 
@@ -208,18 +374,13 @@ pub fn make_surprise_window(
 
 	surprise_stream_listener.set_nonblocking(ListenerNonblockingMode::Both)?;
 
-	let shared_surprise_info = Rc::new(RefCell::new(SharedSurpriseInfo {
-		surprise_path_set,
-		queued_surprise_paths: Vec::new(),
-		surprise_stream_listener,
-		surprise_stream_path_buffer: String::with_capacity(SURPRISE_STREAM_PATH_BUFFER_INITIAL_SIZE)
-	}));
+	let triggering_info = SurpriseTriggeringInfo {surprise_indices_by_name, surprise_stream_listener};
 
 	////////// Making the surprise windows
 
 	let surprise_windows = surprise_creation_info.iter().enumerate().map(
 		|(index, creation_info)| {
-			assert_in_unit_interval(creation_info.chance_of_appearing_when_updating as f32);
+			assert_in_unit_interval(creation_info.chance_of_appearing.get_inner() as f32);
 
 			/* The lower bound checks that it actually appears, and the upper
 			bound checks that the ` + 1` in the updater does not overflow */
@@ -237,13 +398,16 @@ pub fn make_surprise_window(
 				creation_info.update_rate.subsec_nanos() as Seconds / 1_000_000_000.0;
 
 			log::info!(
-				"Surprise '{}' will occur approximately every {:.3} seconds (from {}:00 to {}:00), and then {} for {:.3} seconds{}.",
-				creation_info.texture_path,
-				update_rate_secs / creation_info.chance_of_appearing_when_updating as Seconds,
+				"Surprise '{}' will occur approximately every {:.3} seconds (from {}:00 to {}:59, {}), and then {} for {:.3} seconds{}.",
+				surprise_names[index],
+				creation_info.chance_of_appearing.get_approx_secs_between_appearances(update_rate_secs),
 
 				creation_info.local_hours_24_start,
 				creation_info.local_hours_24_end,
 
+				if creation_info.allowed_weekdays.is_empty() {Cow::Borrowed("on any day")}
+				else {Cow::Owned(format!("starting on {:?}", creation_info.allowed_weekdays))},
+
 				if creation_info.flicker_window {"flicker"} else {"persist"},
 				update_rate_secs * creation_info.num_update_steps_to_appear_for as Seconds,
 
@@ -253,27 +417,46 @@ pub fn make_surprise_window(
 
 			//////////
 
+			let maybe_cooldown = creation_info.maybe_cooldown.map(|cooldown| cooldown.to_std()).transpose()
+				.to_generic().context(format!("The cooldown for the surprise '{}' was negative", surprise_names[index]))?;
+
 			let update_rate = update_rate_creator.new_instance(update_rate_secs);
 			let texture_creation_info = TextureCreationInfo::Path(Cow::Borrowed(creation_info.texture_path));
 
 			let texture = texture_pool.make_texture(&texture_creation_info)?;
-			texture_pool.set_blend_mode_for(&texture, creation_info.texture_blend_mode);
+
+			let blend_mode = match (creation_info.maybe_fade, creation_info.texture_blend_mode) {
+				(Some(_), BlendMode::None) => {
+					log::warn!("The surprise '{}' fades, but has no blend mode, so it will be blended normally instead.", surprise_names[index]);
+					BlendMode::Blend
+				},
+
+				(_, blend_mode) => blend_mode
+			};
+
+			texture_pool.set_blend_mode_for(&texture, blend_mode);
 
 			let mut window = Window::new(
 				Some((updater_fn, update_rate)),
 
 				DynamicOptional::new(SurpriseInfo {
-					path: surprise_paths[index].clone(),
+					name: surprise_names[index].to_string(),
 
 					num_update_steps_to_appear_for: creation_info.num_update_steps_to_appear_for,
-					chance_of_appearing_when_updating: creation_info.chance_of_appearing_when_updating,
+					chance_of_appearing: creation_info.chance_of_appearing,
+					update_rate_secs,
+					maybe_last_roll_time: None,
 					curr_num_steps_when_appeared: None,
 
 					local_hours_24_start: creation_info.local_hours_24_start,
 					local_hours_24_end: creation_info.local_hours_24_end,
+					allowed_weekdays: creation_info.allowed_weekdays.clone(),
 					flicker_window: creation_info.flicker_window,
+					maybe_fade: creation_info.maybe_fade,
 
-					shared_info: shared_surprise_info.clone()
+					maybe_cooldown,
+					maybe_last_disappearance_time: None,
+					num_appearing_surprises: num_appearing_surprises.clone()
 				}),
 
 				WindowContents::Texture(texture),
@@ -290,8 +473,8 @@ pub fn make_surprise_window(
 	).collect::<GenericResult<_>>()?;
 
 	Ok(Window::new(
-		None,
-		DynamicOptional::NONE,
+		Some((artificial_triggering_updater_fn, UpdateRate::ONCE_PER_FRAME)),
+		DynamicOptional::new(triggering_info),
 		WindowContents::Nothing,
 		None,
 		top_left,
@@ -299,3 +482,28 @@ pub fn make_surprise_window(
 		Some(surprise_windows)
 	))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn surprise_can_randomly_appear() {
+		let (secs, cooldown) = (Duration::from_secs, Some(Duration::from_secs(60)));
+		let can_appear = super::surprise_can_randomly_appear;
+
+		// Without a cooldown, or before the first disappearance, only other appearing surprises block it
+		assert!(can_appear(0, None, Some(secs(0))));
+		assert!(can_appear(0, cooldown, None));
+		assert!(!can_appear(1, None, None));
+
+		// Within the cooldown it is blocked, and once the full cooldown has passed it is not
+		assert!(!can_appear(0, cooldown, Some(secs(0))));
+		assert!(!can_appear(0, cooldown, Some(secs(59))));
+		assert!(can_appear(0, cooldown, Some(secs(60))));
+		assert!(can_appear(0, cooldown, Some(secs(3600))));
+
+		// Being out of the cooldown does not let it appear over another surprise
+		assert!(!can_appear(2, cooldown, Some(secs(3600))));
+	}
+}
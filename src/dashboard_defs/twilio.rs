@@ -1,8 +1,9 @@
 use chrono::DateTime;
-use std::{sync::Arc, borrow::Cow, collections::HashMap};
+use std::{sync::Arc, borrow::Cow, collections::HashMap, time::{Duration, Instant}};
 
 use crate::{
 	request,
+	test_data,
 
 	utility_types::{
 		vec2f::Vec2f,
@@ -13,8 +14,8 @@ use crate::{
 	},
 
 	dashboard_defs::shared_window_state::SharedWindowState,
-	window_tree::{ColorSDL, Window, WindowContents, WindowUpdaterParams},
-	texture::{FontInfo, DisplayText, TextDisplayInfo, TextureCreationInfo, TextureHandle, TexturePool}
+	window_tree::{ColorSDL, Window, WindowBorderRadius, WindowContents, WindowUpdaterParams, WindowClickParams},
+	texture::{FontInfo, DisplayText, TextDisplayInfo, TextScrollParams, TextMargins, TextureCreationInfo, TextureHandle, TexturePool, scroll_with_pause}
 };
 
 // TODO: split this file up into some smaller files
@@ -210,7 +211,10 @@ pub struct TwilioState<'a> {
 	texture_subpool_manager: TextureSubpoolManager,
 	id_to_texture_map: SyncedMessageMap<TextureHandle>, // TODO: integrate the subpool manager into this with the searching operations
 	historically_sorted_messages_by_id: Vec<MessageID>, // TODO: avoid resorting with smart insertions and deletions?
-	text_texture_creation_info_cache: Option<((u32, u32), &'a FontInfo, ColorSDL)>
+	text_texture_creation_info_cache: Option<((u32, u32), &'a FontInfo, ColorSDL, TextScrollParams)>,
+
+	// This is the message shown in the focus window (if any), and when it was focused (see `make_twilio_focus_window`)
+	maybe_focused_message: Option<(MessageID, Instant)>
 }
 
 //////////
@@ -238,13 +242,20 @@ impl TwilioStateData {
 	}
 
 	fn do_twilio_request(&self, endpoint: &str, path_params: &[Cow<str>], query_params: &[(&str, Cow<str>)]) -> GenericResult<serde_json::Value> {
+		if test_data::is_enabled() {
+			return test_data::get_next_response(&format!("twilio/{endpoint}"));
+		}
+
 		let base_url = format!("https://api.twilio.com/2010-04-01/Accounts/{}/{endpoint}.json", self.immutable.account_sid);
 		let request_url = request::build_url(&base_url, path_params, query_params);
 
-		request::as_type(request::get_with_maybe_header(
+		let json = request::as_type(request::get_with_maybe_header(
 			&request_url, // TODO: cache the requests, and why is there a 11200 error in the response for messages?
 			Some(("Authorization", &self.immutable.request_auth))
-		))
+		))?;
+
+		test_data::maybe_dump_response(&format!("twilio/{endpoint}"), &json);
+		Ok(json)
 	}
 
 	//////////
@@ -422,12 +433,26 @@ impl Updatable for TwilioStateData {
 /* TODO: eventually, integrate `new` into `Updatable`, and
 reduce the boilerplate for the `Updatable` stuff in general */
 impl TwilioState<'_> {
+	/* The texture subpool, the message maps, and the history windows all grow with the number of messages in the history,
+	and each message has its own text texture. A misconfigured count could use up the memory on the Pi (and make history
+	rows too thin to read anyway), so it is clamped to this. */
+	const MAX_SAFE_NUM_MESSAGES_IN_HISTORY: usize = 30;
+
 	pub fn new(
 		account_sid: &str, auth_token: &str,
 		max_num_messages_in_history: usize,
 		message_history_duration: chrono::Duration,
 		reveal_texter_identities: bool) -> Self {
 
+		let clamped_max_num_messages_in_history = max_num_messages_in_history.clamp(1, Self::MAX_SAFE_NUM_MESSAGES_IN_HISTORY);
+
+		if clamped_max_num_messages_in_history != max_num_messages_in_history {
+			log::warn!("The maximum number of messages in the Twilio history was {max_num_messages_in_history}, \
+				which is outside of the safe range, so it was clamped to {clamped_max_num_messages_in_history}.");
+		}
+
+		let max_num_messages_in_history = clamped_max_num_messages_in_history;
+
 		let data = TwilioStateData::new(
 			account_sid, auth_token, max_num_messages_in_history,
 			message_history_duration, reveal_texter_identities
@@ -438,14 +463,39 @@ impl TwilioState<'_> {
 			texture_subpool_manager: TextureSubpoolManager::new(max_num_messages_in_history),
 			id_to_texture_map: SyncedMessageMap::new(max_num_messages_in_history),
 			historically_sorted_messages_by_id: Vec::new(),
-			text_texture_creation_info_cache: None
+			text_texture_creation_info_cache: None,
+			maybe_focused_message: None
 		}
 	}
 
+	pub fn time_since_last_update(&self) -> std::time::Duration {
+		self.continually_updated.time_since_last_success()
+	}
+
+	pub fn request_refresh(&mut self) {
+		self.continually_updated.request_refresh();
+	}
+
+	pub const fn is_refresh_pending(&self) -> bool {
+		self.continually_updated.is_refresh_pending()
+	}
+
+	// Until this is true, there are no messages yet only because the first request has not finished
+	pub const fn has_loaded(&self) -> bool {
+		self.continually_updated.has_gotten_new_data()
+	}
+
+	// Focusing the message that is already focused unfocuses it
+	fn toggle_focused_message(&mut self, message_index: usize) {
+		let Some(message_id) = self.historically_sorted_messages_by_id.get(message_index) else {return};
+		let is_focused = self.maybe_focused_message.as_ref().is_some_and(|(focused_id, _)| focused_id == message_id);
+		self.maybe_focused_message = (!is_focused).then(|| (message_id.clone(), Instant::now()));
+	}
+
 	// This returns false if something failed with the continual updater.
 	pub fn update(&mut self, texture_pool: &mut TexturePool) -> GenericResult<bool> {
 		// TODO: change other instances of `if-let` to this form
-		let Some((pixel_area, font_info, text_color)) = self.text_texture_creation_info_cache else {
+		let Some((pixel_area, font_info, text_color, scroll_params)) = self.text_texture_creation_info_cache else {
 			// println!("It has not been cached yet, so wait for the next iteration");
 			return Ok(true);
 		};
@@ -461,21 +511,19 @@ impl TwilioState<'_> {
 
 			TextDisplayInfo {
 				text: DisplayText::new(""),
-				color: text_color,
+				color: text_color.into(),
+				maybe_outline_color: None,
 				pixel_area,
 
-				scroll_fn: |seed, text_fits_in_box| {
+				scroll_fn: |seed, text_fits_in_box, pause_fraction| {
 					if text_fits_in_box {return (0.0, true);}
+					(scroll_with_pause(seed, 4.0, pause_fraction), true)
+				},
 
-					let total_cycle_time = 4.0;
-					let scroll_time_percent = 0.75;
-
-					let wait_boundary = total_cycle_time * scroll_time_percent;
-					let scroll_value = seed % total_cycle_time;
+				scroll_params,
 
-					let scroll_fract = if scroll_value < wait_boundary {scroll_value / wait_boundary} else {0.0};
-					(scroll_fract, true)
-				}
+				// This keeps the message text off of the rounded edges of the text bubble
+				margins: TextMargins {fraction: (0.03, 0.1), pixels: (0, 0)}
 			}
 		));
 
@@ -545,19 +593,36 @@ impl TwilioState<'_> {
 
 //////////
 
+// This is what the message history window looks like (the top box is above the history, and it shows the phone number)
+pub struct TwilioWindowAppearance {
+	pub top_box_height: f32,
+	pub top_box_contents: WindowContents,
+
+	pub message_background_contents: WindowContents,
+	pub message_background_contents_text_crop_factor: Vec2f,
+	pub message_scroll_params: TextScrollParams,
+
+	pub overall_border_color: ColorSDL,
+	pub maybe_history_border_radius: Option<WindowBorderRadius>,
+	pub text_color: ColorSDL
+}
+
 pub fn make_twilio_window(
 	twilio_state: &TwilioState,
 	update_rate: UpdateRate,
 	top_left: Vec2f, size: Vec2f,
-	top_box_height: f32,
-	top_box_contents: WindowContents,
-	message_background_contents_text_crop_factor: Vec2f,
-	overall_border_color: ColorSDL, text_color: ColorSDL,
-	message_background_contents: WindowContents) -> Window {
+	appearance: TwilioWindowAppearance) -> Window {
+
+	let TwilioWindowAppearance {
+		top_box_height, top_box_contents,
+		message_background_contents, message_background_contents_text_crop_factor, message_scroll_params,
+		overall_border_color, maybe_history_border_radius, text_color
+	} = appearance;
 
 	struct TwilioHistoryWindowState {
 		message_index: usize,
-		text_color: ColorSDL
+		text_color: ColorSDL,
+		scroll_params: TextScrollParams
 	}
 
 	////////// Making a series of history windows
@@ -566,7 +631,7 @@ pub fn make_twilio_window(
 
 	fn history_updater_fn(params: WindowUpdaterParams) -> MaybeError {
 		let inner_shared_state = params.shared_window_state.get_mut::<SharedWindowState>();
-		let twilio_state = &mut inner_shared_state.twilio_state;
+		let twilio_state = inner_shared_state.maybe_twilio_state.as_mut().context("Expected Twilio to be enabled")?;
 		let individual_window_state = params.window.get_state::<TwilioHistoryWindowState>();
 		let sorted_message_ids = &twilio_state.historically_sorted_messages_by_id;
 
@@ -575,7 +640,8 @@ pub fn make_twilio_window(
 			twilio_state.text_texture_creation_info_cache = Some((
 				params.area_drawn_to_screen,
 				inner_shared_state.font_info,
-				individual_window_state.text_color
+				individual_window_state.text_color,
+				individual_window_state.scroll_params
 			));
 		}
 
@@ -605,11 +671,19 @@ pub fn make_twilio_window(
 
 	let history_window_height = 1.0 / max_num_messages_in_history as f32;
 
+	// Clicking a message shows it in the focus window (if there is one)
+	fn history_click_handler(params: WindowClickParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get_mut::<SharedWindowState>();
+		let twilio_state = inner_shared_state.maybe_twilio_state.as_mut().context("Expected Twilio to be enabled")?;
+		twilio_state.toggle_focused_message(params.window.get_state::<TwilioHistoryWindowState>().message_index);
+		Ok(())
+	}
+
 	let all_subwindows = (0..max_num_messages_in_history).rev().map(|i| {
 		// Note: I can't directly put the background contents into the history windows since it's sized differently
-		let history_window = Window::new(
+		let mut history_window = Window::new(
 			Some((history_updater_fn, update_rate)),
-			DynamicOptional::new(TwilioHistoryWindowState {message_index: i, text_color}),
+			DynamicOptional::new(TwilioHistoryWindowState {message_index: i, text_color, scroll_params: message_scroll_params}),
 			WindowContents::Nothing,
 			None,
 			cropped_text_tl_in_history_window,
@@ -617,6 +691,8 @@ pub fn make_twilio_window(
 			None
 		);
 
+		history_window.set_click_handler(Some(history_click_handler));
+
 		// This is just the history window with the background contents
 		let mut with_background_contents = Window::new(
 			None,
@@ -638,7 +714,7 @@ pub fn make_twilio_window(
 
 	fn top_box_updater_fn(params: WindowUpdaterParams) -> MaybeError {
 		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
-		let twilio_state = inner_shared_state.twilio_state.continually_updated.get_data();
+		let twilio_state = inner_shared_state.maybe_twilio_state.as_ref().context("Expected Twilio to be enabled")?.continually_updated.get_data();
 		let text_color = *params.window.get_state::<ColorSDL>();
 
 		let WindowContents::Many(many) = params.window.get_contents_mut()
@@ -664,9 +740,12 @@ pub fn make_twilio_window(
 
 				TextDisplayInfo {
 					text: DisplayText::new(&formatted_number).with_padding(" ", ""),
-					color: text_color,
+					color: text_color.into(),
+					maybe_outline_color: None,
 					pixel_area: params.area_drawn_to_screen,
-					scroll_fn: |_, _| (0.0, true)
+					scroll_fn: |_, _, _| (0.0, true),
+					scroll_params: TextScrollParams::DEFAULT,
+					margins: TextMargins::NONE
 				}
 			));
 
@@ -689,7 +768,7 @@ pub fn make_twilio_window(
 	);
 
 	// This just contains the history windows
-	let history_window_container = Window::new(
+	let mut history_window_container = Window::new(
 		None,
 		DynamicOptional::NONE,
 		WindowContents::Nothing,
@@ -699,6 +778,8 @@ pub fn make_twilio_window(
 		Some(all_subwindows)
 	);
 
+	history_window_container.set_border_radius(maybe_history_border_radius);
+
 	Window::new(
 		None,
 		DynamicOptional::NONE,
@@ -709,3 +790,159 @@ pub fn make_twilio_window(
 		Some(vec![history_window_container, top_box])
 	)
 }
+
+//////////
+
+struct TwilioFocusWindowState {
+	text_color: ColorSDL,
+	focus_duration: Duration,
+	maybe_shown_text: Option<String>
+}
+
+/* This splits text into lines of at most `max_line_len` chars, breaking at spaces where possible (and breaking up words
+that are too long for a line). If there would be more than `max_num_lines` lines, the rest goes on the last line. */
+fn wrap_text(text: &str, max_line_len: usize, max_num_lines: usize) -> Vec<String> {
+	let (max_line_len, max_num_lines) = (max_line_len.max(1), max_num_lines.max(1));
+	let (mut lines, mut curr_line) = (Vec::new(), String::new());
+
+	for word in text.split_whitespace() {
+		let len_with_word = curr_line.chars().count() + usize::from(!curr_line.is_empty()) + word.chars().count();
+
+		if len_with_word > max_line_len && !curr_line.is_empty() {
+			lines.push(std::mem::take(&mut curr_line));
+		}
+
+		if !curr_line.is_empty() {curr_line.push(' ');}
+		curr_line.push_str(word);
+
+		while curr_line.chars().count() > max_line_len {
+			let split_index = curr_line.char_indices().nth(max_line_len).map_or(curr_line.len(), |(index, _)| index);
+			let rest = curr_line.split_off(split_index);
+			lines.push(std::mem::replace(&mut curr_line, rest));
+		}
+	}
+
+	if !curr_line.is_empty() {
+		lines.push(curr_line);
+	}
+
+	if lines.len() > max_num_lines {
+		let overflow = lines.split_off(max_num_lines - 1).join(" ");
+		lines.push(overflow);
+	}
+
+	lines
+}
+
+/* This shows a clicked message in full, over several unscrolled lines, until it times out, the message leaves
+the history, or the focus window (or the same message) is clicked. It uses the message info that the Twilio state
+already has, so nothing is requested again. When nothing is focused, the window and its lines are not drawn. */
+pub fn make_twilio_focus_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
+	background_contents: WindowContents, text_color: ColorSDL, num_lines: usize, focus_duration: Duration) -> Window {
+
+	/* The text wrapping is based on the width of a half-width Unifont glyph (which is half of its height).
+	A line that still does not fit (e.g. because of wide characters) scrolls, rather than being cut off. */
+	const CHAR_WIDTH_TO_HEIGHT: f32 = 0.5;
+
+	fn focus_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get_mut::<SharedWindowState>();
+		let twilio_state = inner_shared_state.maybe_twilio_state.as_mut().context("Expected Twilio to be enabled")?;
+		let individual_state = params.window.get_state_mut::<TwilioFocusWindowState>();
+		let messages = &twilio_state.continually_updated.get_data().curr_messages.map;
+
+		if let Some((message_id, focus_time)) = &twilio_state.maybe_focused_message {
+			if focus_time.elapsed() >= individual_state.focus_duration || !messages.contains_key(message_id) {
+				twilio_state.maybe_focused_message = None;
+			}
+		}
+
+		// The display text is used, since it has the message's age and sender too
+		let maybe_text = twilio_state.maybe_focused_message.as_ref().map(|(message_id, _)| messages[message_id].display_text.clone());
+
+		if maybe_text == individual_state.maybe_shown_text {
+			return Ok(());
+		}
+
+		individual_state.maybe_shown_text.clone_from(&maybe_text);
+		let text_color = individual_state.text_color;
+		let window = params.window;
+
+		let Some(text) = maybe_text else {
+			window.set_draw_skipping(true);
+			window.get_children_mut().into_iter().flatten().for_each(|line_child| line_child.set_draw_skipping(true));
+			return Ok(());
+		};
+
+		window.set_draw_skipping(false);
+
+		let line_children = window.get_children_mut().context("The Twilio focus window was expected to have children")?;
+		let line_pixel_area = (params.area_drawn_to_screen.0, params.area_drawn_to_screen.1 / line_children.len() as u32);
+		let max_line_len = (line_pixel_area.0 as f32 / (line_pixel_area.1 as f32 * CHAR_WIDTH_TO_HEIGHT).max(1.0)) as usize;
+		let lines = wrap_text(&text, max_line_len, line_children.len());
+
+		for (index, line_child) in line_children.iter_mut().enumerate() {
+			let Some(line) = lines.get(index) else {
+				line_child.set_draw_skipping(true);
+				continue;
+			};
+
+			let texture_creation_info = TextureCreationInfo::Text((
+				Cow::Borrowed(inner_shared_state.font_info),
+
+				TextDisplayInfo {
+					text: DisplayText::new(line).with_padding(" ", " "),
+					color: text_color.into(),
+					maybe_outline_color: None,
+					pixel_area: line_pixel_area,
+
+					scroll_fn: |seed, text_fits_in_box, pause_fraction| {
+						if text_fits_in_box {return (0.0, false);}
+						(scroll_with_pause(seed, 4.0, pause_fraction), true)
+					},
+
+					scroll_params: TextScrollParams::DEFAULT,
+					margins: TextMargins::NONE
+				}
+			));
+
+			line_child.set_draw_skipping(false);
+
+			line_child.get_contents_mut().update_as_texture(true, params.texture_pool,
+				&texture_creation_info, inner_shared_state.fallback_texture_creation_info)?;
+		}
+
+		Ok(())
+	}
+
+	fn focus_click_handler(params: WindowClickParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get_mut::<SharedWindowState>();
+		inner_shared_state.maybe_twilio_state.as_mut().context("Expected Twilio to be enabled")?.maybe_focused_message = None;
+		Ok(())
+	}
+
+	let line_height = 1.0 / num_lines.max(1) as f32;
+
+	let line_children = (0..num_lines.max(1)).map(|index| {
+		let mut line_child = Window::new(
+			None, DynamicOptional::NONE, WindowContents::Nothing, None,
+			Vec2f::new(0.0, line_height * index as f32), Vec2f::new(1.0, line_height), None
+		);
+
+		line_child.set_draw_skipping(true);
+		line_child
+	}).collect();
+
+	let mut window = Window::new(
+		Some((focus_updater_fn, update_rate)),
+		DynamicOptional::new(TwilioFocusWindowState {text_color, focus_duration, maybe_shown_text: None}),
+		background_contents,
+		Some(text_color),
+		top_left,
+		size,
+		Some(line_children)
+	);
+
+	window.set_click_handler(Some(focus_click_handler));
+	window.set_draw_skipping(true);
+	window
+}
@@ -5,6 +5,8 @@ use crate::{
 		FontInfo,
 		DisplayText,
 		TextDisplayInfo,
+		TextScrollParams,
+		TextMargins,
 		TextureCreationInfo,
 		TextTextureScrollFn
 	},
@@ -43,6 +45,7 @@ pub struct UpdatableTextWindowFields<IndividualState> {
 	pub inner: IndividualState,
 	pub text_color: ColorSDL,
 	pub scroll_fn: TextTextureScrollFn,
+	pub scroll_params: TextScrollParams,
 	pub update_rate: UpdateRate,
 	pub maybe_border_color: Option<ColorSDL>
 }
@@ -70,9 +73,12 @@ pub fn make_window<IndividualState: UpdatableTextWindowMethods + Clone + 'static
 
 			TextDisplayInfo {
 				text: DisplayText::new(&extracted_text).with_padding("", right_padding),
-				color: wrapped_individual_state.text_color,
+				color: wrapped_individual_state.text_color.into(),
+				maybe_outline_color: None,
 				pixel_area: params.area_drawn_to_screen,
-				scroll_fn: wrapped_individual_state.scroll_fn
+				scroll_fn: wrapped_individual_state.scroll_fn,
+				scroll_params: wrapped_individual_state.scroll_params,
+				margins: TextMargins::NONE
 			}
 		));
 
@@ -1,21 +1,23 @@
 /* TODO:
-- Actually implement this
 - Make the general structure of the text updater fns less repetitive
-- Consider using an alternative API
+- Show more than the temperature (e.g. the wind, if it's windy)
 */
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, sync::Arc, time::{Duration, Instant}};
 
 use crate::{
-	// request,
+	request,
+	test_data,
 
-	texture::{DisplayText, TextDisplayInfo, TextureCreationInfo},
+	texture::{DisplayText, TextDisplayInfo, TextScrollParams, TextMargins, TextureCreationInfo, scroll_with_pause},
 
 	utility_types::{
 		vec2f::Vec2f,
+		easing_fns::EasingFn,
 		generic_result::*,
 		dynamic_optional::DynamicOptional,
-		update_rate::{UpdateRateCreator, Seconds}
+		thread_task::{ContinuallyUpdated, Updatable},
+		update_rate::{UpdateRate, Seconds}
 	},
 
 	window_tree::{
@@ -25,168 +27,649 @@ use crate::{
 		WindowUpdaterParams
 	},
 
-	dashboard_defs::shared_window_state::SharedWindowState
+	dashboard_defs::{
+		updatable_text_pattern,
+		shared_window_state::SharedWindowState
+	}
 };
 
-// TODO: fill this with stuff
-struct WeatherWindowState {
-	api_key: String,
-	location: String
-}
-
-pub fn weather_updater_fn(params: WindowUpdaterParams) -> MaybeError {
-	let weather_changed = true;
-	let weather_string = "Rain (32f). So cold.";
-	let weather_text_color = ColorSDL::BLACK;
-
-	/*
-	- 1000 API calls free every day
-	- That's 1000 per 24 hrs
-	- Our 41.666 per hour, or around once per 1.444 minutes
-	- To make stuff easy, do once every 2 minutes
-	- TODO: do it once every 10 minutes (that's how frequently the data updates: https://openweathermap.org/appid)
-	*/
-
-	// let individual_window_state = window.get_state::<WeatherWindowState>();
-	let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
-
-	/*
-	// TODO: perhaps don't build request urls, just build request objects directly
-	let url = request::build_url("https://api.openweathermap.org/data/2.5/weather",
-		&[],
-
-		&[
-			("q", Cow::Borrowed(&individual_window_state.location)),
-			("appid", Cow::Borrowed(&individual_window_state.api_key)),
-			("units", Cow::Borrowed("metric"))
-		]
-	);
+////////// Weather icons
 
-	//////////
+// An icon is either a local image, or some text (like an emoji)
+#[derive(serde::Deserialize)]
+pub enum WeatherIcon {
+	Path(String),
+	Text(String)
+}
 
-	// TODO: why are all the damn fields optional?
+// This is tomorrow.io's code for unknown weather, and its icon is also used for unrecognized codes
+const UNKNOWN_WEATHER_CODE: u32 = 0;
+
+/* Weather codes can be given different icons without recompiling, through this file (it is optional).
+It is a JSON object that maps tomorrow.io weather codes to icons (e.g. `{"1000": {"Path": "assets/sunny.png"},
+"4001": {"Text": "🌧️"}}`), and its entries are applied over the default ones below. */
+const WEATHER_ICONS_OVERRIDE_PATH: &str = "assets/weather_icons.json";
+
+lazy_static::lazy_static!(
+	// These are all of tomorrow.io's weather codes (see https://docs.tomorrow.io/reference/data-layers-weather-codes)
+	static ref WEATHER_ICON_MAPPING: HashMap<u32, WeatherIcon> = load_weather_icon_mapping(HashMap::from([
+		(UNKNOWN_WEATHER_CODE, "🌡️"),
+		(1000, "☀️"), // Clear, sunny
+		(1100, "🌤️"), // Mostly clear
+		(1101, "⛅"), // Partly cloudy
+		(1102, "🌥️"), // Mostly cloudy
+		(1001, "☁️"), // Cloudy
+		(2000, "🌫️"), // Fog
+		(2100, "🌫️"), // Light fog
+		(4000, "🌦️"), // Drizzle
+		(4001, "🌧️"), // Rain
+		(4200, "🌦️"), // Light rain
+		(4201, "🌧️"), // Heavy rain
+		(5000, "🌨️"), // Snow
+		(5001, "🌨️"), // Flurries
+		(5100, "🌨️"), // Light snow
+		(5101, "❄️"), // Heavy snow
+		(6000, "🧊"), // Freezing drizzle
+		(6001, "🧊"), // Freezing rain
+		(6200, "🧊"), // Light freezing rain
+		(6201, "🧊"), // Heavy freezing rain
+		(7000, "🧊"), // Ice pellets
+		(7101, "🧊"), // Heavy ice pellets
+		(7102, "🧊"), // Light ice pellets
+		(8000, "⛈️") // Thunderstorm
+	]));
+);
+
+fn load_weather_icon_mapping(defaults: HashMap<u32, &str>) -> HashMap<u32, WeatherIcon> {
+	let mut mapping: HashMap<u32, WeatherIcon> = defaults.into_iter()
+		.map(|(weather_code, emoji)| (weather_code, WeatherIcon::Text(emoji.to_string()))).collect();
+
+	if std::path::Path::new(WEATHER_ICONS_OVERRIDE_PATH).exists() {
+		match crate::utility_types::json_utils::load_from_file::<HashMap<u32, WeatherIcon>>(WEATHER_ICONS_OVERRIDE_PATH) {
+			Ok(overrides) => mapping.extend(overrides),
+
+			Err(err) => log::warn!("Could not load the weather icons from \
+				'{WEATHER_ICONS_OVERRIDE_PATH}', so only the default ones will be used: '{err}'.")
+		}
+	}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct WeatherDesc1 {
-		feels_like: Option<f32>,
-		temp: Option<f32>,
-		pressure: Option<i32>,
-		humidity: Option<i32>,
-		temp_min: Option<f32>,
-		temp_max: Option<f32>
+	mapping
+}
+
+fn get_weather_icon(weather_code: u32) -> &'static WeatherIcon {
+	WEATHER_ICON_MAPPING.get(&weather_code).unwrap_or_else(|| {
+		log::warn!("Unrecognized weather code {weather_code}, so the icon for unknown weather will be used.");
+		&WEATHER_ICON_MAPPING[&UNKNOWN_WEATHER_CODE] // The overrides can replace this one, but not remove it
+	})
+}
+
+// This is for checking that all of the icon images exist at startup
+pub fn get_weather_icon_paths() -> Vec<String> {
+	WEATHER_ICON_MAPPING.values().filter_map(|icon| match icon {
+		WeatherIcon::Path(path) => Some(path.clone()),
+		WeatherIcon::Text(_) => None
+	}).collect()
+}
+
+////////// Weather units
+
+/* tomorrow.io returns its data in whichever of these is requested, so nothing has to be converted here.
+Imperial is the default, since that is what the dashboard has always shown. */
+#[derive(serde::Deserialize, Copy, Clone, Default)]
+pub enum WeatherUnits {
+	Metric,
+
+	#[default]
+	Imperial
+}
+
+impl WeatherUnits {
+	fn get_api_name(self) -> &'static str {
+		match self {
+			Self::Metric => "metric",
+			Self::Imperial => "imperial"
+		}
 	}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct WeatherDesc2 {
-		description: Option<String>,
-		icon: Option<String>,
-		id: Option<i32>,
-		main: Option<String>
-		// visibility: Option<i32>
+	fn get_temperature_suffix(self) -> &'static str {
+		match self {
+			Self::Metric => "°C",
+			Self::Imperial => "°F"
+		}
 	}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct WindDesc {
-		deg: Option<i32>,
-		gust: Option<f32>,
-		speed: Option<f32>
+	fn get_wind_speed_suffix(self) -> &'static str {
+		match self {
+			Self::Metric => "m/s",
+			Self::Imperial => "mph"
+		}
 	}
+}
+
+////////// Weather data (from tomorrow.io)
+
+struct ImmutableWeatherData {
+	api_key: String,
+	location: String, // This is `latitude,longitude`
+	units: WeatherUnits
+}
+
+// The temperatures and wind speed are in the units that the weather state was made with
+#[derive(Copy, Clone)]
+pub struct CurrentConditions {
+	pub weather_code: u32,
+	pub temperature: f64,
+	pub maybe_feels_like: Option<f64>,
+	pub maybe_humidity: Option<f64>, // This is a percentage
+	pub maybe_wind_speed: Option<f64>,
+	pub maybe_wind_direction: Option<f64> // This is in degrees, clockwise from north
+}
+
+#[derive(Clone)]
+struct WeatherData {
+	immutable: Arc<ImmutableWeatherData>,
+
+	maybe_last_conditions_fetch_time: Option<Instant>,
+	maybe_current_conditions: Option<CurrentConditions>,
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct CloudsDesc {
-		all: Option<i32>
+	maybe_last_alerts_fetch_time: Option<Instant>,
+	active_alerts: Vec<String> // These are the alert headlines
+}
+
+impl WeatherData {
+	/* The free tomorrow.io plan allows for 25 requests per hour (and 500 per day), and the shared window
+	state is updated much more often than that, so the fetches are spaced out. Together, these make 432 requests per day. */
+	const CONDITIONS_FETCH_INTERVAL: Duration = Duration::from_secs(60 * 10); // This is about how often the realtime data changes
+	const ALERTS_FETCH_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
+	// These are the tomorrow.io event categories that count as severe weather
+	const INSIGHTS: &'static str = "fires,wind,winter,thunderstorms,floods,temperature,tropical,marine,fog,tornado";
+
+	fn fetch_is_due(maybe_last_fetch_time: Option<Instant>, fetch_interval: Duration) -> bool {
+		match maybe_last_fetch_time {
+			Some(last_fetch_time) => last_fetch_time.elapsed() >= fetch_interval,
+			None => true
+		}
 	}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct RainDesc {
-		// all: i32
+	// In test data mode, this reads the response from the given test data subdirectory instead
+	fn get_json(url: &str, test_data_subdir: &str) -> GenericResult<serde_json::Value> {
+		if test_data::is_enabled() {
+			test_data::get_next_response(test_data_subdir)
+		}
+		else {
+			let json = request::as_type(request::get(url))?;
+			test_data::maybe_dump_response(test_data_subdir, &json);
+			Ok(json)
+		}
+	}
 
-		#[serde(rename = "1h")]
-		one_hour: Option<f32>
+	fn update_current_conditions(&mut self) -> MaybeError {
+		if !Self::fetch_is_due(self.maybe_last_conditions_fetch_time, Self::CONDITIONS_FETCH_INTERVAL) {
+			return Ok(());
+		}
+
+		let url = request::build_url("https://api.tomorrow.io/v4/weather/realtime",
+			&[],
+
+			&[
+				("location", Cow::Borrowed(&self.immutable.location)),
+				("units", Cow::Borrowed(self.immutable.units.get_api_name())),
+				("apikey", Cow::Borrowed(&self.immutable.api_key))
+			]
+		);
+
+		#[derive(serde::Deserialize)]
+		#[serde(rename_all = "camelCase")]
+		struct Values {
+			weather_code: u32,
+			temperature: f64,
+
+			// These are only used by the extra fields, so they are allowed to be missing
+			temperature_apparent: Option<f64>,
+			humidity: Option<f64>,
+			wind_speed: Option<f64>,
+			wind_direction: Option<f64>
+		}
+
+		#[derive(serde::Deserialize)]
+		struct RealtimeData {
+			values: Values
+		}
+
+		#[derive(serde::Deserialize)]
+		struct RealtimeResponse {
+			data: RealtimeData
+		}
+
+		let response: RealtimeResponse = serde_json::from_value(Self::get_json(&url, "weather/realtime")?)?;
+		let values = response.data.values;
+
+		self.maybe_current_conditions = Some(CurrentConditions {
+			weather_code: values.weather_code,
+			temperature: values.temperature,
+			maybe_feels_like: values.temperature_apparent,
+			maybe_humidity: values.humidity,
+			maybe_wind_speed: values.wind_speed,
+			maybe_wind_direction: values.wind_direction
+		});
+		self.maybe_last_conditions_fetch_time = Some(Instant::now());
+		Ok(())
 	}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct SnowDesc {
-		all: Option<i32>
+	fn update_alerts(&mut self) -> MaybeError {
+		if !Self::fetch_is_due(self.maybe_last_alerts_fetch_time, Self::ALERTS_FETCH_INTERVAL) {
+			return Ok(());
+		}
+
+		let url = request::build_url("https://api.tomorrow.io/v4/events",
+			&[],
+
+			&[
+				("location", Cow::Borrowed(&self.immutable.location)),
+				("insights", Cow::Borrowed(Self::INSIGHTS)),
+				("apikey", Cow::Borrowed(&self.immutable.api_key))
+			]
+		);
+
+		#[derive(serde::Deserialize)]
+		#[serde(rename_all = "camelCase")]
+		struct EventValues {
+			title: Option<String>,
+			headline: Option<String>
+		}
+
+		#[derive(serde::Deserialize)]
+		#[serde(rename_all = "camelCase")]
+		struct Event {
+			start_time: Option<String>,
+			end_time: Option<String>,
+			event_values: EventValues
+		}
+
+		#[derive(serde::Deserialize)]
+		struct EventList {
+			events: Vec<Event>
+		}
+
+		#[derive(serde::Deserialize)]
+		struct EventResponse {
+			data: EventList
+		}
+
+		let response: EventResponse = serde_json::from_value(Self::get_json(&url, "weather/events")?)?;
+
+		//////////
+
+		let now = chrono::Utc::now();
+
+		// A time that is missing or can't be parsed is treated as unbounded
+		let time_is_before_now = |maybe_time: &Option<String>, default: bool| {
+			maybe_time.as_deref().and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+				.map_or(default, |time| time <= now)
+		};
+
+		self.active_alerts = response.data.events.into_iter().filter(|event|
+			time_is_before_now(&event.start_time, true) && !time_is_before_now(&event.end_time, false)
+		).filter_map(|event|
+			event.event_values.headline.or(event.event_values.title)
+		).collect();
+
+		self.maybe_last_alerts_fetch_time = Some(Instant::now());
+		Ok(())
 	}
+}
 
-	#[derive(serde::Deserialize, Debug)] // TODO: remove `Debug`
-	struct WeatherInfo {
-		main: WeatherDesc1,
-		weather: [WeatherDesc2; 1],
+impl Updatable for WeatherData {
+	type Param = ();
 
-		wind: Option<WindDesc>,
-		clouds: Option<CloudsDesc>,
-		rain: Option<RainDesc>,
-		snow: Option<SnowDesc>
+	// The alerts are fetched even if the current conditions could not be
+	fn update(&mut self, _: &Self::Param) -> MaybeError {
+		let conditions_result = self.update_current_conditions();
+		self.update_alerts()?;
+		conditions_result
 	}
+}
 
-	//////////
+//////////
+
+pub struct WeatherState {
+	// This is `None` if there is no tomorrow.io API key
+	maybe_continually_updated: Option<ContinuallyUpdated<WeatherData>>
+}
+
+impl WeatherState {
+	// Weather data is only fetched if an API key for tomorrow.io is given
+	pub fn new(maybe_api_key: Option<&str>, latitude: f64, longitude: f64, units: WeatherUnits) -> Self {
+		let maybe_continually_updated = maybe_api_key.map(|api_key| {
+			let data = WeatherData {
+				immutable: Arc::new(ImmutableWeatherData {
+					api_key: api_key.to_string(),
+					location: format!("{latitude},{longitude}"),
+					units
+				}),
 
-	let json = request::as_type(request::get(&url))?;
-	let w: WeatherInfo = serde_json::from_value(json)?;
+				maybe_last_conditions_fetch_time: None,
+				maybe_current_conditions: None,
 
-	fn maybe_add<T: std::fmt::Display>(string: &mut String, field: Option<T>, formatter: fn(T) -> String) {
-		if let Some(inner) = field {
-			*string += &(formatter(inner) + ". ");
+				maybe_last_alerts_fetch_time: None,
+				active_alerts: Vec::new()
+			};
+
+			ContinuallyUpdated::new(&data, &(), "Weather")
+		});
+
+		Self {maybe_continually_updated}
+	}
+
+	// This returns false if something failed with the continual updater.
+	pub fn update(&mut self) -> GenericResult<bool> {
+		match &mut self.maybe_continually_updated {
+			Some(continually_updated) => continually_updated.update(&()),
+			None => Ok(true)
 		}
 	}
 
-	let mut weather_string = String::new();
-	maybe_add(&mut weather_string, w.main.feels_like, |t| format!("It feels like {t}"));
-	println!("ws = {:?}", weather_string);
-	*/
+	// This is `None` until the first fetch succeeds
+	pub fn current_conditions(&self) -> Option<CurrentConditions> {
+		self.maybe_continually_updated.as_ref().and_then(
+			|continually_updated| continually_updated.get_data().maybe_current_conditions
+		)
+	}
 
-	/*
-	Deciding what data to show (I don't want to go overboard):
-	1. (MOST IMPORTANT) An emoji for the given icon (I have this data)
-	2. (SECOND-MOST IMPORTANT) What temperature it feels like
-	3. (MID-LATER) If there's high pressure or humidity, say "It's a scorcher!" Or "It's a hot one today!".
-	4. (LATER) If it's windy, show the wind gust and speed (same for rain, snow, etc.)
-	*/
+	pub fn active_alerts(&self) -> &[String] {
+		self.maybe_continually_updated.as_ref().map_or(
+			&[], |continually_updated| &continually_updated.get_data().active_alerts
+		)
+	}
+}
 
-	let texture_creation_info = TextureCreationInfo::Text((
-		Cow::Borrowed(inner_shared_state.font_info),
+//////////
 
-		TextDisplayInfo {
-			text: DisplayText::new(weather_string),
-			color: weather_text_color,
-			pixel_area: params.area_drawn_to_screen,
+// These are the optional secondary lines under the temperature (each one is only shown if it is turned on)
+#[derive(serde::Deserialize, Copy, Clone, Default)]
+#[serde(default)]
+pub struct WeatherExtraFields {
+	pub feels_like: bool,
+	pub humidity: bool,
+	pub wind: bool
+}
 
-			scroll_fn: |seed, _| {
-				let repeat_rate_secs = 3.0;
-				let base_scroll = (seed % repeat_rate_secs) / repeat_rate_secs;
-				(1.0 - base_scroll, true)
+#[derive(Copy, Clone)]
+enum WeatherTextPart {
+	Temperature,
+	FeelsLike,
+	Humidity,
+	Wind
+}
+
+impl WeatherTextPart {
+	// This is `None` if the part's value is missing from the response
+	fn get_text(self, conditions: &CurrentConditions, units: WeatherUnits) -> Option<String> {
+		const COMPASS_POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+		let temperature_suffix = units.get_temperature_suffix();
+
+		match self {
+			Self::Temperature => Some(format!("{:.0}{temperature_suffix}", conditions.temperature)),
+			Self::FeelsLike => conditions.maybe_feels_like.map(|feels_like| format!("Feels like {feels_like:.0}{temperature_suffix}")),
+			Self::Humidity => conditions.maybe_humidity.map(|humidity| format!("Humidity: {humidity:.0}%")),
+
+			Self::Wind => conditions.maybe_wind_speed.map(|wind_speed| {
+				let maybe_compass_point = conditions.maybe_wind_direction.map(|degrees|
+					COMPASS_POINTS[(degrees / 45.0).round().rem_euclid(8.0) as usize]
+				);
+
+				format!("Wind: {wind_speed:.0} {}{}", units.get_wind_speed_suffix(),
+					maybe_compass_point.map_or(String::new(), |compass_point| format!(" {compass_point}")))
+			})
+		}
+	}
+}
+
+/* This shows the icon for the current weather, with the temperature to its right (and the turned-on extra fields
+as smaller lines under that). Everything is hidden until the current weather is first fetched, and the extra lines
+are also hidden when they would be too short to read. The units should be the same as the ones that the weather
+state was made with. */
+pub fn make_weather_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
+	text_color: ColorSDL, units: WeatherUnits, extra_fields: WeatherExtraFields) -> Window {
+
+	const MIN_EXTRA_LINE_HEIGHT_PIXELS: u32 = 10;
+
+	struct WeatherPartWindowState {
+		maybe_text_part: Option<WeatherTextPart>, // This is `None` for the icon
+		maybe_last_weather_code: Option<u32>,
+		maybe_last_text: Option<String>,
+		text_color: ColorSDL,
+		units: WeatherUnits
+	}
+
+	fn make_text_texture_creation_info<'a>(text: &'a str, color: ColorSDL,
+		pixel_area: (u32, u32), inner_shared_state: &'a SharedWindowState) -> TextureCreationInfo<'a> {
+
+		TextureCreationInfo::Text((
+			Cow::Borrowed(inner_shared_state.font_info),
+
+			TextDisplayInfo {
+				text: DisplayText::new(text),
+				color: color.into(),
+				maybe_outline_color: None,
+				pixel_area,
+				scroll_fn: |_, _, _| (0.0, false),
+				scroll_params: TextScrollParams::DEFAULT,
+				margins: TextMargins::NONE
 			}
+		))
+	}
+
+	fn icon_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let Some(conditions) = inner_shared_state.maybe_weather_state.as_ref().and_then(WeatherState::current_conditions) else {return Ok(())};
+
+		let state = params.window.get_state_mut::<WeatherPartWindowState>();
+
+		if state.maybe_last_weather_code == Some(conditions.weather_code) {
+			return Ok(());
 		}
-	));
 
-	params.window.get_contents_mut().update_as_texture(
-		weather_changed,
-		params.texture_pool,
-		&texture_creation_info,
-		inner_shared_state.fallback_texture_creation_info
-	)
+		state.maybe_last_weather_code = Some(conditions.weather_code);
+		let text_color = state.text_color;
+
+		let texture_creation_info = match get_weather_icon(conditions.weather_code) {
+			WeatherIcon::Path(path) => TextureCreationInfo::Path(Cow::Borrowed(path)),
+			WeatherIcon::Text(text) => make_text_texture_creation_info(text, text_color, params.area_drawn_to_screen, inner_shared_state)
+		};
+
+		params.window.set_draw_skipping(false);
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	fn text_updater_fn(params: WindowUpdaterParams) -> MaybeError {
+		let inner_shared_state = params.shared_window_state.get::<SharedWindowState>();
+		let Some(conditions) = inner_shared_state.maybe_weather_state.as_ref().and_then(WeatherState::current_conditions) else {return Ok(())};
+
+		let state = params.window.get_state_mut::<WeatherPartWindowState>();
+		let text_part = state.maybe_text_part.unwrap();
+
+		let is_too_short = !matches!(text_part, WeatherTextPart::Temperature)
+			&& params.area_drawn_to_screen.1 < MIN_EXTRA_LINE_HEIGHT_PIXELS;
+
+		let maybe_text = (!is_too_short).then(|| text_part.get_text(&conditions, state.units)).flatten();
+
+		if maybe_text == state.maybe_last_text {
+			return Ok(());
+		}
+
+		state.maybe_last_text = maybe_text.clone();
+		let text_color = state.text_color;
+
+		let Some(text) = maybe_text else {
+			params.window.set_draw_skipping(true);
+			return Ok(());
+		};
+
+		let texture_creation_info = make_text_texture_creation_info(&text, text_color, params.area_drawn_to_screen, inner_shared_state);
+		params.window.set_draw_skipping(false);
+
+		params.window.get_contents_mut().update_as_texture(
+			true,
+			params.texture_pool,
+			&texture_creation_info,
+			inner_shared_state.fallback_texture_creation_info
+		)
+	}
+
+	let make_part_window = |maybe_text_part: Option<WeatherTextPart>, top_left, size| {
+		let updater_fn = if maybe_text_part.is_some() {text_updater_fn} else {icon_updater_fn};
+
+		let mut window = Window::new(
+			Some((updater_fn, update_rate)),
+
+			DynamicOptional::new(WeatherPartWindowState {
+				maybe_text_part,
+				maybe_last_weather_code: None,
+				maybe_last_text: None,
+				text_color,
+				units
+			}),
+
+			WindowContents::Nothing,
+			None,
+			top_left,
+			size,
+			None
+		);
+
+		window.set_draw_skipping(true);
+		window
+	};
+
+	//////////
+
+	let icon_width = 0.3;
+	let text_width = 1.0 - icon_width;
+
+	let extra_text_parts: Vec<WeatherTextPart> = [
+		(extra_fields.feels_like, WeatherTextPart::FeelsLike),
+		(extra_fields.humidity, WeatherTextPart::Humidity),
+		(extra_fields.wind, WeatherTextPart::Wind)
+	].into_iter().filter_map(|(enabled, text_part)| enabled.then_some(text_part)).collect();
+
+	// The temperature gets the top half if there are any extra lines (and they split the bottom half)
+	let temperature_height = if extra_text_parts.is_empty() {1.0} else {0.5};
+	let extra_line_height = (1.0 - temperature_height) / extra_text_parts.len().max(1) as f32;
+
+	let mut children = vec![
+		make_part_window(None, Vec2f::ZERO, Vec2f::new(icon_width, 1.0)),
+		make_part_window(Some(WeatherTextPart::Temperature), Vec2f::new(icon_width, 0.0), Vec2f::new(text_width, temperature_height))
+	];
+
+	children.extend(extra_text_parts.into_iter().enumerate().map(|(i, text_part)| make_part_window(
+		Some(text_part),
+		Vec2f::new(icon_width, temperature_height + extra_line_height * i as f32),
+		Vec2f::new(text_width, extra_line_height)
+	)));
+
+	Window::new(None, DynamicOptional::NONE, WindowContents::Nothing, None, top_left, size, Some(children))
 }
 
-// Note: the state code can be empty here!
-pub fn make_weather_window(
-	top_left: Vec2f, size: Vec2f,
-	update_rate_creator: UpdateRateCreator, api_key: &str,
-	city_name: &str, state_code: &str, country_code: &str) -> Window {
+//////////
+
+// This banner is only shown while there is an active weather alert
+pub fn make_weather_alert_window(top_left: Vec2f, size: Vec2f, update_rate: UpdateRate,
+	background_contents: WindowContents, text_color: ColorSDL, scroll_params: TextScrollParams,
+	fade_easing_fn: EasingFn) -> Window {
+
+	#[derive(Clone)]
+	struct WeatherAlertWindowState {
+		maybe_alert_text: Option<String>, // This is the previous alert text
+		fade_easing_fn: EasingFn
+	}
+
+	const ALERT_SEPARATOR: &str = " | ";
+	const FADE_DURATION_SECS: Seconds = 0.5;
+
+	impl updatable_text_pattern::UpdatableTextWindowMethods for WeatherAlertWindowState {
+		fn should_skip_update(updater_params: &mut WindowUpdaterParams) -> bool {
+			let inner_shared_state = updater_params.shared_window_state.get::<SharedWindowState>();
+			let Some(weather_state) = &inner_shared_state.maybe_weather_state else {return true};
+			let active_alerts = weather_state.active_alerts();
+
+			let curr_alert_text = (!active_alerts.is_empty()).then(|| active_alerts.join(ALERT_SEPARATOR));
+
+			let wrapped_individual_state = updater_params.window.get_state_mut
+				::<updatable_text_pattern::UpdatableTextWindowFields<WeatherAlertWindowState>>();
+
+			let state = &mut wrapped_individual_state.inner;
+
+			if curr_alert_text == state.maybe_alert_text {
+				return true;
+			}
+
+			let (skip_update, alert_appeared) = (curr_alert_text.is_none(), state.maybe_alert_text.is_none());
+			state.maybe_alert_text = curr_alert_text;
+			let fade_easing_fn = state.fade_easing_fn;
 
-	const UPDATE_RATE_SECS: Seconds = 60.0 * 10.0; // Once every 10 minutes (this is how frequent the weather data is)
+			let window = &mut updater_params.window;
+			let curr_opacity = window.get_opacity();
 
-	let weather_update_rate = update_rate_creator.new_instance(UPDATE_RATE_SECS);
-	let location = [city_name, state_code, country_code].join(",");
+			// Like with the error window, the banner fades in when an alert appears, and out when all alerts are gone
+			if skip_update {
+				window.animate_opacity(curr_opacity, 0.0, FADE_DURATION_SECS, fade_easing_fn);
+			}
+			else if alert_appeared {
+				let start_opacity = if window.drawing_is_skipped() {0.0} else {curr_opacity};
+				window.set_draw_skipping(false);
+				window.animate_opacity(start_opacity, 1.0, FADE_DURATION_SECS, fade_easing_fn);
+			}
+
+			skip_update
+		}
+
+		fn compute_within_updater<'a>(inner_shared_state: &'a SharedWindowState) -> updatable_text_pattern::ComputedInTextUpdater<'a> {
+			(Cow::Borrowed(inner_shared_state.font_info), ALERT_SEPARATOR)
+		}
+
+		fn extract_text(&self) -> Cow<str> {
+			Cow::Borrowed(self.maybe_alert_text.as_ref().unwrap())
+		}
+
+		fn extract_texture_contents(window_contents: &mut WindowContents) -> &mut WindowContents {
+			let WindowContents::Many(all_contents) = window_contents
+			else {panic!("The weather alert window contents was expected to be a list!")};
+			&mut all_contents[1]
+		}
+	}
+
+	let fields = updatable_text_pattern::UpdatableTextWindowFields {
+		inner: WeatherAlertWindowState {maybe_alert_text: None, fade_easing_fn},
+		text_color,
+
+		// Alert text that fits is left still, and alert text that is too long scrolls
+		scroll_fn: |seed, text_fits, pause_fraction| {
+			if text_fits {
+				return (0.0, false);
+			}
+
+			(scroll_with_pause(seed, 12.0, pause_fraction), true)
+		},
+
+		scroll_params,
+		update_rate,
+		maybe_border_color: Some(text_color)
+	};
+
+	let mut window = updatable_text_pattern::make_window(
+		fields, top_left, size,
+		WindowContents::Many(vec![background_contents, WindowContents::Nothing])
+	);
 
-	Window::new(
-		Some((weather_updater_fn, weather_update_rate)),
-		DynamicOptional::new(WeatherWindowState {api_key: api_key.to_string(), location}),
-		WindowContents::Color(ColorSDL::RGB(255, 0, 255)),
-		Some(ColorSDL::RED),
-		top_left,
-		size,
-		None
-	)
+	window.set_draw_skipping(true);
+	window
 }
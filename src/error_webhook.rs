@@ -0,0 +1,118 @@
+use std::{
+	thread,
+	sync::mpsc,
+	collections::HashMap,
+	time::{Duration, Instant}
+};
+
+use crate::{
+	request,
+	utility_types::{json_utils, error_state::ErrorTimestamp}
+};
+
+//////////
+
+/* This optionally sends dashboard errors to a Discord webhook, so that studio staff get pinged about
+them when nobody is watching the screen. To keep a flapping error from spamming the channel, an error
+is only sent once it has kept happening for a while, and a source is not sent again until a cooldown passes
+(even if its error disappears and comes back in the meantime). The webhook requests are sent from
+a separate thread, so that a slow webhook never holds up drawing. */
+
+#[derive(serde::Deserialize)]
+pub struct ErrorWebhookConfig {
+	// This can be given as `"${SOME_ENV_VAR}"`, like the API keys (since the URL contains a secret token)
+	#[serde(deserialize_with = "json_utils::deserialize_with_env_var")]
+	discord_webhook_url: String,
+
+	maybe_min_error_duration_secs: Option<u64>, // This is `DEFAULT_MIN_ERROR_DURATION` if not given
+	maybe_source_cooldown_secs: Option<u64> // This is `DEFAULT_SOURCE_COOLDOWN` if not given
+}
+
+struct ErrorNotification {
+	source: String,
+	message: String,
+	first_seen: ErrorTimestamp
+}
+
+pub struct ErrorWebhookNotifier {
+	min_error_duration: Duration,
+	source_cooldown: Duration,
+	last_send_times: HashMap<String, Instant>,
+	notification_sender: mpsc::Sender<ErrorNotification>
+}
+
+impl ErrorWebhookNotifier {
+	const DEFAULT_MIN_ERROR_DURATION: Duration = Duration::from_secs(120);
+	const DEFAULT_SOURCE_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+	// This is Discord's limit for the description of an embed
+	const MAX_MESSAGE_CHARS: usize = 4096;
+
+	pub fn new(config: &ErrorWebhookConfig) -> Self {
+		let (notification_sender, notification_receiver) = mpsc::channel::<ErrorNotification>();
+		let url = config.discord_webhook_url.clone();
+
+		// This ends once the notifier (and so the sender) is dropped
+		thread::spawn(move || {
+			for notification in notification_receiver {
+				if let Err(err) = request::post_json(&url, &Self::make_payload(&notification)) {
+					log::warn!("Could not send the '{}' error to the Discord webhook: '{err}'.", notification.source);
+				}
+			}
+		});
+
+		log::info!("Dashboard errors will be sent to a Discord webhook.");
+
+		Self {
+			min_error_duration: config.maybe_min_error_duration_secs.map_or(Self::DEFAULT_MIN_ERROR_DURATION, Duration::from_secs),
+			source_cooldown: config.maybe_source_cooldown_secs.map_or(Self::DEFAULT_SOURCE_COOLDOWN, Duration::from_secs),
+			last_send_times: HashMap::new(),
+			notification_sender
+		}
+	}
+
+	fn make_payload(notification: &ErrorNotification) -> serde_json::Value {
+		let message: String = notification.message.chars().take(Self::MAX_MESSAGE_CHARS).collect();
+
+		serde_json::json!({
+			"content": format!("The dashboard has an error from **{}**.", notification.source),
+
+			"embeds": [{
+				"title": notification.source,
+				"description": message,
+				"timestamp": notification.first_seen.to_rfc3339(),
+				"footer": {"text": "First seen"}
+			}]
+		})
+	}
+
+	/* This is called whenever an error is reported. It returns if the error was sent (and so should
+	not be passed in again until its message changes, or until it expires and comes back). */
+	pub fn send_if_due(&mut self, source: &str, message: &str,
+		first_seen: ErrorTimestamp, last_seen: ErrorTimestamp) -> bool {
+
+		let error_duration = (last_seen - first_seen).to_std().unwrap_or(Duration::ZERO);
+
+		if error_duration < self.min_error_duration {
+			return false;
+		}
+
+		let now = Instant::now();
+
+		if let Some(last_send_time) = self.last_send_times.get(source) {
+			if now.duration_since(*last_send_time) < self.source_cooldown {
+				return false;
+			}
+		}
+
+		let notification = ErrorNotification {source: source.to_owned(), message: message.to_owned(), first_seen};
+
+		if let Err(err) = self.notification_sender.send(notification) {
+			log::warn!("Could not pass the '{source}' error to the Discord webhook thread: '{err}'.");
+			return false;
+		}
+
+		self.last_send_times.insert(source.to_owned(), now);
+		true
+	}
+}
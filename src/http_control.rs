@@ -0,0 +1,139 @@
+use std::{
+	thread,
+	sync::mpsc,
+	time::Duration,
+	io::{Write, BufRead, BufReader},
+	net::{TcpListener, TcpStream}
+};
+
+use crate::utility_types::generic_result::*;
+
+//////////
+
+/* This is an optional HTTP server for triggering dashboard actions from other machines
+(an alternative to the IPC sockets, which need local access). It supports these POST routes:
+- `/refresh/spinitron` and `/refresh/twilio`, which make that data source fetch again right away (and show the new data once it arrives)
+- `/surprise/<name>`, which triggers the surprise with that name
+- `/screenshot`, which saves what is on the screen to a PNG
+- `/dump/window_tree`, which saves a description of the window tree to a JSON file (for debugging layouts)
+- `/dump/spinitron_json`, which saves the last raw Spinitron responses to a file (if they are being kept) */
+
+#[derive(serde::Deserialize)]
+pub struct HttpControlConfig {
+	port: u16,
+	maybe_bind_address: Option<String> // This is localhost if not given
+}
+
+#[derive(Debug)]
+pub enum HttpControlCommand {
+	RefreshSpinitron,
+	RefreshTwilio,
+	Screenshot,
+	DumpWindowTree,
+	DumpSpinitronJson
+}
+
+pub type SurpriseTriggerer = fn(&str) -> MaybeError;
+
+pub struct HttpControlServer {
+	command_receiver: mpsc::Receiver<HttpControlCommand>
+}
+
+impl HttpControlServer {
+	const DEFAULT_BIND_ADDRESS: &'static str = "127.0.0.1";
+
+	// This keeps a stalled client from blocking the other requests
+	const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+	pub fn new(config: &HttpControlConfig, surprise_triggerer: SurpriseTriggerer) -> GenericResult<Self> {
+		let bind_address = config.maybe_bind_address.as_deref().unwrap_or(Self::DEFAULT_BIND_ADDRESS);
+
+		let listener = TcpListener::bind((bind_address, config.port)).context(
+			format!("Could not start the HTTP control server at {bind_address}:{}", config.port)
+		)?;
+
+		log::info!("Listening for HTTP control requests at {bind_address}:{}.", config.port);
+
+		let (command_sender, command_receiver) = mpsc::channel();
+
+		thread::spawn(move || {
+			for maybe_stream in listener.incoming() {
+				let result = maybe_stream.to_generic().and_then(
+					|stream| Self::handle_connection(stream, &command_sender, surprise_triggerer)
+				);
+
+				if let Err(err) = result {
+					log::warn!("Problem with handling an HTTP control request: '{err}'.");
+				}
+			}
+		});
+
+		Ok(Self {command_receiver})
+	}
+
+	fn handle_connection(mut stream: TcpStream,
+		command_sender: &mpsc::Sender<HttpControlCommand>,
+		surprise_triggerer: SurpriseTriggerer) -> MaybeError {
+
+		stream.set_read_timeout(Some(Self::READ_TIMEOUT))?;
+
+		// Only the request line is needed (e.g. `POST /refresh/spinitron HTTP/1.1`)
+		let mut request_line = String::new();
+		BufReader::new(&stream).read_line(&mut request_line)?;
+
+		let mut request_parts = request_line.split_whitespace();
+		let (method, path) = (request_parts.next().unwrap_or(""), request_parts.next().unwrap_or(""));
+
+		let send_command = |command| {
+			match command_sender.send(command) {
+				Ok(()) => "200 OK",
+
+				Err(err) => {
+					log::warn!("Could not pass an HTTP control command to the main thread: '{err}'.");
+					"503 Service Unavailable"
+				}
+			}
+		};
+
+		let status = if method != "POST" {
+			"405 Method Not Allowed"
+		}
+		else if path == "/refresh/spinitron" {
+			send_command(HttpControlCommand::RefreshSpinitron)
+		}
+		else if path == "/refresh/twilio" {
+			send_command(HttpControlCommand::RefreshTwilio)
+		}
+		else if path == "/screenshot" {
+			send_command(HttpControlCommand::Screenshot)
+		}
+		else if path == "/dump/window_tree" {
+			send_command(HttpControlCommand::DumpWindowTree)
+		}
+		else if path == "/dump/spinitron_json" {
+			send_command(HttpControlCommand::DumpSpinitronJson)
+		}
+		else if let Some(surprise_name) = path.strip_prefix("/surprise/") {
+			match surprise_triggerer(surprise_name) {
+				Ok(()) => "200 OK",
+
+				Err(err) => {
+					log::warn!("Could not trigger the surprise '{surprise_name}' over HTTP: '{err}'.");
+					"500 Internal Server Error"
+				}
+			}
+		}
+		else {
+			"404 Not Found"
+		};
+
+		log::info!("Got an HTTP control request of '{method} {path}', and responded with '{status}'.");
+
+		write!(stream, "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").to_generic()
+	}
+
+	// This returns the commands that arrived since the last call to this
+	pub fn poll_commands(&self) -> impl Iterator<Item = HttpControlCommand> + '_ {
+		self.command_receiver.try_iter()
+	}
+}
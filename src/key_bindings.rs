@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use sdl2::keyboard::Keycode;
+
+//////////
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyAction {
+	Quit,
+	ReloadConfig,
+	NextTheme,
+	ToggleFrameStats,
+	Screenshot,
+	DumpWindowTree
+}
+
+impl KeyAction {
+	const ALL: [Self; 6] = [Self::Quit, Self::ReloadConfig, Self::NextTheme, Self::ToggleFrameStats, Self::Screenshot, Self::DumpWindowTree];
+
+	fn get_name(self) -> &'static str {
+		match self {
+			Self::Quit => "quit",
+			Self::ReloadConfig => "reload_config",
+			Self::NextTheme => "next_theme",
+			Self::ToggleFrameStats => "toggle_frame_stats",
+			Self::Screenshot => "screenshot",
+			Self::DumpWindowTree => "dump_window_tree"
+		}
+	}
+
+	// This is used when the config does not have a binding for an action
+	fn get_default_key(self) -> Keycode {
+		match self {
+			Self::Quit => Keycode::Escape,
+			Self::ReloadConfig => Keycode::R,
+			Self::NextTheme => Keycode::T,
+			Self::ToggleFrameStats => Keycode::F,
+			Self::Screenshot => Keycode::P,
+			Self::DumpWindowTree => Keycode::D
+		}
+	}
+}
+
+//////////
+
+pub struct KeyBindings {
+	actions: HashMap<Keycode, KeyAction>
+}
+
+impl KeyBindings {
+	/* The config maps action names to SDL keycode names (e.g. "quit" to "Escape").
+	Unknown action or keycode names are logged and skipped, rather than failing.
+	If several actions are bound to the same key, the one that comes first in `KeyAction::ALL` is used. */
+	pub fn new(config: &HashMap<String, String>) -> Self {
+		let mut keys_for_actions = KeyAction::ALL.map(|action| (action, action.get_default_key()));
+
+		for (action_name, keycode_name) in config {
+			let Some((_, key)) = keys_for_actions.iter_mut().find(|(action, _)| action.get_name() == action_name) else {
+				log::warn!("Unknown key binding action '{action_name}'! Skipping it.");
+				continue;
+			};
+
+			match Keycode::from_name(keycode_name) {
+				Some(keycode) => *key = keycode,
+				None => log::warn!("Unknown keycode '{keycode_name}' for the '{action_name}' action! Using the default key instead.")
+			}
+		}
+
+		Self {actions: Self::make_actions_for_keys(keys_for_actions)}
+	}
+
+	// If several actions are bound to the same key, the first one of them is used
+	fn make_actions_for_keys(keys_for_actions: impl IntoIterator<Item = (KeyAction, Keycode)>) -> HashMap<Keycode, KeyAction> {
+		let mut actions: HashMap<Keycode, KeyAction> = HashMap::new();
+
+		for (action, key) in keys_for_actions {
+			if let Some(&other_action) = actions.get(&key) {
+				log::warn!("Both the '{}' and '{}' actions are bound to {key}! Only the '{}' action will be used.",
+					other_action.get_name(), action.get_name(), other_action.get_name());
+
+				continue;
+			}
+
+			actions.insert(key, action);
+		}
+
+		actions
+	}
+
+	pub fn get_action_for(&self, keycode: Keycode) -> Option<KeyAction> {
+		self.actions.get(&keycode).copied()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn shared_keys_go_to_the_first_action() {
+		let with_rebinding = |rebound_action: KeyAction, key: Keycode| KeyBindings::make_actions_for_keys(KeyAction::ALL.map(
+			|action| (action, if action == rebound_action {key} else {action.get_default_key()})
+		));
+
+		// Quitting comes before reloading the config, so binding quitting to R always makes R quit
+		for _ in 0..20 {
+			let actions = with_rebinding(KeyAction::Quit, Keycode::R);
+			assert_eq!(actions.get(&Keycode::R), Some(&KeyAction::Quit));
+			assert_eq!(actions.get(&Keycode::Escape), None);
+		}
+
+		// This is the same when the later action is the one that is rebound
+		let actions = with_rebinding(KeyAction::Screenshot, Keycode::T);
+		assert_eq!(actions.get(&Keycode::T), Some(&KeyAction::NextTheme));
+		assert_eq!(actions.get(&Keycode::P), None);
+
+		// Without any shared keys, every action keeps its own key
+		let actions = with_rebinding(KeyAction::Quit, Keycode::Q);
+		assert_eq!(actions.len(), KeyAction::ALL.len());
+		assert!(KeyAction::ALL.iter().all(|action| action == &KeyAction::Quit || actions.get(&action.get_default_key()) == Some(action)));
+	}
+}
@@ -1,5 +1,17 @@
+mod themes;
 mod request;
 mod texture;
+mod key_bindings;
+mod http_control;
+mod error_webhook;
+mod metrics;
+mod watchdog;
+mod screen_dim;
+mod pixel_shift;
+mod palette;
+mod screenshot;
+mod test_data;
+mod window_geometry;
 mod spinitron;
 mod window_tree;
 mod utility_types;
@@ -18,6 +30,10 @@ enum ScreenOption {
 	down the app. It is slower than real fullscreen. */
 	FullscreenDesktop,
 
+	/* This is like `FullscreenDesktop`, but on the display with the given index
+	(which is useful for multi-monitor setups). An invalid index falls back to display 0. */
+	FullscreenDesktopOnDisplay(i32),
+
 	/* This makes the OS change its output rendering resolution to one of
 	the officially supported ones (which you can find in your settings app).
 	You cannot exit from this window while the app is still running. */
@@ -31,9 +47,163 @@ struct AppConfig {
 	maybe_pause_subduration_ms_when_window_unfocused: Option<u32>,
 
 	screen_option: ScreenOption,
+
+	// For the windowed screen option, this reopens the window where it was (and at the size it was) when the app last quit
+	#[serde(default)]
+	remember_window_geometry: bool,
+
 	hide_cursor: bool,
+
+	/* This makes clicks (or taps) go to the windows under them that have click handlers,
+	for interactive kiosks. The cursor is always shown with this on, even if `hide_cursor` is set. */
+	#[serde(default)]
+	enable_click_handling: bool,
 	use_linear_filtering: bool,
-	background_color: (u8, u8, u8)
+
+	/* If this is given, everything is drawn at this fixed (width, height), and then scaled up to the
+	screen by the largest whole number that fits, with letterboxing around it. This is for pixel art,
+	so linear filtering is turned off with it. Like vsync, this only takes effect at startup. */
+	maybe_pixel_perfect_logical_size: Option<(u32, u32)>,
+
+	/* If vsync is off, frames are paced manually to the display's refresh rate instead.
+	Either way, while the window is unfocused (and a pause subduration is given), the pausing
+	takes over the pacing, and no frames are drawn or presented until it is focused again. */
+	use_vsync: bool,
+
+	background_color: (u8, u8, u8),
+
+	/* If this is given, this texture is drawn over the background color before the window tree, stretched
+	to fill the whole screen (including any letterboxing from pixel-perfect rendering) */
+	#[serde(default)]
+	maybe_background_texture_path: Option<String>,
+
+	// This maps action names to keycode names. Actions without a binding here use their default keys.
+	#[serde(default)]
+	key_bindings: std::collections::HashMap<String, String>,
+
+	// The HTTP control server is off if this is not given
+	maybe_http_control: Option<http_control::HttpControlConfig>,
+
+	// The metrics endpoint is off if this is not given
+	#[serde(default)]
+	maybe_metrics: Option<metrics::MetricsConfig>,
+
+	/* The watchdog is off if this is not given (which is best for development, since pausing in a debugger
+	would trigger it). This only takes effect at startup. */
+	#[serde(default)]
+	maybe_watchdog: Option<watchdog::WatchdogConfig>,
+
+	// The screen is only dimmed when idle if this is given. This only takes effect at startup.
+	#[serde(default)]
+	maybe_screen_dim: Option<screen_dim::ScreenDimConfig>,
+
+	// The whole screen is shifted by a few pixels now and then if this is given. This only takes effect at startup.
+	#[serde(default)]
+	maybe_pixel_shift: Option<pixel_shift::PixelShiftConfig>,
+
+	/* If this is given, the themes use a darker palette between sunset and sunrise (at this location),
+	and they are remade when the sun rises or sets. This only takes effect at startup. */
+	#[serde(default)]
+	maybe_palette_swap: Option<palette::PaletteSwapConfig>,
+
+	// Errors are only sent to a Discord webhook if this is given. This only takes effect at startup.
+	#[serde(default)]
+	maybe_error_webhook: Option<error_webhook::ErrorWebhookConfig>,
+
+	// This switches themes automatically based on the local time of day
+	#[serde(default)]
+	theme_schedule: Vec<themes::ScheduledThemeConfig>,
+
+	/* If this is given, the Spinitron and Twilio data is read from JSON files in this directory instead
+	of from the network (see `test_data.rs` for the file layout). This only takes effect at startup. */
+	#[serde(default)]
+	maybe_test_data_dir: Option<String>,
+
+	/* If this is given, every Spinitron, Twilio, and weather API response is written to this directory, in the same
+	layout as the test data directory (so a dump can be replayed by giving it as the test data directory).
+	This writes a lot, so it is off by default. This only takes effect at startup. */
+	#[serde(default)]
+	maybe_api_response_dump_dir: Option<String>,
+
+	/* If this is given, at most this many requests (for API data and images) are made at once, across all threads.
+	This keeps a slow connection from being saturated when many textures load at once. It only takes effect at startup. */
+	#[serde(default)]
+	maybe_max_concurrent_requests: Option<usize>,
+
+	/* This is how many Spinitron model textures (like album art) the dashboard requests at once, when several change
+	together. It is 2 if this is not given, and it only takes effect at startup. */
+	#[serde(default)]
+	maybe_max_concurrent_spinitron_texture_requests: Option<usize>,
+
+	/* If this is given, the last raw Spinitron response for each model type is kept (cut off at this many bytes),
+	so that it can be dumped to a file over HTTP control. It is off by default, and it only takes effect at startup. */
+	#[serde(default)]
+	maybe_raw_spinitron_json_max_bytes: Option<usize>,
+
+	// This smooths out the corners of rounded borders (some prefer the crisp look, so it is off by default)
+	#[serde(default)]
+	use_anti_aliased_borders: bool,
+
+	/* This renders emojis as color glyphs (this needs a font override with a color emoji font as the fallback font).
+	It is off by default, since color emojis are not rendered correctly on the Pi. It only takes effect at startup. */
+	#[serde(default)]
+	color_emoji: bool,
+
+	/* If this is above zero, each update rate gets a random phase offset of up to this many seconds, so that
+	API requests made at the same rate are spread out. This does not change the time between updates, and it only takes effect at startup. */
+	#[serde(default)]
+	max_update_rate_jitter_secs: f64,
+
+	// This decides what happens when a window's texture changes again during a transition
+	#[serde(default)]
+	texture_transition_overlap_policy: texture::TextureTransitionOverlapPolicy
+}
+
+/* Mouse positions are in window coordinates. SDL already converts them to logical coordinates when a logical size is set,
+but otherwise, they are scaled to the output size here (since the two differ on high-DPI screens). */
+fn get_click_position_on_canvas(canvas: &window_tree::CanvasSDL, x: i32, y: i32) -> (f32, f32) {
+	let (window_width, window_height) = canvas.window().size();
+
+	match (canvas.logical_size(), canvas.output_size()) {
+		((0, 0), Ok((output_width, output_height))) if window_width != 0 && window_height != 0 => (
+			x as f32 * output_width as f32 / window_width as f32,
+			y as f32 * output_height as f32 / window_height as f32
+		),
+
+		_ => (x as f32, y as f32)
+	}
+}
+
+/* This makes the background texture the first time it is drawn, and after the texture pool is cleared
+(e.g. when switching themes). When a logical size is set, drawing is limited to the logical area, so the logical
+size is turned off while drawing this (so that the letterboxing is covered too), and then turned back on. */
+fn draw_background_texture(path: &str, maybe_background_texture: &mut Option<texture::TextureHandle>,
+	rendering_params: &mut window_tree::PerFrameConstantRenderingParams) -> utility_types::generic_result::MaybeError {
+
+	use utility_types::generic_result::ToGenericError;
+
+	let background_texture = match maybe_background_texture {
+		Some(texture) => texture,
+
+		None => maybe_background_texture.insert(rendering_params.texture_pool.make_texture(
+			&texture::TextureCreationInfo::Path(std::borrow::Cow::Borrowed(path))
+		)?)
+	};
+
+	let canvas = &mut rendering_params.sdl_canvas;
+	let logical_size = canvas.logical_size();
+	let using_logical_size = logical_size != (0, 0);
+
+	if using_logical_size {canvas.set_logical_size(0, 0).to_generic()?;}
+
+	let (width, height) = canvas.output_size().to_generic()?;
+	let result = rendering_params.texture_pool.draw_texture_to_canvas(
+		background_texture, canvas, sdl2::rect::Rect::new(0, 0, width, height), texture::TextureDrawParams::DEFAULT
+	);
+
+	if using_logical_size {canvas.set_logical_size(logical_size.0, logical_size.1).to_generic()?;}
+
+	result
 }
 
 fn get_fps(sdl_timer: &sdl2::TimerSubsystem,
@@ -67,12 +237,49 @@ fn main() -> utility_types::generic_result::MaybeError {
 
 	log::info!("App launched!");
 
-	let app_config: AppConfig = utility_types::json_utils::load_from_file("assets/app_config.json")?;
-	let top_level_window_creator = dashboard_defs::dashboard::make_dashboard;
+	const APP_CONFIG_PATH: &str = "assets/app_config.json";
+
+	let mut app_config: AppConfig = utility_types::json_utils::load_from_file(APP_CONFIG_PATH)?;
+
+	if let Some(test_data_dir) = &app_config.maybe_test_data_dir {
+		test_data::enable(test_data_dir)?;
+	}
+
+	if let Some(dump_dir) = &app_config.maybe_api_response_dump_dir {
+		test_data::enable_dumping(dump_dir)?;
+	}
+
+	if let Some(max_concurrent_requests) = app_config.maybe_max_concurrent_requests {
+		request::limit_concurrent_requests(max_concurrent_requests)?;
+	}
+
+	if let Some(max_concurrent_texture_requests) = app_config.maybe_max_concurrent_spinitron_texture_requests {
+		dashboard_defs::dashboard::limit_concurrent_spinitron_texture_requests(max_concurrent_texture_requests)?;
+	}
+
+	if app_config.color_emoji {
+		texture::enable_color_emoji();
+	}
+
+	if let Some(max_bytes_per_model) = app_config.maybe_raw_spinitron_json_max_bytes {
+		spinitron::state::SpinitronState::enable_raw_json_retention(max_bytes_per_model)?;
+	}
+
+	let mut key_bindings = key_bindings::KeyBindings::new(&app_config.key_bindings);
+	let mut theme_schedule = themes::ThemeSchedule::new(&app_config.theme_schedule);
+
+	let mut maybe_palette_swapper = app_config.maybe_palette_swap.as_ref().map(palette::PaletteSwapper::new);
+	let mut curr_palette = maybe_palette_swapper.as_ref().map_or(palette::Palette::Light, palette::PaletteSwapper::get_palette);
+
+	let mut maybe_prev_scheduled_theme_index = theme_schedule.get_scheduled_theme_index();
+	let mut curr_theme_index = maybe_prev_scheduled_theme_index.unwrap_or(themes::DEFAULT_THEME_INDEX);
+
+	// This is done before anything is set up, so that every missing asset is listed at once, right away
+	themes::check_required_assets(curr_theme_index)?;
 
 	//////////
 
-	use crate::utility_types::generic_result::ToGenericError;
+	use crate::utility_types::generic_result::{Context, ToGenericError};
 
 	let sdl_context = sdl2::init().to_generic()?;
 	let sdl_video_subsystem = sdl_context.video().to_generic()?;
@@ -83,18 +290,46 @@ fn main() -> utility_types::generic_result::MaybeError {
 	let build_window = |width: u32, height: u32, applier: fn(&mut WindowBuilder) -> &mut WindowBuilder|
 		applier(&mut sdl_video_subsystem.window(&app_config.title, width, height)).allow_highdpi().build();
 
+	let maybe_restored_window_geometry = match app_config.screen_option {
+		ScreenOption::Windowed(..) if app_config.remember_window_geometry => window_geometry::WindowGeometry::load(&sdl_video_subsystem),
+		_ => None
+	};
+
 	let mut sdl_window = match app_config.screen_option {
-		ScreenOption::Windowed(width, height, borderless, _) => build_window(
-			width, height,
-			if borderless {|wb| wb.position_centered().borderless()}
-			else {WindowBuilder::position_centered}
-		),
+		ScreenOption::Windowed(width, height, borderless, _) => {
+			let (width, height) = maybe_restored_window_geometry.as_ref()
+				.map_or((width, height), |geometry| (geometry.width, geometry.height));
+
+			build_window(
+				width, height,
+				if borderless {|wb| wb.position_centered().borderless()}
+				else {WindowBuilder::position_centered}
+			)
+		},
 
 		// The resolution passed in here is irrelevant
 		ScreenOption::FullscreenDesktop => build_window(
 			0, 0, WindowBuilder::fullscreen_desktop
 		),
 
+		ScreenOption::FullscreenDesktopOnDisplay(display_index) => {
+			let num_displays = sdl_video_subsystem.num_video_displays().to_generic()?;
+
+			let display_index = if (0..num_displays).contains(&display_index) {
+				display_index
+			}
+			else {
+				log::warn!("Display index {display_index} is out of range (there are {num_displays} displays)! Falling back to display 0.");
+				0
+			};
+
+			// The window is put at the display's top-left before going fullscreen, so that it lands on that display
+			let bounds = sdl_video_subsystem.display_bounds(display_index).to_generic()?;
+
+			sdl_video_subsystem.window(&app_config.title, bounds.width(), bounds.height())
+				.position(bounds.x(), bounds.y()).fullscreen_desktop().allow_highdpi().build()
+		},
+
 		ScreenOption::Fullscreen => {
 			let mode = sdl_video_subsystem.display_mode(0, 0).to_generic()?;
 
@@ -105,6 +340,12 @@ fn main() -> utility_types::generic_result::MaybeError {
 		}
 	}?;
 
+	if let Some(geometry) = &maybe_restored_window_geometry {
+		use sdl2::video::WindowPos;
+		sdl_window.set_position(WindowPos::Positioned(geometry.x), WindowPos::Positioned(geometry.y));
+		log::info!("Restored the window geometry (at ({}, {}), with a size of {}x{}).", geometry.x, geometry.y, geometry.width, geometry.height);
+	}
+
 	////////// Setting the window opacity and icon
 
 	// TODO: why does not setting the opacity result in broken fullscreen screen clearing?
@@ -119,25 +360,43 @@ fn main() -> utility_types::generic_result::MaybeError {
 
 	//////////
 
-	let sdl_canvas = sdl_window
-		.into_canvas()
-		.accelerated()
-		.present_vsync()
-		.build()?;
+	// This is kept apart from the app config, since reloading the config cannot change it for an existing canvas
+	let using_vsync = app_config.use_vsync;
+	let mut sdl_canvas_builder = sdl_window.into_canvas().accelerated();
+
+	if using_vsync {
+		sdl_canvas_builder = sdl_canvas_builder.present_vsync();
+	}
+
+	let mut sdl_canvas = sdl_canvas_builder.build()?;
+
+	/* The window tree draws in logical coordinates when a logical size is set
+	(see `Window::get_sdl_window_bounds`), so the windows still map to the whole screen. */
+	if let Some((width, height)) = app_config.maybe_pixel_perfect_logical_size {
+		sdl_canvas.set_logical_size(width, height).to_generic()?;
+		sdl_canvas.set_integer_scale(true).to_generic()?;
+		log::info!("Rendering pixel-perfectly at a logical size of {width}x{height}.");
+	}
 
 	//////////
 
+	let use_linear_filtering = app_config.use_linear_filtering && app_config.maybe_pixel_perfect_logical_size.is_none();
+
+	if app_config.use_linear_filtering && !use_linear_filtering {
+		log::warn!("Linear filtering is turned off, since pixel-perfect rendering is on.");
+	}
+
 	// TODO: why is the top-right texture not linearly filtered?
 	let using_texture_filtering_option =
 		sdl2::hint::set_with_priority(
 			"SDL_RENDER_SCALE_QUALITY",
-			if app_config.use_linear_filtering {"1"} else {"0"},
+			if use_linear_filtering {"1"} else {"0"},
 			&sdl2::hint::Hint::Override
 		);
 
 	assert!(using_texture_filtering_option);
 
-	if app_config.hide_cursor {
+	if app_config.hide_cursor && !app_config.enable_click_handling {
 		sdl_context.mouse().show_cursor(false);
 	}
 
@@ -149,7 +408,8 @@ fn main() -> utility_types::generic_result::MaybeError {
 
 	let texture_creator = sdl_canvas.texture_creator();
 
-	let fps = sdl_video_subsystem.current_display_mode(0).to_generic()?.refresh_rate as u32;
+	let display_index = sdl_canvas.window().display_index().to_generic()?;
+	let fps = sdl_video_subsystem.current_display_mode(display_index).to_generic()?.refresh_rate as u32;
 
 	let sdl_renderer_info = sdl_canvas.info();
 	let max_texture_size = (sdl_renderer_info.max_texture_width, sdl_renderer_info.max_texture_height);
@@ -157,44 +417,187 @@ fn main() -> utility_types::generic_result::MaybeError {
 	let mut rendering_params =
 		window_tree::PerFrameConstantRenderingParams {
 			sdl_canvas,
-			texture_pool: texture::TexturePool::new(&texture_creator, &sdl_ttf_context, max_texture_size),
+			texture_pool: texture::TexturePool::new(&texture_creator, &sdl_ttf_context, max_texture_size)?,
 			frame_counter: utility_types::update_rate::FrameCounter::new(),
 			shared_window_state: utility_types::dynamic_optional::DynamicOptional::NONE,
-			shared_window_state_updater: None
+			shared_window_state_updater: None,
+			use_anti_aliased_borders: app_config.use_anti_aliased_borders,
+			window_is_focused: true,
+			pixel_shift: (0, 0),
+			error_state: utility_types::error_state::ErrorState::new(
+				app_config.maybe_error_webhook.as_ref().map(error_webhook::ErrorWebhookNotifier::new)
+			)
 		};
 
-	let core_init_info = (top_level_window_creator)(
-		&mut rendering_params.texture_pool, utility_types::update_rate::UpdateRateCreator::new(fps)
+	rendering_params.texture_pool.set_transition_overlap_policy(app_config.texture_transition_overlap_policy);
+
+	let update_rate_creator = utility_types::update_rate::UpdateRateCreator::new(fps, app_config.max_update_rate_jitter_secs);
+
+	let core_init_info = themes::get_theme_creator(curr_theme_index)(
+		&mut rendering_params.texture_pool, update_rate_creator, curr_palette, utility_types::dynamic_optional::DynamicOptional::NONE
 	);
 
 	let (mut top_level_window, shared_window_state, shared_window_state_updater) =
-		match core_init_info {
-			Ok(info) => info,
-			Err(err) => panic!("An error arose when initializing the application: '{err}'.")
-		};
+		core_init_info.context("An error arose when initializing the application")?;
 
 	rendering_params.shared_window_state = shared_window_state;
 	rendering_params.shared_window_state_updater = shared_window_state_updater;
 
+	log::info!("Starting with the '{}' theme.", themes::get_theme_name(curr_theme_index));
+
+	let maybe_http_control_server = app_config.maybe_http_control.as_ref().map(|config|
+		http_control::HttpControlServer::new(config, dashboard_defs::dashboard::trigger_surprise)
+	).transpose()?;
+
+	if let Some(config) = &app_config.maybe_metrics {
+		metrics::MetricsServer::start(config)?;
+	}
+
 	//////////
 
 	let mut pausing_window = false;
+	let mut showing_frame_stats = false;
+
+	// A screenshot is taken on the next redrawn frame (and a redraw is forced when one is requested)
+	let mut screenshot_requested = false;
+
+	// A window tree dump is saved right after the next update
+	let mut window_tree_dump_requested = false;
+
+	// This is set by key presses or by the theme schedule, and handled once per frame
+	let mut maybe_requested_theme_index = None;
+
+	// If no theme could be made after a switch, the current one is remade again at this time
+	let mut maybe_theme_remake_retry_time = None;
+
+	// This is made lazily from the app config's background texture path
+	let mut maybe_background_texture = None;
+
+	/* The first frame is always drawn. After that, a frame is only redrawn if something in the window
+	tree changed, or if the screen contents may have been lost (e.g. after a resize or being covered). */
+	let mut force_full_redraw = true;
+	let frame_duration_ms = 1000 / fps.max(1);
+
 	// let mut initial_num_textures_in_pool = None;
 
+	let mut canvas_output_size = rendering_params.sdl_canvas.output_size().to_generic()?;
+
 	log::info!("Finished setting up window. Canvas size: {:?}. Renderer info: {:?}.",
-		rendering_params.sdl_canvas.output_size().to_generic()?, sdl_renderer_info);
+		canvas_output_size, sdl_renderer_info);
+
+	// This is started right before the main loop, so that a slow startup does not trigger it
+	let maybe_watchdog = app_config.maybe_watchdog.as_ref().map(watchdog::Watchdog::new);
+
+	let mut maybe_screen_dimmer = app_config.maybe_screen_dim.as_ref().map(screen_dim::ScreenDimmer::new).transpose()?;
+	let mut maybe_pixel_shifter = app_config.maybe_pixel_shift.as_ref().map(pixel_shift::PixelShifter::new);
 
 	'running: loop {
+		// This is sent before anything else, so that heartbeats keep arriving while the window is paused
+		if let Some(watchdog) = &maybe_watchdog {
+			watchdog.send_heartbeat();
+		}
+
 		for sdl_event in sdl_event_pump.poll_iter() {
-			use sdl2::{event::{self, Event}, keyboard::Keycode};
+			use sdl2::event::{self, Event};
+
+			if let Some(screen_dimmer) = &mut maybe_screen_dimmer {
+				let is_activity = matches!(sdl_event,
+					Event::KeyDown {..} | Event::MouseMotion {..} | Event::MouseButtonDown {..} | Event::MouseWheel {..}
+					| Event::Window {win_event: event::WindowEvent::FocusGained | event::WindowEvent::FocusLost, ..}
+				);
+
+				if is_activity {
+					screen_dimmer.register_activity();
+				}
+			}
 
 			match sdl_event {
-				Event::Quit {..} | Event::KeyDown {keycode: Some(Keycode::Escape), ..} => break 'running,
+				Event::Quit {..} => break 'running,
+
+				Event::KeyDown {keycode: Some(keycode), repeat: false, ..} => {
+					use crate::key_bindings::KeyAction;
+
+					match key_bindings.get_action_for(keycode) {
+						Some(KeyAction::Quit) => break 'running,
+
+						// Only the settings that can change while the app is running are applied here
+						Some(KeyAction::ReloadConfig) => {
+							match utility_types::json_utils::load_from_file::<AppConfig>(APP_CONFIG_PATH) {
+								Ok(new_app_config) => {
+									key_bindings = key_bindings::KeyBindings::new(&new_app_config.key_bindings);
+									theme_schedule = themes::ThemeSchedule::new(&new_app_config.theme_schedule);
+									sdl_context.mouse().show_cursor(!new_app_config.hide_cursor || new_app_config.enable_click_handling);
+
+									if new_app_config.maybe_background_texture_path != app_config.maybe_background_texture_path {
+										maybe_background_texture = None;
+									}
+
+									rendering_params.texture_pool.set_transition_overlap_policy(new_app_config.texture_transition_overlap_policy);
+									rendering_params.use_anti_aliased_borders = new_app_config.use_anti_aliased_borders;
+									app_config = new_app_config;
+									force_full_redraw = true;
+
+									log::info!("Reloaded the app config. Screen, title, icon, filtering, vsync, pixel-perfect rendering, HTTP control, metrics, watchdog, screen dimming, pixel shift, palette swap, and error webhook changes take effect after a restart.");
+								},
+
+								Err(err) => log::error!("Could not reload the app config: '{err}'.")
+							}
+						},
+
+						Some(KeyAction::NextTheme) => {
+							maybe_requested_theme_index = Some((curr_theme_index + 1) % themes::get_num_themes());
+						},
+						Some(KeyAction::ToggleFrameStats) => showing_frame_stats = !showing_frame_stats,
+
+						Some(KeyAction::Screenshot) => {
+							screenshot_requested = true;
+							force_full_redraw = true;
+						},
+
+						Some(KeyAction::DumpWindowTree) => window_tree_dump_requested = true,
+
+						None => {}
+					}
+				},
+
+				Event::MouseButtonDown {mouse_btn: sdl2::mouse::MouseButton::Left, x, y, ..} if app_config.enable_click_handling => {
+					let position = get_click_position_on_canvas(&rendering_params.sdl_canvas, x, y);
+
+					match top_level_window.handle_click(&mut rendering_params, position) {
+						Ok(true) => force_full_redraw = true,
+						Ok(false) => {},
+						Err(err) => rendering_params.error_state.report("Click handling", err.to_string())
+					}
+				},
 
 				Event::Window {win_event, ..} => {
 					match win_event {
-						event::WindowEvent::FocusLost => pausing_window = true,
-						event::WindowEvent::FocusGained => pausing_window = false,
+						event::WindowEvent::FocusLost => {
+							pausing_window = true;
+							rendering_params.window_is_focused = false;
+						},
+
+						event::WindowEvent::FocusGained => {
+							pausing_window = false;
+							rendering_params.window_is_focused = true;
+							force_full_redraw = true;
+						},
+
+						/* The output size can change without the window being resized by the user
+						(e.g. from a monitor resolution change, or the app moving to another monitor) */
+						event::WindowEvent::Resized(..) | event::WindowEvent::SizeChanged(..) => {
+							let new_canvas_output_size = rendering_params.sdl_canvas.output_size().to_generic()?;
+
+							if new_canvas_output_size != canvas_output_size {
+								log::info!("Canvas size changed from {canvas_output_size:?} to {new_canvas_output_size:?}.");
+								canvas_output_size = new_canvas_output_size;
+							}
+
+							force_full_redraw = true;
+						},
+
+						event::WindowEvent::Exposed | event::WindowEvent::Restored => force_full_redraw = true,
+
 						_ => {}
 					}
 				},
@@ -215,42 +618,238 @@ fn main() -> utility_types::generic_result::MaybeError {
 		// TODO: should I put this before event polling?
 		let sdl_performance_counter_before = sdl_timer.performance_counter();
 
-		rendering_params.sdl_canvas.set_draw_color(app_config.background_color);
-		rendering_params.sdl_canvas.clear(); // TODO: make this work on fullscreen too
+		let tree_changed = top_level_window.update(&mut rendering_params).unwrap_or_else(|err| {
+			log::error!("An error arose during updating: '{err}'."); // TODO: put this error in the red dialog on the screen (pass into the renderer)
+			true
+		});
+
+		// This is done after updating, so that the dump matches what is drawn on this frame
+		if window_tree_dump_requested {
+			window_tree_dump_requested = false;
+
+			match top_level_window.save_debug_dump(&rendering_params) {
+				Ok(path) => log::info!("Saved a window tree dump to '{path}'."),
+				Err(err) => log::error!("Could not save a window tree dump: '{err}'.")
+			}
+		}
+
+		if let Some(screen_dimmer) = &mut maybe_screen_dimmer {
+			let surprise_is_appearing = dashboard_defs::dashboard::is_surprise_appearing(&rendering_params.shared_window_state);
+			force_full_redraw |= screen_dimmer.update(surprise_is_appearing);
+		}
+
+		if let Some(new_pixel_shift) = maybe_pixel_shifter.as_mut().and_then(pixel_shift::PixelShifter::update) {
+			rendering_params.pixel_shift = new_pixel_shift;
+			force_full_redraw = true;
+		}
+
+		let should_redraw = force_full_redraw || tree_changed;
+
+		if should_redraw {
+			rendering_params.sdl_canvas.set_draw_color(app_config.background_color);
+			rendering_params.sdl_canvas.clear(); // TODO: make this work on fullscreen too
+
+			// The canvas is still cleared first, since the background texture may be transparent in places
+			if let Some(background_texture_path) = &app_config.maybe_background_texture_path {
+				if let Err(err) = draw_background_texture(background_texture_path, &mut maybe_background_texture, &mut rendering_params) {
+					log::error!("Could not draw the background texture at '{background_texture_path}', so it will not be drawn anymore: '{err}'.");
+					app_config.maybe_background_texture_path = None;
+				}
+			}
+
+			if let Err(err) = top_level_window.render(&mut rendering_params) {
+				log::error!("An error arose during rendering: '{err}'."); // TODO: put this error in the red dialog on the screen (pass into the renderer)
+			}
+
+			if let Some(screen_dimmer) = &maybe_screen_dimmer {
+				if let Err(err) = screen_dimmer.draw(&mut rendering_params.sdl_canvas) {
+					log::error!("Could not dim the screen: '{err}'.");
+				}
+			}
+
+			// This is done before presenting, since the back buffer's contents are undefined after a present
+			if screenshot_requested {
+				screenshot_requested = false;
+
+				match screenshot::save_screenshot(&rendering_params.sdl_canvas) {
+					Ok(path) => log::info!("Saved a screenshot to '{path}'."),
+					Err(err) => log::error!("Could not save a screenshot: '{err}'.")
+				}
+			}
+
+			force_full_redraw = false;
+		}
+
+		if let Some(http_control_server) = &maybe_http_control_server {
+			for command in http_control_server.poll_commands() {
+				log::info!("Handling HTTP control command: {command:?}.");
+
+				let mut request_refresh = |source| {
+					if let Err(err) = dashboard_defs::dashboard::request_refresh(&mut rendering_params.shared_window_state, source) {
+						log::error!("Could not refresh a data source: '{err}'.");
+					}
+				};
+
+				match command {
+					http_control::HttpControlCommand::RefreshSpinitron => request_refresh(dashboard_defs::dashboard::RefreshableSource::Spinitron),
+					http_control::HttpControlCommand::RefreshTwilio => request_refresh(dashboard_defs::dashboard::RefreshableSource::Twilio),
 
-		if let Err(err) = top_level_window.render(&mut rendering_params) {
-			log::error!("An error arose during rendering: '{err}'."); // TODO: put this error in the red dialog on the screen (pass into the renderer)
+					http_control::HttpControlCommand::Screenshot => {
+						screenshot_requested = true;
+						force_full_redraw = true;
+					},
+
+					http_control::HttpControlCommand::DumpWindowTree => window_tree_dump_requested = true,
+
+					http_control::HttpControlCommand::DumpSpinitronJson => {
+						match dashboard_defs::dashboard::save_spinitron_json_dump(&rendering_params.shared_window_state) {
+							Ok(path) => log::info!("Saved a Spinitron JSON dump to '{path}'."),
+							Err(err) => log::error!("Could not save a Spinitron JSON dump: '{err}'.")
+						}
+					}
+				}
+			}
 		}
 
-		if let Some((shared_window_state_updater, shared_update_rate)) = shared_window_state_updater {
-			if shared_update_rate.is_time_to_update(rendering_params.frame_counter) {
-				if let Err(err) = shared_window_state_updater(&mut rendering_params.shared_window_state, &mut rendering_params.texture_pool) {
-					log::error!("An error arose from the shared window state updater: '{err}'."); // TODO: put this error in the red dialog on the screen
+		/* While a refresh is pending, the shared window state updates on every frame (rather than waiting for its update rate),
+		so that the refreshing data source starts its new fetch right away, and its data is shown as soon as it arrives */
+		let forcing_shared_update = dashboard_defs::dashboard::is_refresh_pending(&rendering_params.shared_window_state);
+		let mut ran_shared_update = false;
+
+		if let Some((shared_window_state_updater, shared_update_rate)) = rendering_params.shared_window_state_updater {
+			if forcing_shared_update || shared_update_rate.is_time_to_update(rendering_params.frame_counter) {
+				ran_shared_update = true;
+
+				if let Err(err) = shared_window_state_updater(
+					&mut rendering_params.shared_window_state,
+					&mut rendering_params.texture_pool,
+					&mut rendering_params.error_state) {
+
+					rendering_params.error_state.report("Shared window state updater", err.to_string());
 				}
 			}
 		}
 
+		////////// Switching themes
+
+		/* Crossing a boundary in the theme schedule is not acted upon right after the shared window state updated,
+		since rebuilding then would throw away the results of that (expensive) update. It is handled on the next frame instead. */
+		let scheduled_theme_index = theme_schedule.get_scheduled_theme_index();
+
+		if scheduled_theme_index != maybe_prev_scheduled_theme_index && !ran_shared_update {
+			maybe_prev_scheduled_theme_index = scheduled_theme_index;
+
+			if let Some(index) = scheduled_theme_index {
+				if index != curr_theme_index {
+					maybe_requested_theme_index = Some(index);
+				}
+			}
+		}
+
+		/* Like with the theme schedule, the palette changing remakes the current theme on the next frame.
+		The current theme's data states are kept when it is remade, so that no blocking requests are made again. */
+		if !ran_shared_update {
+			if let Some(new_palette) = maybe_palette_swapper.as_mut().and_then(palette::PaletteSwapper::update) {
+				curr_palette = new_palette;
+				maybe_requested_theme_index = maybe_requested_theme_index.or(Some(curr_theme_index));
+			}
+		}
+
+		if maybe_theme_remake_retry_time.is_some_and(|retry_time| std::time::Instant::now() >= retry_time) {
+			maybe_theme_remake_retry_time = None;
+			maybe_requested_theme_index = maybe_requested_theme_index.or(Some(curr_theme_index));
+		}
+
+		if let Some(index) = maybe_requested_theme_index.take() {
+			// Remaking the current theme (like for a palette change) keeps its data states
+			if let Err(err) = themes::switch_to_theme(index, index == curr_theme_index,
+				&mut top_level_window, &mut rendering_params, update_rate_creator, curr_palette) {
+
+				rendering_params.error_state.report("Theme switching",
+					format!("Could not switch to the '{}' theme: {err}", themes::get_theme_name(index)));
+
+				/* If the previous theme cannot be remade either, there is nothing left to show. That can come from a
+				network error (like for Spinitron), so it is tried again after a while, rather than ending the program. */
+				if let Err(err) = themes::switch_to_theme(curr_theme_index, false,
+					&mut top_level_window, &mut rendering_params, update_rate_creator, curr_palette) {
+
+					const THEME_REMAKE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+
+					rendering_params.error_state.report("Theme switching",
+						format!("Could not remake the '{}' theme either (trying again in {} seconds): {err}",
+						themes::get_theme_name(curr_theme_index), THEME_REMAKE_RETRY_DELAY.as_secs()));
+
+					maybe_theme_remake_retry_time = Some(std::time::Instant::now() + THEME_REMAKE_RETRY_DELAY);
+				}
+			}
+			else {
+				curr_theme_index = index;
+			}
+
+			// Switching themes clears the texture pool, so the background texture is remade on the next redraw
+			maybe_background_texture = None;
+			force_full_redraw = true;
+		}
+
 		//////////
 
 		rendering_params.frame_counter.tick();
 
-		let _fps_without_vsync = get_fps(&sdl_timer,
+		let fps_without_vsync = get_fps(&sdl_timer,
 			sdl_performance_counter_before,
 			sdl_performance_frequency
 		);
 
-		rendering_params.sdl_canvas.present();
+		/* When nothing is redrawn, the last presented frame stays on the screen. Presenting is skipped then,
+		since the back buffer's contents are undefined after a present (so the frame is delayed manually instead). */
+		if should_redraw {
+			rendering_params.sdl_canvas.present();
+
+			// Without vsync, presenting does not wait for the display, so the rest of the frame is waited out here
+			if !using_vsync {
+				let frame_time_ms = (sdl_timer.performance_counter() - sdl_performance_counter_before) * 1000 / sdl_performance_frequency;
+				sdl_timer.delay(frame_duration_ms.saturating_sub(frame_time_ms as u32));
+			}
+		}
+		else {
+			sdl_timer.delay(frame_duration_ms);
+		}
 
-		let _fps_with_vsync = get_fps(&sdl_timer,
+		let fps_with_vsync = get_fps(&sdl_timer,
 			sdl_performance_counter_before,
 			sdl_performance_frequency
 		);
 
-		// println!("fps without and with vsync = {:.3}, {:.3}", _fps_without_vsync, _fps_with_vsync);
+		metrics::set_fps(fps_with_vsync);
+		metrics::set_texture_pool_size(rendering_params.texture_pool.size());
+
+		if showing_frame_stats {
+			println!("fps without and with vsync = {:.3}, {:.3}", fps_without_vsync, fps_with_vsync);
+		}
 
 		// TODO: add this back later
 		// check_for_texture_pool_memory_leak(&mut initial_num_textures_in_pool, &rendering_params.texture_pool);
 	}
 
+	////////// Shutting down
+
+	log::info!("Shutting down...");
+
+	if matches!(app_config.screen_option, ScreenOption::Windowed(..)) && app_config.remember_window_geometry {
+		if let Err(err) = window_geometry::WindowGeometry::save(rendering_params.sdl_canvas.window()) {
+			log::warn!("{err}.");
+		}
+	}
+
+	/* Dropping the window tree closes its IPC socket listeners, and dropping the shared window state tells its
+	continual updater threads to stop (they are then waited on for a bounded time, in case one is in the middle of a request) */
+	const THREAD_SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+	drop(top_level_window);
+	rendering_params.shared_window_state = utility_types::dynamic_optional::DynamicOptional::NONE;
+	utility_types::thread_task::join_stopping_threads(THREAD_SHUTDOWN_TIMEOUT);
+
+	log::info!("Finished shutting down.");
+
 	Ok(())
 }
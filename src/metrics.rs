@@ -0,0 +1,196 @@
+use std::{
+	thread,
+	fmt::Write as _,
+	io::{Write, BufRead, BufReader},
+	net::{TcpListener, TcpStream},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+	sync::{Arc, Mutex, PoisonError, atomic::{AtomicU64, Ordering}}
+};
+
+use crate::utility_types::generic_result::*;
+
+//////////
+
+/* This is an optional HTTP endpoint that serves metrics in the Prometheus text format, at `GET /metrics`
+(for monitoring several dashboards at once). The metrics are always collected, but only with atomic stores
+and adds, so that collecting them does not affect the render timing. They are only formatted when scraped.
+
+The metrics are:
+- `dashboard_fps`: the frames per second of the last frame
+- `dashboard_texture_pool_size`: the number of textures in the texture pool
+- `dashboard_continual_updates_total`: the number of finished continual updates, per source and result
+- `dashboard_seconds_since_last_successful_update`: per source
+- `dashboard_update_duration_seconds`: how long each continual update took, per source (as a sum and count).
+Since the continual updates mostly wait on API requests, this is the API request latency for each source. */
+
+#[derive(serde::Deserialize)]
+pub struct MetricsConfig {
+	port: u16,
+	maybe_bind_address: Option<String> // This is localhost if not given
+}
+
+//////////
+
+pub struct SourceMetrics {
+	name: &'static str,
+	num_successes: AtomicU64,
+	num_failures: AtomicU64,
+	last_success_unix_ms: AtomicU64,
+	update_duration_sum_us: AtomicU64
+}
+
+impl SourceMetrics {
+	// This is called from the continual updater threads
+	pub fn record_update(&self, succeeded: bool, update_duration: Duration) {
+		self.update_duration_sum_us.fetch_add(update_duration.as_micros() as u64, Ordering::Relaxed);
+
+		if succeeded {
+			self.num_successes.fetch_add(1, Ordering::Relaxed);
+			self.last_success_unix_ms.store(get_unix_ms(), Ordering::Relaxed);
+		}
+		else {
+			self.num_failures.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+}
+
+// The FPS is stored as the bits of an `f64`
+static FPS_BITS: AtomicU64 = AtomicU64::new(0);
+static TEXTURE_POOL_SIZE: AtomicU64 = AtomicU64::new(0);
+
+// This is only locked when a source is registered, and when the metrics are scraped
+static SOURCES: Mutex<Vec<Arc<SourceMetrics>>> = Mutex::new(Vec::new());
+
+fn get_unix_ms() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64)
+}
+
+pub fn set_fps(fps: f64) {
+	FPS_BITS.store(fps.to_bits(), Ordering::Relaxed);
+}
+
+pub fn set_texture_pool_size(size: usize) {
+	TEXTURE_POOL_SIZE.store(size as u64, Ordering::Relaxed);
+}
+
+/* Sources with the same name share their metrics (so that the counts carry on when a theme switch
+remakes a continual updater). Like the continual updaters, a source starts out as just updated. */
+pub fn register_source(name: &'static str) -> Arc<SourceMetrics> {
+	let mut sources = SOURCES.lock().unwrap_or_else(PoisonError::into_inner);
+
+	if let Some(source) = sources.iter().find(|source| source.name == name) {
+		source.last_success_unix_ms.store(get_unix_ms(), Ordering::Relaxed);
+		return source.clone();
+	}
+
+	let source = Arc::new(SourceMetrics {
+		name,
+		num_successes: AtomicU64::new(0),
+		num_failures: AtomicU64::new(0),
+		last_success_unix_ms: AtomicU64::new(get_unix_ms()),
+		update_duration_sum_us: AtomicU64::new(0)
+	});
+
+	sources.push(source.clone());
+	source
+}
+
+fn format_metrics() -> String {
+	fn add_header(text: &mut String, name: &str, kind: &str, help: &str) {
+		let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}");
+	}
+
+	let mut text = String::new();
+
+	add_header(&mut text, "dashboard_fps", "gauge", "The frames per second of the last frame.");
+	let _ = writeln!(text, "dashboard_fps {}", f64::from_bits(FPS_BITS.load(Ordering::Relaxed)));
+
+	add_header(&mut text, "dashboard_texture_pool_size", "gauge", "The number of textures in the texture pool.");
+	let _ = writeln!(text, "dashboard_texture_pool_size {}", TEXTURE_POOL_SIZE.load(Ordering::Relaxed));
+
+	let sources = SOURCES.lock().unwrap_or_else(PoisonError::into_inner);
+	let now_unix_ms = get_unix_ms();
+
+	let get_label = |source: &SourceMetrics| format!("source=\"{}\"", source.name.replace('\\', "\\\\").replace('"', "\\\""));
+
+	add_header(&mut text, "dashboard_continual_updates_total", "counter", "The number of finished continual updates.");
+
+	for source in sources.iter() {
+		let label = get_label(source);
+		let _ = writeln!(text, "dashboard_continual_updates_total{{{label},result=\"success\"}} {}", source.num_successes.load(Ordering::Relaxed));
+		let _ = writeln!(text, "dashboard_continual_updates_total{{{label},result=\"failure\"}} {}", source.num_failures.load(Ordering::Relaxed));
+	}
+
+	add_header(&mut text, "dashboard_seconds_since_last_successful_update", "gauge", "The time since the last successful continual update.");
+
+	for source in sources.iter() {
+		let secs_since_success = now_unix_ms.saturating_sub(source.last_success_unix_ms.load(Ordering::Relaxed)) as f64 / 1000.0;
+		let _ = writeln!(text, "dashboard_seconds_since_last_successful_update{{{}}} {secs_since_success}", get_label(source));
+	}
+
+	add_header(&mut text, "dashboard_update_duration_seconds", "summary", "How long the continual updates took (mostly waiting on API requests).");
+
+	for source in sources.iter() {
+		let label = get_label(source);
+		let num_updates = source.num_successes.load(Ordering::Relaxed) + source.num_failures.load(Ordering::Relaxed);
+		let duration_sum_secs = source.update_duration_sum_us.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+
+		let _ = writeln!(text, "dashboard_update_duration_seconds_sum{{{label}}} {duration_sum_secs}");
+		let _ = writeln!(text, "dashboard_update_duration_seconds_count{{{label}}} {num_updates}");
+	}
+
+	text
+}
+
+//////////
+
+pub struct MetricsServer;
+
+impl MetricsServer {
+	const DEFAULT_BIND_ADDRESS: &'static str = "127.0.0.1";
+
+	// This keeps a stalled scraper from blocking the other requests
+	const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+	pub fn start(config: &MetricsConfig) -> MaybeError {
+		let bind_address = config.maybe_bind_address.as_deref().unwrap_or(Self::DEFAULT_BIND_ADDRESS);
+
+		let listener = TcpListener::bind((bind_address, config.port)).context(
+			format!("Could not start the metrics server at {bind_address}:{}", config.port)
+		)?;
+
+		log::info!("Serving metrics at {bind_address}:{}/metrics.", config.port);
+
+		thread::spawn(move || {
+			for maybe_stream in listener.incoming() {
+				if let Err(err) = maybe_stream.to_generic().and_then(Self::handle_connection) {
+					log::warn!("Problem with handling a metrics request: '{err}'.");
+				}
+			}
+		});
+
+		Ok(())
+	}
+
+	fn handle_connection(mut stream: TcpStream) -> MaybeError {
+		stream.set_read_timeout(Some(Self::READ_TIMEOUT))?;
+
+		// Only the request line is needed (e.g. `GET /metrics HTTP/1.1`)
+		let mut request_line = String::new();
+		BufReader::new(&stream).read_line(&mut request_line)?;
+
+		let mut request_parts = request_line.split_whitespace();
+		let (method, path) = (request_parts.next().unwrap_or(""), request_parts.next().unwrap_or(""));
+
+		let (status, body) = match (method, path) {
+			("GET", "/metrics") => ("200 OK", format_metrics()),
+			("GET", _) => ("404 Not Found", String::new()),
+			_ => ("405 Method Not Allowed", String::new())
+		};
+
+		write!(stream,
+			"HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+			body.len()
+		).to_generic()
+	}
+}
@@ -0,0 +1,140 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+//////////
+
+/* This is an optional palette swap, where themes use a darker palette between sunset and sunrise. The sunrise and
+sunset times are calculated from the configured location (with the sunrise equation, which is accurate to a few minutes),
+so no API is needed. Since text colors are baked into textures, the theme is remade when the palette changes (keeping its
+data states, so nothing is fetched again), and that only happens when the sun actually rises or sets. */
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Palette {
+	Light,
+	Dark
+}
+
+#[derive(serde::Deserialize)]
+pub struct PaletteSwapConfig {
+	latitude: f64,
+	longitude: f64 // This is positive for east, and negative for west
+}
+
+//////////
+
+#[derive(Copy, Clone)]
+enum DaylightOnDate {
+	SunRisesAndSets(DateTime<Utc>, DateTime<Utc>),
+	AlwaysDay, // This is the midnight sun
+	AlwaysNight // This is the polar night
+}
+
+// This is from https://en.wikipedia.org/wiki/Sunrise_equation (the angles are in degrees)
+fn get_daylight_on_date(date: NaiveDate, latitude: f64, longitude: f64) -> DaylightOnDate {
+	const JULIAN_DATE_OF_2000: f64 = 2451545.0; // This is noon on January 1st, 2000 (in UTC)
+	const JULIAN_DATE_OF_UNIX_EPOCH: f64 = 2440587.5;
+	const SECS_PER_DAY: f64 = 86400.0;
+
+	let (sin_deg, cos_deg) = (|deg: f64| (deg * PI / 180.0).sin(), |deg: f64| (deg * PI / 180.0).cos());
+
+	let days_since_2000 = (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()).num_days() as f64;
+	let mean_solar_time = days_since_2000 + 0.0009 - longitude / 360.0;
+
+	let mean_anomaly = (357.5291 + 0.98560028 * mean_solar_time).rem_euclid(360.0);
+	let center = 1.9148 * sin_deg(mean_anomaly) + 0.02 * sin_deg(2.0 * mean_anomaly) + 0.0003 * sin_deg(3.0 * mean_anomaly);
+	let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+
+	let solar_transit = JULIAN_DATE_OF_2000 + mean_solar_time
+		+ 0.0053 * sin_deg(mean_anomaly) - 0.0069 * sin_deg(2.0 * ecliptic_longitude);
+
+	let sin_declination = sin_deg(ecliptic_longitude) * sin_deg(23.4397);
+	let cos_declination = (1.0 - sin_declination * sin_declination).sqrt();
+
+	// The -0.833 degrees accounts for atmospheric refraction, and the size of the sun's disc
+	let cos_hour_angle = (sin_deg(-0.833) - sin_deg(latitude) * sin_declination) / (cos_deg(latitude) * cos_declination);
+
+	if cos_hour_angle < -1.0 {
+		return DaylightOnDate::AlwaysDay;
+	}
+	else if cos_hour_angle > 1.0 {
+		return DaylightOnDate::AlwaysNight;
+	}
+
+	let hour_angle_in_days = cos_hour_angle.acos() / (2.0 * PI);
+
+	let to_utc = |julian_date: f64| DateTime::from_timestamp(
+		((julian_date - JULIAN_DATE_OF_UNIX_EPOCH) * SECS_PER_DAY) as i64, 0
+	).unwrap_or_default();
+
+	DaylightOnDate::SunRisesAndSets(to_utc(solar_transit - hour_angle_in_days), to_utc(solar_transit + hour_angle_in_days))
+}
+
+//////////
+
+pub struct PaletteSwapper {
+	latitude: f64,
+	longitude: f64,
+
+	// The daylight is only recalculated when the local date changes
+	daylight_date: NaiveDate,
+	daylight: DaylightOnDate,
+
+	palette: Palette
+}
+
+impl PaletteSwapper {
+	pub fn new(config: &PaletteSwapConfig) -> Self {
+		let daylight_date = Local::now().date_naive();
+
+		let mut swapper = Self {
+			latitude: config.latitude,
+			longitude: config.longitude,
+
+			daylight_date,
+			daylight: get_daylight_on_date(daylight_date, config.latitude, config.longitude),
+
+			palette: Palette::Light
+		};
+
+		swapper.palette = swapper.get_palette_for_now();
+		log::info!("The palette will follow the sunrise and sunset, and it starts out as {:?}.", swapper.palette);
+		swapper
+	}
+
+	pub fn get_palette(&self) -> Palette {
+		self.palette
+	}
+
+	fn get_palette_for_now(&mut self) -> Palette {
+		let date = Local::now().date_naive();
+
+		if date != self.daylight_date {
+			self.daylight_date = date;
+			self.daylight = get_daylight_on_date(date, self.latitude, self.longitude);
+		}
+
+		let now = Utc::now();
+
+		let is_day = match self.daylight {
+			DaylightOnDate::SunRisesAndSets(sunrise, sunset) => now >= sunrise && now < sunset,
+			DaylightOnDate::AlwaysDay => true,
+			DaylightOnDate::AlwaysNight => false
+		};
+
+		if is_day {Palette::Light} else {Palette::Dark}
+	}
+
+	// This returns the new palette if it changed (in which case the theme should be remade with it)
+	pub fn update(&mut self) -> Option<Palette> {
+		let palette = self.get_palette_for_now();
+
+		if palette == self.palette {
+			return None;
+		}
+
+		log::info!("The sun {}, so switching to the {palette:?} palette.", if palette == Palette::Light {"rose"} else {"set"});
+		self.palette = palette;
+		Some(palette)
+	}
+}
@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+//////////
+
+/* This is an optional pixel shift, for reducing burn-in from static elements (like the clock and the logo).
+Every so often, the whole window tree is moved to a new random offset of a few pixels at most, so that no pixel
+stays lit the same way forever. The canvas is cleared before each redraw, so the edges that the shift uncovers
+are just filled with the background color. */
+
+#[derive(serde::Deserialize)]
+pub struct PixelShiftConfig {
+	interval_secs: u64,
+	max_shift_pixels: u8 // This should be kept small (like 1 or 2), so that the shift is not noticeable
+}
+
+pub struct PixelShifter {
+	interval: Duration,
+	max_shift_pixels: i32,
+	last_shift_time: Instant,
+	shift: (i32, i32)
+}
+
+impl PixelShifter {
+	pub fn new(config: &PixelShiftConfig) -> Self {
+		log::info!("The screen will be shifted by up to {} pixels every {} seconds.", config.max_shift_pixels, config.interval_secs);
+
+		Self {
+			interval: Duration::from_secs(config.interval_secs),
+			max_shift_pixels: config.max_shift_pixels.into(),
+			last_shift_time: Instant::now(),
+			shift: (0, 0)
+		}
+	}
+
+	// This returns the new shift if it changed (in which case the screen should be redrawn)
+	pub fn update(&mut self) -> Option<(i32, i32)> {
+		if self.max_shift_pixels == 0 || self.last_shift_time.elapsed() < self.interval {
+			return None;
+		}
+
+		self.last_shift_time = Instant::now();
+
+		let mut rand_generator = rand::thread_rng();
+		let range = -self.max_shift_pixels..=self.max_shift_pixels;
+
+		// A new shift is always picked, so that every interval moves the screen
+		let new_shift = loop {
+			let shift = (rand_generator.gen_range(range.clone()), rand_generator.gen_range(range.clone()));
+			if shift != self.shift {break shift;}
+		};
+
+		self.shift = new_shift;
+		Some(new_shift)
+	}
+}
@@ -1,6 +1,70 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::{Condvar, Mutex, OnceLock, PoisonError}};
 use crate::utility_types::generic_result::*;
 
+////////// Limiting the number of concurrent requests
+
+/* This limits how many requests can be in flight at once (across all threads), so that loading many textures
+at once (like at startup) does not saturate a slow connection. Requests over the limit wait for another one to finish.
+A permit is only held while one request is sent and its response is read (and no request is made while holding one),
+so waiting for a permit can't deadlock. */
+struct RequestLimiter {
+	max_concurrent_requests: usize,
+	num_requests_in_flight: Mutex<usize>,
+	request_finished: Condvar
+}
+
+// This is only set when a limit is given; there is no limit by default
+static REQUEST_LIMITER: OnceLock<RequestLimiter> = OnceLock::new();
+
+pub fn limit_concurrent_requests(max_concurrent_requests: usize) -> MaybeError {
+	let limiter = RequestLimiter::new(max_concurrent_requests)?;
+	REQUEST_LIMITER.set(limiter).ok().context("The concurrent request limit was already set")?;
+	log::info!("At most {max_concurrent_requests} requests will be made at once.");
+	Ok(())
+}
+
+impl RequestLimiter {
+	fn new(max_concurrent_requests: usize) -> GenericResult<Self> {
+		if max_concurrent_requests == 0 {
+			return error_msg!("The maximum number of concurrent requests should be above zero");
+		}
+
+		Ok(Self {max_concurrent_requests, num_requests_in_flight: Mutex::new(0), request_finished: Condvar::new()})
+	}
+
+	// This blocks until a permit is free
+	fn acquire(&self) -> RequestPermit<'_> {
+		let mut num_in_flight = self.num_requests_in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+
+		while *num_in_flight >= self.max_concurrent_requests {
+			num_in_flight = self.request_finished.wait(num_in_flight).unwrap_or_else(PoisonError::into_inner);
+		}
+
+		*num_in_flight += 1;
+		RequestPermit {limiter: self}
+	}
+}
+
+struct RequestPermit<'a> {
+	limiter: &'a RequestLimiter
+}
+
+impl RequestPermit<'_> {
+	// This returns `None` if there is no limit
+	fn acquire() -> Option<RequestPermit<'static>> {
+		REQUEST_LIMITER.get().map(RequestLimiter::acquire)
+	}
+}
+
+impl Drop for RequestPermit<'_> {
+	fn drop(&mut self) {
+		*self.limiter.num_requests_in_flight.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+		self.limiter.request_finished.notify_one();
+	}
+}
+
+//////////
+
 pub fn build_url(base_url: &str, path_params: &[Cow<str>],
 	query_params: &[(&str, Cow<str>)]) -> String {
 
@@ -31,7 +95,9 @@ pub fn get_with_maybe_header(url: &str, maybe_header: Option<(&str, &str)>) -> G
 		request = request.with_header(header.0, header.1);
 	}
 
+	let permit = RequestPermit::acquire();
 	let response = request.with_timeout(DEFAULT_TIMEOUT_SECONDS).send()?;
+	drop(permit);
 
 	if response.status_code == EXPECTED_STATUS_CODE {
 		Ok(response)
@@ -44,6 +110,29 @@ pub fn get_with_maybe_header(url: &str, maybe_header: Option<(&str, &str)>) -> G
 	}
 }
 
+/* This accepts any 2XX status code, since some APIs respond to a POST with `204 No Content`.
+The URL is left out of the error message, since URLs for POSTs (like webhook URLs) often contain secrets. */
+pub fn post_json(url: &str, body: &serde_json::Value) -> GenericResult<minreq::Response> {
+	const DEFAULT_TIMEOUT_SECONDS: u64 = 20;
+
+	let permit = RequestPermit::acquire();
+
+	let response = minreq::post(url).with_header("Content-Type", "application/json")
+		.with_body(body.to_string()).with_timeout(DEFAULT_TIMEOUT_SECONDS).send()?;
+
+	drop(permit);
+
+	if (200..300).contains(&response.status_code) {
+		Ok(response)
+	}
+	else {
+		error_msg!(
+			"Response status code for a POST request was not a success code, \
+			but '{}', with this reason: '{}'", response.status_code, response.reason_phrase
+		)
+	}
+}
+
 pub fn get(url: &str) -> GenericResult<minreq::Response> {
 	get_with_maybe_header(url, None)
 }
@@ -53,3 +142,45 @@ pub fn as_type<T: for<'de> serde::Deserialize<'de>>(response: GenericResult<minr
 	let unpacked_response = response?;
 	serde_json::from_str(unpacked_response.as_str()?).to_generic()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{thread, time::Duration, sync::atomic::{AtomicUsize, Ordering}};
+
+	#[test]
+	fn request_limiter_caps_in_flight_requests() {
+		const MAX_CONCURRENT_REQUESTS: usize = 3;
+		const NUM_THREADS: usize = 12;
+		const NUM_REQUESTS_PER_THREAD: usize = 20;
+
+		assert!(RequestLimiter::new(0).is_err());
+
+		let limiter = RequestLimiter::new(MAX_CONCURRENT_REQUESTS).unwrap();
+		let (num_in_flight, peak_num_in_flight, num_finished) = (AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0));
+
+		thread::scope(|scope| {
+			for _ in 0..NUM_THREADS {
+				scope.spawn(|| {
+					for _ in 0..NUM_REQUESTS_PER_THREAD {
+						let permit = limiter.acquire();
+
+						// This stands in for sending a request, and reading its response
+						let curr_num_in_flight = num_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+						peak_num_in_flight.fetch_max(curr_num_in_flight, Ordering::SeqCst);
+						thread::sleep(Duration::from_micros(200));
+						num_in_flight.fetch_sub(1, Ordering::SeqCst);
+
+						drop(permit);
+						num_finished.fetch_add(1, Ordering::SeqCst);
+					}
+				});
+			}
+		});
+
+		let peak_num_in_flight = peak_num_in_flight.into_inner();
+		assert!(peak_num_in_flight <= MAX_CONCURRENT_REQUESTS, "The peak in-flight count was {peak_num_in_flight}");
+		assert_eq!(num_finished.into_inner(), NUM_THREADS * NUM_REQUESTS_PER_THREAD);
+		assert_eq!(*limiter.num_requests_in_flight.lock().unwrap(), 0);
+	}
+}
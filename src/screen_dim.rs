@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use chrono::NaiveTime;
+
+use crate::{
+	themes::{self, HourAndMinute},
+	utility_types::generic_result::*,
+	window_tree::{CanvasSDL, ColorSDL}
+};
+
+//////////
+
+/* This is an optional screen dimmer, for reducing burn-in on OLED and plasma screens overnight. Within the
+configured hours, once there has been no interaction (key presses, mouse input, or focus changes) for the idle
+period, a dark overlay is drawn over everything. Any interaction lifts it right away. It is also lifted while
+a surprise is appearing, so that surprises are shown as usual. */
+
+#[derive(serde::Deserialize)]
+pub struct ScreenDimConfig {
+	idle_secs: u64,
+
+	// This is in local time, and it works like the theme schedule's time ranges (so it can wrap around midnight)
+	start: HourAndMinute,
+	end: HourAndMinute,
+
+	dim_alpha: u8 // This is how dark the overlay is, from 0 (no dimming) to 255 (fully black)
+}
+
+pub struct ScreenDimmer {
+	idle_duration: Duration,
+	start: NaiveTime,
+	end: NaiveTime,
+	overlay_color: ColorSDL,
+
+	last_activity_time: Instant,
+	is_dimmed: bool
+}
+
+impl ScreenDimmer {
+	pub fn new(config: &ScreenDimConfig) -> GenericResult<Self> {
+		let (Some(start), Some(end)) = (themes::to_time(config.start), themes::to_time(config.end)) else {
+			return error_msg!("Invalid start or end time for the screen dimming hours");
+		};
+
+		log::info!("The screen will be dimmed after {} seconds of inactivity, from {start} to {end}.", config.idle_secs);
+
+		Ok(Self {
+			idle_duration: Duration::from_secs(config.idle_secs),
+			start,
+			end,
+			overlay_color: ColorSDL::RGBA(0, 0, 0, config.dim_alpha),
+
+			last_activity_time: Instant::now(),
+			is_dimmed: false
+		})
+	}
+
+	pub fn register_activity(&mut self) {
+		self.last_activity_time = Instant::now();
+	}
+
+	// This returns if the dimming changed (in which case the screen should be redrawn)
+	pub fn update(&mut self, surprise_is_appearing: bool) -> bool {
+		let in_dimming_hours = themes::time_range_contains(self.start, self.end, chrono::Local::now().time());
+
+		let should_dim = in_dimming_hours && !surprise_is_appearing
+			&& self.last_activity_time.elapsed() >= self.idle_duration;
+
+		if should_dim == self.is_dimmed {
+			return false;
+		}
+
+		log::info!("{} the screen.", if should_dim {"Dimming"} else {"Undimming"});
+		self.is_dimmed = should_dim;
+		true
+	}
+
+	// This should be called after everything else is drawn
+	pub fn draw(&self, sdl_canvas: &mut CanvasSDL) -> MaybeError {
+		if !self.is_dimmed {
+			return Ok(());
+		}
+
+		let prev_blend_mode = sdl_canvas.blend_mode();
+
+		sdl_canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
+		sdl_canvas.set_draw_color(self.overlay_color);
+		let result = sdl_canvas.fill_rect(None).to_generic();
+		sdl_canvas.set_blend_mode(prev_blend_mode);
+
+		result
+	}
+}
@@ -0,0 +1,34 @@
+use sdl2::{pixels::PixelFormatEnum, surface::Surface, image::SaveSurface};
+
+use crate::{
+	window_tree::CanvasSDL,
+	utility_types::generic_result::*
+};
+
+//////////
+
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+/* The pixels are always read back in this format. SDL converts from the render target's own
+format (whether that is RGBA, ARGB, or something else), so the surface below always matches. */
+const READ_BACK_PIXEL_FORMAT: PixelFormatEnum = PixelFormatEnum::ARGB8888;
+const BYTES_PER_PIXEL: u32 = 4;
+
+/* This saves what is currently on the canvas to a PNG with a timestamped filename, and returns its path.
+It should be called after rendering, but before presenting, since the back buffer's contents are undefined after a present.
+Reading back pixels is not supported by every renderer, in which case this returns an error. */
+pub fn save_screenshot(canvas: &CanvasSDL) -> GenericResult<String> {
+	let (width, height) = canvas.output_size().to_generic()?;
+
+	let mut pixels = canvas.read_pixels(None, READ_BACK_PIXEL_FORMAT).to_generic()
+		.context("Reading back the canvas pixels is not supported by this renderer")?;
+
+	let surface = Surface::from_data(&mut pixels, width, height, width * BYTES_PER_PIXEL, READ_BACK_PIXEL_FORMAT).to_generic()?;
+
+	std::fs::create_dir_all(SCREENSHOT_DIRECTORY)?;
+
+	let path = format!("{SCREENSHOT_DIRECTORY}/dashboard_{}.png", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+	surface.save(&path).to_generic()?;
+
+	Ok(path)
+}
@@ -1,12 +1,13 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::{Mutex, OnceLock, PoisonError}};
 
 use crate::{
 	request,
+	test_data,
 	utility_types::generic_result::*,
 
 	spinitron::{
 		wrapper_types::MaybeSpinitronModelId,
-		model::{SpinitronModelWithProps, NUM_SPINITRON_MODEL_TYPES}
+		model::{SpinitronModelWithProps, SpinitronModelName, NUM_SPINITRON_MODEL_TYPES}
 	}
 };
 
@@ -16,6 +17,43 @@ use crate::{
 - Fix the mysterious Serde-Spinitron-API error (that arose from a portion of the logs on the studio dashboard)
 */
 
+////////// Optionally keeping the last raw response for each model type (for debugging)
+
+/* If this is set, the last raw JSON response for each model type is kept (cut off at this many bytes),
+so that it can be looked at when the spin data looks wrong. It is off by default, since it costs some memory. */
+static MAYBE_MAX_RAW_RESPONSE_BYTES: OnceLock<usize> = OnceLock::new();
+
+// These are indexed by model name (like the valid endpoints below)
+const NO_RAW_RESPONSE: Option<String> = None;
+static LAST_RAW_RESPONSES: Mutex<[Option<String>; NUM_SPINITRON_MODEL_TYPES]> = Mutex::new([NO_RAW_RESPONSE; NUM_SPINITRON_MODEL_TYPES]);
+
+pub fn enable_raw_response_retention(max_bytes_per_model: usize) -> MaybeError {
+	MAYBE_MAX_RAW_RESPONSE_BYTES.set(max_bytes_per_model).ok().context("Raw Spinitron response retention was already turned on")?;
+	log::info!("The last raw Spinitron response for each model will be kept (up to {max_bytes_per_model} bytes each).");
+	Ok(())
+}
+
+pub fn get_last_raw_response(model_name: SpinitronModelName) -> Option<String> {
+	LAST_RAW_RESPONSES.lock().unwrap_or_else(PoisonError::into_inner)[model_name as usize].clone()
+}
+
+fn maybe_retain_raw_response(endpoint_index: usize, json: &serde_json::Value) {
+	let Some(&max_bytes) = MAYBE_MAX_RAW_RESPONSE_BYTES.get() else {return};
+
+	let mut raw_response = serde_json::to_string_pretty(json).unwrap_or_else(|err| format!("<Could not format the response: '{err}'>"));
+
+	if raw_response.len() > max_bytes {
+		let mut cutoff = max_bytes;
+		while !raw_response.is_char_boundary(cutoff) {cutoff -= 1;}
+		raw_response.truncate(cutoff);
+		raw_response.push_str("\n<Cut off here>");
+	}
+
+	LAST_RAW_RESPONSES.lock().unwrap_or_else(PoisonError::into_inner)[endpoint_index] = Some(raw_response);
+}
+
+//////////
+
 fn get_json_from_spinitron_request<T: SpinitronModelWithProps>(
 	api_key: &str, possible_model_id: MaybeSpinitronModelId,
 	possible_item_count: Option<u16>
@@ -33,10 +71,26 @@ fn get_json_from_spinitron_request<T: SpinitronModelWithProps>(
 
 	////////// Checking endpoint validity
 
+	// These are in the same order as the model names
 	const VALID_ENDPOINTS: [&str; NUM_SPINITRON_MODEL_TYPES] = ["spins", "playlists", "personas", "shows"];
 
-	if !VALID_ENDPOINTS.contains(&api_endpoint.as_str()) {
+	let Some(endpoint_index) = VALID_ENDPOINTS.iter().position(|&endpoint| endpoint == api_endpoint) else {
 		return error_msg!("Invalid Spinitron API endpoint '{api_endpoint}'");
+	};
+
+	////////// In test data mode, a canned model is used instead
+
+	let test_data_subdir = format!("spinitron/{api_endpoint}");
+
+	if test_data::is_enabled() {
+		let model_json = test_data::get_next_response(&test_data_subdir)?;
+
+		/* The canned responses are usually single models, so they are wrapped like a plural response when no model ID
+		is given (unless they are already whole plural responses, like the ones from dump mode) */
+		let json = if possible_model_id.is_some() || model_json.get("items").is_some() {model_json}
+			else {serde_json::json!({"items": [model_json]})};
+		maybe_retain_raw_response(endpoint_index, &json);
+		return Ok(json);
 	}
 
 	////////// Limiting the requested fields by what exists within the given model type
@@ -73,7 +127,10 @@ fn get_json_from_spinitron_request<T: SpinitronModelWithProps>(
 	Actually, don't do that, build the URL, and then cache the request itself (it will then be resent other times). */
 	let url = request::build_url("https://spinitron.com/api", &path_params, &query_params);
 
-	request::as_type(request::get(&url))
+	let json = request::as_type(request::get(&url))?;
+	test_data::maybe_dump_response(&test_data_subdir, &json);
+	maybe_retain_raw_response(endpoint_index, &json);
+	Ok(json)
 }
 
 fn get_vec_from_spinitron_json<T: SpinitronModelWithProps>(json: &serde_json::Value) -> GenericResult<Vec<T>> {
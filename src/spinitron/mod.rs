@@ -1,5 +1,5 @@
 mod api;
-mod wrapper_types;
+pub mod wrapper_types;
 
 pub mod model;
 pub mod state;
@@ -22,7 +22,7 @@ lazy_static::lazy_static!(
 	static ref SPIN_IMAGE_REGEXP: Regex = Regex::new(r#"^https:\/\/.+\d+x\d+bb.+$"#).unwrap();
 	static ref DEFAULT_PERSONA_AND_SHOW_IMAGE_REGEXP: Regex = Regex::new(r#"^https:\/\/farm\d.staticflickr\.com\/\d+\/.+\..+$"#).unwrap();
 
-	static ref SHOW_CATEGORY_EMOJIS_MAPPING: HashMap<&'static str, &'static str> = HashMap::from([
+	static ref SHOW_CATEGORY_EMOJIS_MAPPING: HashMap<String, String> = load_show_category_emojis_mapping(HashMap::from([
 		("Automation", "🤖"),
 		("Ambient", "🌌"),
 		("Blues", "🎺"),
@@ -43,9 +43,40 @@ lazy_static::lazy_static!(
 		("Regional", "🗺️"),
 		("Rock", "🎸"),
 		("Talk", "🗣️")
-	]);
+	]));
 );
 
+/* Show categories can be added or given different emojis without recompiling, through this file
+(it is optional). It is a JSON object that maps category names to emojis, and its entries are applied
+over the default ones above. */
+const SHOW_CATEGORY_EMOJIS_OVERRIDE_PATH: &str = "assets/show_category_emojis.json";
+
+// These are shown when a persona or show has no image, and when the current spin is expired
+const NO_PERSONA_IMAGE_PATH: &str = "assets/no_persona_image.png";
+const NO_SHOW_IMAGE_PATH: &str = "assets/no_show_image.png";
+const EXPIRED_SPIN_IMAGE_PATH: &str = "assets/polar_headphones_logo.png";
+
+pub const PLACEHOLDER_IMAGE_PATHS: [&str; 3] = [NO_PERSONA_IMAGE_PATH, NO_SHOW_IMAGE_PATH, EXPIRED_SPIN_IMAGE_PATH];
+
+// Shows with an unrecognized category (or no category) get the emojis for this one instead
+const FALLBACK_SHOW_CATEGORY: &str = "Music";
+
+fn load_show_category_emojis_mapping(defaults: HashMap<&str, &str>) -> HashMap<String, String> {
+	let mut mapping: HashMap<String, String> = defaults.into_iter()
+		.map(|(category, emojis)| (category.to_string(), emojis.to_string())).collect();
+
+	if std::path::Path::new(SHOW_CATEGORY_EMOJIS_OVERRIDE_PATH).exists() {
+		match crate::utility_types::json_utils::load_from_file::<HashMap<String, String>>(SHOW_CATEGORY_EMOJIS_OVERRIDE_PATH) {
+			Ok(overrides) => mapping.extend(overrides),
+
+			Err(err) => log::warn!("Could not load the show category emojis from \
+				'{SHOW_CATEGORY_EMOJIS_OVERRIDE_PATH}', so only the default ones will be used: '{err}'.")
+		}
+	}
+
+	mapping
+}
+
 ////////// This is a set of model-related traits
 
 pub type MaybeTextureCreationInfo<'a> = Option<TextureCreationInfo<'a>>;
@@ -133,7 +164,7 @@ derive_alias! {derive_spinitron_model_props => #[derive(Serialize, Deserialize,
 impl SpinitronModel for Spin {
 	fn get_id(&self) -> SpinitronModelId {self.id}
 
-	// TODO: for this, can I split it up into multiple lines, and then render multiline text somehow?
+	// This is for the combined spin text; `make_spinitron_windows` can also show the parts as separate lines
 	fn to_string(&self) -> String {format!("{} (from {}), by {}", self.song, self.release, self.artist)}
 
 	fn get_texture_creation_info(&self, (texture_width, texture_height): (u32, u32)) -> MaybeTextureCreationInfo {
@@ -168,7 +199,7 @@ impl SpinitronModel for Persona {
 	fn to_string(&self) -> String {format!("Welcome, {}!", self.name)}
 
 	fn get_texture_creation_info(&self, _: (u32, u32)) -> MaybeTextureCreationInfo {
-		Self::evaluate_model_image_url_for_persona_or_show(&self.image, "assets/no_persona_image.png")
+		Self::evaluate_model_image_url_for_persona_or_show(&self.image, NO_PERSONA_IMAGE_PATH)
 	}
 }
 
@@ -176,26 +207,31 @@ impl SpinitronModel for Show {
 	fn get_id(&self) -> SpinitronModelId {self.id}
 
 	fn to_string(&self) -> String {
-		let (mut show_emojis, mut spacing) = ("", "");
+		let maybe_emojis = if let Some(category) = &self.category {
+			let maybe_emojis = SHOW_CATEGORY_EMOJIS_MAPPING.get(category.as_str());
 
-		if let Some(category) = &self.category {
-			if let Some(emojis) = SHOW_CATEGORY_EMOJIS_MAPPING.get(category.as_str()) {
-				show_emojis = emojis;
-				spacing = " ";
-			}
-			else {
+			if maybe_emojis.is_none() {
 				log::warn!("Unrecognized genre '{category}' for show with name '{}'", self.title);
 			}
+
+			maybe_emojis
 		}
 		else {
 			log::warn!("No genre for show with name '{}'", self.title);
-		}
+			None
+		};
+
+		// The fallback category may have been overridden with nothing, in which case there are no emojis
+		let show_emojis = maybe_emojis.or_else(|| SHOW_CATEGORY_EMOJIS_MAPPING.get(FALLBACK_SHOW_CATEGORY))
+			.map_or("", String::as_str);
+
+		let spacing = if show_emojis.is_empty() {""} else {" "};
 
 		format!("{show_emojis}{spacing}This is '{}'{spacing}{show_emojis}", self.title)
 	}
 
 	fn get_texture_creation_info(&self, _: (u32, u32)) -> MaybeTextureCreationInfo {
-		Self::evaluate_model_image_url_for_persona_or_show(&self.image, "assets/no_show_image.png")
+		Self::evaluate_model_image_url_for_persona_or_show(&self.image, NO_SHOW_IMAGE_PATH)
 	}
 }
 
@@ -203,10 +239,39 @@ impl Spin {
 	// TODO: can I reduce the repetition on the `get`s?
 	pub fn get(api_key: &str) -> GenericResult<Self> {get_model_from_id(api_key, None)}
 
+	// Spinitron's times have no colon in their UTC offset (e.g. `-0500`), so one is added to make them valid RFC 3339
+	fn parse_time(time: &str) -> GenericResult<chrono::DateTime<chrono::Utc>> {
+		let mut amended_time = time.to_string();
+		amended_time.insert(amended_time.len() - 2, ':');
+		Ok(chrono::DateTime::parse_from_rfc3339(&amended_time)?.into())
+	}
+
 	pub fn get_end_time(&self) -> GenericResult<chrono::DateTime<chrono::Utc>> {
-		let mut amended_end = self.end.to_string();
-		amended_end.insert(amended_end.len() - 2, ':');
-		Ok(chrono::DateTime::parse_from_rfc3339(&amended_end)?.into())
+		Self::parse_time(&self.end)
+	}
+
+	/* This returns how far into the spin the current time is (from 0 to 1), or `None` if the spin has no duration.
+	It is clamped, so a song that runs longer than its duration stays at 1. */
+	pub fn get_progress_fraction(&self) -> GenericResult<Option<f64>> {
+		let Some(duration) = self.duration.filter(|&duration| duration != 0) else {
+			return Ok(None);
+		};
+
+		let time_since_start = chrono::Utc::now().signed_duration_since(Self::parse_time(&self.start)?);
+		let secs_since_start = time_since_start.num_milliseconds() as f64 / 1000.0;
+
+		Ok(Some((secs_since_start / duration as f64).clamp(0.0, 1.0)))
+	}
+
+	pub fn get_song(&self) -> &str {&self.song}
+	pub fn get_artist(&self) -> &str {&self.artist}
+	pub fn get_release(&self) -> &str {&self.release}
+
+	// These are short labels for the spin's flags (in a fixed order), for showing as badges
+	pub fn get_badge_labels(&self) -> Vec<&'static str> {
+		[(self.new, "NEW"), (self.va, "VA")].into_iter()
+			.filter_map(|(flag, label)| (flag == Some(true)).then_some(label))
+			.collect()
 	}
 
 	pub const fn to_string_when_spin_is_expired() -> &'static str {
@@ -214,12 +279,17 @@ impl Spin {
 	}
 
 	pub const fn get_texture_creation_info_when_spin_is_expired() -> TextureCreationInfo<'static> {
-		TextureCreationInfo::Path(Cow::Borrowed("assets/polar_headphones_logo.png"))
+		TextureCreationInfo::Path(Cow::Borrowed(EXPIRED_SPIN_IMAGE_PATH))
 	}
 }
 
 impl Playlist {
 	pub fn get(api_key: &str) -> GenericResult<Self> {get_model_from_id(api_key, None)}
+
+	// If the automation flag is not given, the playlist is assumed to be live
+	pub fn is_automated(&self) -> bool {
+		self.automation == Some(1)
+	}
 }
 
 impl Persona {
@@ -259,8 +329,9 @@ pub struct Spin {
 
 	// TODO: why is `time` not there?
 
-	duration: Uint,
+	start: String,
 	end: String,
+	duration: MaybeUint, // This is in seconds, and it is not always given
 
 	request: MaybeBool,
 	new: MaybeBool,
@@ -277,7 +348,7 @@ pub struct Spin {
 	/*
 	- Ignoring "_links" for now.
 	- Also not  keeping the playlist ID here, since if someone doesn't come to their show, then the playlist ID will be invalid.
-	- TODO: add start, end, and label later (given the start, can I figure out where I am in the song?)
+	- TODO: add the label later
 	*/
 
 	id: SpinitronModelId,
@@ -344,3 +415,32 @@ pub struct Show {
 	hide_dj: Uint, // 0 or 1
 	image: MaybeString
 });
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn spin_texture_creation_info_for_unusual_urls() {
+		let get_url = |image: &str| {
+			let spin = Spin {image: Some(image.to_owned()), ..Default::default()};
+
+			match spin.get_texture_creation_info((300, 300)) {
+				Some(TextureCreationInfo::Url(url)) => url.into_owned(),
+				_ => panic!("Expected a URL texture creation info for the spin image '{image}'")
+			}
+		};
+
+		// The size in a URL in the usual structure is replaced, even without a '.com' domain
+		assert_eq!(get_url("https://is1-ssl.mzstatic.net/image/thumb/170x170bb.jpg"), "https://is1-ssl.mzstatic.net/image/thumb/300x300bb.jpg");
+
+		// A URL in another structure (with or without '.com') does not panic, and it is kept exactly as it was
+		for url in ["https://images.example.org/cover.jpg", "https://example.com/cover", "https://no-dot-com/a", "not a url at all"] {
+			assert_eq!(get_url(url), url);
+		}
+
+		// An empty or missing image gives no texture creation info
+		assert!(Spin {image: Some(String::new()), ..Default::default()}.get_texture_creation_info((300, 300)).is_none());
+		assert!(Spin::default().get_texture_creation_info((300, 300)).is_none());
+	}
+}
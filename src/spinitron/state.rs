@@ -5,16 +5,21 @@ use chrono::Timelike;
 use crate::{
 	request,
 	texture::TextureCreationInfo,
+	window_tree::FadeThroughTransition,
 
 	utility_types::{
 		generic_result::*,
 		thread_task::{Updatable, ContinuallyUpdated}
 	},
 
-	spinitron::model::{
-		NUM_SPINITRON_MODEL_TYPES,
-		Spin, Playlist, Persona, Show,
-		SpinitronModel, SpinitronModelName
+	spinitron::{
+		api,
+
+		model::{
+			NUM_SPINITRON_MODEL_TYPES,
+			Spin, Playlist, Persona, Show,
+			SpinitronModel, SpinitronModelName
+		}
 	}
 };
 
@@ -69,6 +74,18 @@ impl SpinExpiryData {
 
 //////////
 
+/* After the playlist changes, the previous playlist and persona are kept around
+for a grace period, so that the outgoing DJ can be shown during a handoff. */
+#[derive(Clone)]
+struct PreviousPlaylistData {
+	playlist: Playlist,
+	persona: Persona,
+	persona_texture_bytes: Vec<u8>,
+	end_time: chrono::DateTime<chrono::Utc>
+}
+
+//////////
+
 #[derive(Clone)]
 struct SpinitronStateData {
 	api_key: String,
@@ -80,7 +97,14 @@ struct SpinitronStateData {
 
 	spin_expiry_data: SpinExpiryData,
 	precached_texture_bytes: [Vec<u8>; NUM_SPINITRON_MODEL_TYPES],
-	fallback_texture_creation_info: &'static TextureCreationInfo<'static>,
+	fallback_texture_creation_infos: &'static [TextureCreationInfo<'static>],
+
+	// This bounds how many model textures are requested at once (so that the API is not hammered)
+	max_concurrent_texture_requests: usize,
+
+	// If the retention duration is `None`, the previous playlist data is never kept
+	maybe_previous_playlist_retention_duration: Option<chrono::Duration>,
+	maybe_previous_playlist_data: Option<PreviousPlaylistData>,
 
 	/* The boolean at index `i` is true if the model at index `i` was recently
 	updated. Model indices are (in order) spin, playlist, persona, and show. */
@@ -88,16 +112,21 @@ struct SpinitronStateData {
 }
 
 type WindowSize = (u32, u32);
+type ModelWindowSizes = [WindowSize; NUM_SPINITRON_MODEL_TYPES]; // These are indexed by model name
 type SpinitronModels<'a> = [&'a dyn SpinitronModel; NUM_SPINITRON_MODEL_TYPES];
 
-// The third param is the fallback texture creation info, and the fourth one is the spin window size
-type SpinitronStateDataParams<'a> = (&'a str, chrono::Duration, &'static TextureCreationInfo<'static>, WindowSize);
+/* The third param is the set of fallback texture creation infos (which should not be empty), the fourth one is an initial model window size guess,
+the fifth one is how long to keep the previous playlist and persona for after the playlist changes, and the sixth one is
+the maximum number of model textures to request at once. */
+type SpinitronStateDataParams<'a> = (&'a str, chrono::Duration,
+	&'static [TextureCreationInfo<'static>], WindowSize, Option<chrono::Duration>, usize);
 
 //////////
 
 impl SpinitronStateData {
 	fn new((api_key, spin_expiry_duration,
-		fallback_texture_creation_info, spin_window_size):
+		fallback_texture_creation_infos, spin_window_size,
+		maybe_previous_playlist_retention_duration, max_concurrent_texture_requests):
 		SpinitronStateDataParams) -> GenericResult<Self> {
 
 		let spin = Spin::get(api_key)?;
@@ -116,18 +145,52 @@ impl SpinitronStateData {
 
 			spin_expiry_data,
 			precached_texture_bytes: [INITIAL_PRECACHED; NUM_SPINITRON_MODEL_TYPES],
-			fallback_texture_creation_info,
+			fallback_texture_creation_infos,
+			max_concurrent_texture_requests,
+
+			maybe_previous_playlist_retention_duration,
+			maybe_previous_playlist_data: None,
 
 			update_statuses: [false; NUM_SPINITRON_MODEL_TYPES]
 		};
 
-		data.precached_texture_bytes = data.get_models().map( // TODO: don't unwrap once `try_map` becomes stable
-			|model| data.get_model_texture_bytes(model, spin_window_size).unwrap()
-		);
+		let all_model_indices: Vec<usize> = (0..NUM_SPINITRON_MODEL_TYPES).collect();
+		let all_texture_bytes = data.get_model_texture_bytes_concurrently(&all_model_indices, &[spin_window_size; NUM_SPINITRON_MODEL_TYPES])?;
+
+		for (i, texture_bytes) in all_model_indices.into_iter().zip(all_texture_bytes) {
+			data.precached_texture_bytes[i] = texture_bytes;
+		}
 
 		Ok(data)
 	}
 
+	/* This gets the texture bytes for the models at the given indices, requesting up to `max_concurrent_texture_requests`
+	of them at once (since each one may be an image URL request). The results are in the same order as the indices. */
+	fn get_model_texture_bytes_concurrently(&self, model_indices: &[usize],
+		sizes_pixels: &ModelWindowSizes) -> GenericResult<Vec<Vec<u8>>> {
+
+		let mut all_texture_bytes = Vec::with_capacity(model_indices.len());
+
+		for index_chunk in model_indices.chunks(self.max_concurrent_texture_requests.max(1)) {
+			let chunk_results: Vec<GenericResult<Vec<u8>>> = std::thread::scope(|scope| {
+				let thread_handles: Vec<_> = index_chunk.iter().map(|&i| scope.spawn(
+					move || self.get_model_texture_bytes(self.get_models()[i], sizes_pixels[i])
+				)).collect();
+
+				// The handles are joined in order, so the results stay in the same order as the indices
+				thread_handles.into_iter().map(|thread_handle| thread_handle.join()
+					.unwrap_or_else(|_| error_msg!("A Spinitron model texture request thread panicked"))
+				).collect()
+			});
+
+			for texture_bytes in chunk_results {
+				all_texture_bytes.push(texture_bytes?);
+			}
+		}
+
+		Ok(all_texture_bytes)
+	}
+
 	fn get_model_texture_bytes(&self, model: &dyn SpinitronModel, size_pixels: WindowSize) -> GenericResult<Vec<u8>> {
 		fn load_for_info(info: Cow<TextureCreationInfo>) -> GenericResult<Vec<u8>> {
 			/* I am doing this to speed up the loading of textures on the main
@@ -140,22 +203,31 @@ impl SpinitronStateData {
 				TextureCreationInfo::Url(url) =>
 					Ok(request::get(url)?.as_bytes().to_vec()),
 
+				/* These should not happen, but if they do (e.g. after a model change), they are errors rather than panics,
+				so that the fallback texture is used, and the dashboard keeps running */
 				TextureCreationInfo::RawBytes(_) =>
-					panic!("Spinitron model textures should not be returning raw bytes!"),
+					error_msg!("Spinitron model textures should not be returning raw bytes"),
 
 				TextureCreationInfo::Text(_) =>
-					panic!("Precaching the text texture creation info is not supported for plain Spinitron model textures!")
+					error_msg!("Precaching the text texture creation info is not supported for plain Spinitron model textures")
 			}
 		}
 
+		/* This runs on the Spinitron thread, so it can't use the shared window state's random
+		generator (and the thread-local one is used instead). The fallbacks were validated when the dashboard was made. */
+		use rand::seq::SliceRandom;
+
+		let fallback_texture_creation_info = self.fallback_texture_creation_infos.choose(&mut rand::thread_rng())
+			.context("There are no fallback textures to choose from")?;
+
 		let info = match model.get_texture_creation_info(size_pixels) {
 			Some(texture_creation_info) => Cow::Owned(texture_creation_info),
-			None => Cow::Borrowed(self.fallback_texture_creation_info)
+			None => Cow::Borrowed(fallback_texture_creation_info)
 		};
 
 		load_for_info(info).or_else(|error| {
 			log::warn!("Reverting to fallback texture for Spinitron model. Error: '{error}'");
-			load_for_info(Cow::Borrowed(self.fallback_texture_creation_info))
+			load_for_info(Cow::Borrowed(fallback_texture_creation_info))
 		})
 	}
 
@@ -204,7 +276,7 @@ impl SpinitronStateData {
 }
 
 impl Updatable for SpinitronStateData {
-	type Param = WindowSize;
+	type Param = ModelWindowSizes;
 
 	fn update(&mut self, param: &Self::Param) -> MaybeError {
 		////////// Update the models
@@ -213,21 +285,47 @@ impl Updatable for SpinitronStateData {
 			data.get_models().map(|model| model.get_id());
 
 		let original_ids = get_model_ids(self);
+		let (original_playlist, original_persona) = (self.playlist.clone(), self.persona.clone());
 		self.sync_models()?;
 		let new_ids = get_model_ids(self);
 
+		////////// Keeping the previous playlist and persona around (this is before the persona texture is replaced)
+
+		let playlist_index = SpinitronModelName::Playlist as usize;
+		let persona_index = SpinitronModelName::Persona as usize;
+
+		if let Some(retention_duration) = self.maybe_previous_playlist_retention_duration {
+			if original_ids[playlist_index] != new_ids[playlist_index] {
+				self.maybe_previous_playlist_data = Some(PreviousPlaylistData {
+					playlist: original_playlist,
+					persona: original_persona,
+					persona_texture_bytes: self.precached_texture_bytes[persona_index].clone(),
+					end_time: chrono::Utc::now() + retention_duration
+				});
+			}
+		}
+
+		// Once the grace period ends, the previous data (including its texture bytes) is dropped
+		if let Some(previous_playlist_data) = &self.maybe_previous_playlist_data {
+			if chrono::Utc::now() >= previous_playlist_data.end_time {
+				self.maybe_previous_playlist_data = None;
+			}
+		}
+
 		////////// Update the model textures
 
 		// TODO: how to do this without all the indexing?
-		for i in 0..NUM_SPINITRON_MODEL_TYPES {
-			let updated = original_ids[i] != new_ids[i];
+		let updated_model_indices: Vec<usize> = (0..NUM_SPINITRON_MODEL_TYPES)
+			.filter(|&i| original_ids[i] != new_ids[i]).collect();
 
-			if updated {
-				let model = self.get_models()[i];
-				self.precached_texture_bytes[i] = self.get_model_texture_bytes(model, *param)?;
-			}
+		let updated_texture_bytes = self.get_model_texture_bytes_concurrently(&updated_model_indices, param)?;
 
-			self.update_statuses[i] = updated;
+		for (&i, texture_bytes) in updated_model_indices.iter().zip(updated_texture_bytes) {
+			self.precached_texture_bytes[i] = texture_bytes;
+		}
+
+		for i in 0..NUM_SPINITRON_MODEL_TYPES {
+			self.update_statuses[i] = updated_model_indices.contains(&i);
 		}
 
 		////////// Marking the expiration of the current spin
@@ -242,18 +340,23 @@ impl Updatable for SpinitronStateData {
 
 pub struct SpinitronState {
 	continually_updated: ContinuallyUpdated<SpinitronStateData>,
-	saved_continually_updated_param: <SpinitronStateData as Updatable>::Param
+	saved_continually_updated_param: <SpinitronStateData as Updatable>::Param,
+
+	// If this is `None`, model images change right away
+	maybe_image_transition: Option<FadeThroughTransition>
 }
 
 impl SpinitronState {
 	pub fn new(params: SpinitronStateDataParams) -> GenericResult<Self> {
 		let data = SpinitronStateData::new(params)?;
 
-		let initial_spin_window_size_guess = params.3;
+		// Until the model windows register their sizes, each model uses the same size guess
+		let initial_window_size_guesses = [params.3; NUM_SPINITRON_MODEL_TYPES];
 
 		Ok(Self {
-			continually_updated: ContinuallyUpdated::new(&data, &initial_spin_window_size_guess, "Spinitron"),
-			saved_continually_updated_param: initial_spin_window_size_guess
+			continually_updated: ContinuallyUpdated::new(&data, &initial_window_size_guesses, "Spinitron"),
+			saved_continually_updated_param: initial_window_size_guesses,
+			maybe_image_transition: None
 		})
 	}
 
@@ -273,15 +376,28 @@ impl SpinitronState {
 		matches!(model_name, SpinitronModelName::Spin) && self.continually_updated.get_data().spin_expiry_data.just_expired
 	}
 
+	pub const fn get_spin(&self) -> &Spin {
+		&self.continually_updated.get_data().spin
+	}
+
+	pub const fn get_playlist(&self) -> &Playlist {
+		&self.continually_updated.get_data().playlist
+	}
+
+	// Unlike `is_spin_and_just_expired`, this stays true for as long as the spin is expired
+	pub const fn spin_is_expired(&self) -> bool {
+		self.continually_updated.get_data().spin_expiry_data.marked_as_expired
+	}
+
 	pub const fn model_was_updated(&self, model_name: SpinitronModelName) -> bool {
 		self.is_spin_and_just_expired(model_name) || self.continually_updated.get_data().update_statuses[model_name as usize]
 	}
 
-	/* This is meant to be called by a spin texture window, so that the
-	spin window size can be given to the continual updater (which preloads
-	the spin texture's data on its line of execution, for less load times). */
-	pub fn register_spin_window_size(&mut self, size: WindowSize) {
-		self.saved_continually_updated_param = size;
+	/* This is meant to be called by model texture windows, so that their sizes
+	can be given to the continual updater (which preloads the model textures'
+	data on its line of execution, for less load times). */
+	pub fn register_model_window_size(&mut self, model_name: SpinitronModelName, size: WindowSize) {
+		self.saved_continually_updated_param[model_name as usize] = size;
 	}
 
 	// Note: this is not for text textures.
@@ -299,4 +415,60 @@ impl SpinitronState {
 	pub fn update(&mut self) -> GenericResult<bool> {
 		self.continually_updated.update(&self.saved_continually_updated_param)
 	}
+
+	////////// These are for the previous playlist and persona (which are only kept for a grace period after a playlist change)
+
+	pub const fn get_previous_playlist(&self) -> Option<&Playlist> {
+		match &self.continually_updated.get_data().maybe_previous_playlist_data {
+			Some(previous_playlist_data) => Some(&previous_playlist_data.playlist),
+			None => None
+		}
+	}
+
+	pub const fn get_previous_persona(&self) -> Option<&Persona> {
+		match &self.continually_updated.get_data().maybe_previous_playlist_data {
+			Some(previous_playlist_data) => Some(&previous_playlist_data.persona),
+			None => None
+		}
+	}
+
+	// Note: this is not for text textures.
+	pub fn get_previous_persona_texture_creation_info(&self) -> Option<TextureCreationInfo> {
+		self.continually_updated.get_data().maybe_previous_playlist_data.as_ref().map(
+			|previous_playlist_data| TextureCreationInfo::RawBytes(&previous_playlist_data.persona_texture_bytes)
+		)
+	}
+
+	/* This keeps the last raw JSON response for each model type, for debugging. It can only be turned on
+	at startup (before the state is made), and each response is cut off at the given number of bytes. */
+	pub fn enable_raw_json_retention(max_bytes_per_model: usize) -> MaybeError {
+		api::enable_raw_response_retention(max_bytes_per_model)
+	}
+
+	// This is `None` if raw JSON retention is off, or if the model has not been requested yet
+	pub fn get_last_raw_model_json(&self, model_name: SpinitronModelName) -> Option<String> {
+		api::get_last_raw_response(model_name)
+	}
+
+	/* This can be changed at any time (e.g. to turn transitions off while updates are rapid). A transition that is
+	already running keeps its own copy of its parameters, so it finishes as it started. */
+	pub fn set_image_transition(&mut self, maybe_image_transition: Option<FadeThroughTransition>) {
+		self.maybe_image_transition = maybe_image_transition;
+	}
+
+	pub const fn get_image_transition(&self) -> Option<FadeThroughTransition> {
+		self.maybe_image_transition
+	}
+
+	pub fn time_since_last_update(&self) -> std::time::Duration {
+		self.continually_updated.time_since_last_success()
+	}
+
+	pub fn request_refresh(&mut self) {
+		self.continually_updated.request_refresh();
+	}
+
+	pub const fn is_refresh_pending(&self) -> bool {
+		self.continually_updated.is_refresh_pending()
+	}
 }
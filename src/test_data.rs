@@ -0,0 +1,114 @@
+use std::{
+	sync::{Mutex, OnceLock, atomic::{AtomicUsize, Ordering}},
+	collections::HashMap
+};
+
+use crate::utility_types::{generic_result::*, json_utils};
+
+//////////
+
+/* In test data mode, the Spinitron and Twilio states read their API responses from local JSON files,
+rather than from the network (so layouts can be worked on offline, without API keys). It is turned on
+by giving a test data directory in the app config, and it can only be turned on at startup.
+
+Each kind of response has its own subdirectory, and its JSON files are cycled through in filename order,
+one per request (so one per update, for each kind of response). The expected layout is this (where `<n>` is any file name):
+
+- `<dir>/spinitron/spins/<n>.json`
+- `<dir>/spinitron/playlists/<n>.json`
+- `<dir>/spinitron/personas/<n>.json`
+- `<dir>/spinitron/shows/<n>.json`
+- `<dir>/twilio/Messages/<n>.json`
+- `<dir>/weather/events/<n>.json`
+- `<dir>/weather/realtime/<n>.json`
+
+Each Spinitron file is one model object, in the same format as one of the `items` from the Spinitron API
+(e.g. `{"id": 1, "playlist_id": 2, "start": ..., "end": ..., ...}` for a spin), with all of the fields that
+the model in `spinitron/model.rs` has. A whole Spinitron response (with its `items`) also works. Each Twilio file is a whole response from Twilio's `Messages` endpoint (i.e. `{"messages": [...]}`,
+where each message has a `uri`, `from`, `body`, and an RFC 2822 `date_created`).
+
+The timestamps are used as they are, so spins end and messages age out as usual (so for example, a spin with
+an end time in the past shows up as expired, and messages older than the message history duration are hidden). Each weather
+file is a whole response from tomorrow.io's `events` or `weather/realtime` endpoint (a realtime response needs
+at least `{"data": {"values": {"weatherCode": ..., "temperature": ...}}}`).
+
+There is also a dump mode (for debugging API problems), where every API response from the network is written to a
+dump directory, in the layout above. The files are named by the time that they were received, so giving a dump directory
+as the test data directory later replays the responses in order. Dump mode writes a lot, so it is off by default. */
+
+static TEST_DATA_DIR: OnceLock<String> = OnceLock::new();
+
+static DUMP_DIR: OnceLock<String> = OnceLock::new();
+
+// This keeps responses received in the same millisecond in order
+static NEXT_DUMP_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+// This maps response subdirectories to the index of the next file to read from them
+static NEXT_FILE_INDICES: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+pub fn enable(dir: &str) -> MaybeError {
+	if !std::path::Path::new(dir).is_dir() {
+		return error_msg!("The test data directory '{dir}' does not exist");
+	}
+
+	TEST_DATA_DIR.set(dir.to_string()).ok().context("Test data mode was already turned on")?;
+	log::warn!("Test data mode is on, so Spinitron and Twilio data will be read from '{dir}' instead of the network.");
+	Ok(())
+}
+
+pub fn is_enabled() -> bool {
+	TEST_DATA_DIR.get().is_some()
+}
+
+// The subdirectory is relative to the test data directory (e.g. `spinitron/spins`)
+pub fn get_next_response(subdir: &str) -> GenericResult<serde_json::Value> {
+	let dir = TEST_DATA_DIR.get().context("Test data mode is not on")?;
+	let full_subdir = format!("{dir}/{subdir}");
+
+	let mut paths = std::fs::read_dir(&full_subdir).context(format!("Could not read the test data directory '{full_subdir}'"))?
+		.filter_map(|maybe_entry| maybe_entry.ok().map(|entry| entry.path()))
+		.filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+		.collect::<Vec<_>>();
+
+	if paths.is_empty() {
+		return error_msg!("There are no JSON files in the test data directory '{full_subdir}'");
+	}
+
+	paths.sort();
+
+	let file_index = {
+		let mut next_file_indices = NEXT_FILE_INDICES.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+		let next_file_index = next_file_indices.get_or_insert_with(HashMap::new).entry(full_subdir).or_insert(0);
+		let file_index = *next_file_index % paths.len();
+		*next_file_index = file_index + 1;
+		file_index
+	};
+
+	let path = paths[file_index].to_str().context("Expected the test data path to be valid UTF-8")?;
+	json_utils::load_from_file(path)
+}
+
+//////////
+
+pub fn enable_dumping(dir: &str) -> MaybeError {
+	std::fs::create_dir_all(dir).context(format!("Could not make the API response dump directory '{dir}'"))?;
+	DUMP_DIR.set(dir.to_string()).ok().context("API response dumping was already turned on")?;
+	log::warn!("API response dumping is on, so every API response will be written to '{dir}'.");
+	Ok(())
+}
+
+// This does nothing if dump mode is off. Dumping failures are only logged, so that they never stop an update.
+pub fn maybe_dump_response(subdir: &str, response: &serde_json::Value) {
+	let Some(dir) = DUMP_DIR.get() else {return};
+
+	let full_subdir = format!("{dir}/{subdir}");
+	let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f");
+	let path = format!("{full_subdir}/{timestamp}_{:06}.json", NEXT_DUMP_INDEX.fetch_add(1, Ordering::Relaxed));
+
+	let result = std::fs::create_dir_all(&full_subdir)
+		.and_then(|_| std::fs::write(&path, response.to_string()));
+
+	if let Err(err) = result {
+		log::warn!("Could not dump an API response to '{path}': '{err}'.");
+	}
+}
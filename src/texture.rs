@@ -1,13 +1,17 @@
 use std::{
 	borrow::Cow,
-	collections::HashMap
+	collections::{HashMap, hash_map::Entry},
+	time::{Duration, Instant},
+	sync::{Mutex, atomic::{AtomicBool, Ordering}}
 };
 
 use sdl2::{
 	ttf,
-	rect::Rect,
+	rect::{Rect, Point},
+	rwops::RWops,
 	surface::Surface,
-	image::LoadTexture,
+	pixels::PixelFormatEnum,
+	image::{LoadSurface, ImageRWops},
 	render::{self, Texture}
 };
 
@@ -24,22 +28,74 @@ use crate::{
 The needed structs + data can go there, and the text
 + font scaling metadata can then go in its own struct. */
 
+// A path can be chosen at runtime, and bytes are for fonts embedded with `include_bytes!`
+#[derive(Clone)]
+pub enum FontSource {
+	Path(Cow<'static, str>),
+	Bytes(&'static [u8])
+}
+
+/* Embedded fonts are keyed by the address and length of their bytes
+(rather than the bytes themselves), so that keying them stays cheap */
+#[derive(Hash, Eq, PartialEq)]
+enum FontSourceCacheKey {
+	Path(Cow<'static, str>),
+	Bytes(usize, usize)
+}
+
+impl FontSource {
+	fn get_cache_key(&self) -> FontSourceCacheKey {
+		match self {
+			Self::Path(path) => FontSourceCacheKey::Path(path.clone()),
+			Self::Bytes(bytes) => FontSourceCacheKey::Bytes(bytes.as_ptr() as usize, bytes.len())
+		}
+	}
+}
+
+impl std::fmt::Display for FontSource {
+	fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Path(path) => write!(formatter, "'{path}'"),
+			Self::Bytes(bytes) => write!(formatter, "<embedded font of {} bytes>", bytes.len())
+		}
+	}
+}
+
 // TODO: make a constructor for this, instead of making everything `pub`.
 #[derive(Clone)]
 pub struct FontInfo {
 	/* TODO:
-	- Support non-static paths for these two
 	- Allow for a variable number of fallback fonts too
 	- Only load fallbacks when necessary
 	*/
-	pub path: &'static str,
-	pub unusual_chars_fallback_path: &'static str,
+	pub source: FontSource,
+	pub unusual_chars_fallback_source: FontSource,
 
 	pub font_has_char: fn(&ttf::Font, char) -> bool,
 
 	pub style: ttf::FontStyle,
 	pub hinting: ttf::Hinting,
-	pub maybe_outline_width: Option<u16>
+	pub maybe_outline_width: Option<u16>,
+
+	/* If this is set, the first time that these fonts are loaded, a sample of codepoints
+	from some common Unicode blocks is checked against both fonts, and the blocks with missing
+	glyphs are logged. This is for catching missing glyphs before they show up on the screen. */
+	pub validate_font_coverage: bool
+}
+
+/* If this is enabled, emojis are rendered as color glyphs: the variation selectors that ask for colored emojis are kept,
+and emojis are always drawn with the fallback font, in their own colors (so a fill gradient is not applied to them).
+This needs the fallback font to be a color emoji font, and SDL_ttf 2.0.18 or later. It is off by default, since color
+emojis are not rendered correctly on the Pi; then the variation selectors are removed, and emojis are monochrome. */
+static COLOR_EMOJI_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable_color_emoji() {
+	COLOR_EMOJI_ENABLED.store(true, Ordering::Relaxed);
+	log::info!("Emojis will be rendered as color glyphs.");
+}
+
+fn color_emoji_enabled() -> bool {
+	COLOR_EMOJI_ENABLED.load(Ordering::Relaxed)
 }
 
 #[derive(Clone)]
@@ -49,36 +105,24 @@ pub struct DisplayText<'a> {
 
 impl<'a> DisplayText<'a> {
 	pub fn new(text: &str) -> Self {
-		// Indicates that emojis should be made colored; not rendered correctly on the Pi
-		const UNICODE_VARIATION_SELECTOR_16: char = '\u{FE0F}';
-
-		const WHITESPACE_REPLACEMENT_PAIRS: [(char, &str); 3] = [
+		const WHITESPACE_REPLACEMENT_PAIRS: [(char, &str); 2] = [
 			('\t', "    "),
-			('\n', " "),
-			(UNICODE_VARIATION_SELECTOR_16, "")
-		];
-
-		/* TODO:
-		- Should I add the rest of the blank characters (see https://invisible-characters.com/ for all), for better cleanup?
-		- The second reason for this is to stop 'nonavailable' character variants to appear - although this would be hard to verify
-		*/
-		const ALL_WHITESPACE_CHARS: [char; 4] = [
-			' ', '\t', '\n', UNICODE_VARIATION_SELECTOR_16
+			('\n', " ")
 		];
 
 		//////////
 
-		let trimmed_text = text.trim();
-		let is_whitespace = |c: char| ALL_WHITESPACE_CHARS.contains(&c);
+		let visible_text = Self::remove_invisible_chars(text, color_emoji_enabled());
+		let trimmed_text = visible_text.trim();
 
-		/* If a string is only whitespace, make it empty.
+		/* If a string is only whitespace (or invisible characters), make it empty.
 		This also implicitly covers completely empty strings,
 		and plenty of blank Unicode characters (that comes from `trim`).
 
 		Note that this does not return "<BLANK TEXT>" since the case for that
 		is based on if the rendered surface has zero width, not based on the contained
 		characters for the string (and the former should be more reliable). */
-		if trimmed_text.chars().all(is_whitespace) {
+		if trimmed_text.is_empty() {
 			return Self {text: Cow::Borrowed("")};
 		}
 
@@ -98,6 +142,56 @@ impl<'a> DisplayText<'a> {
 		Self {text: Cow::Owned(adjusted)}
 	}
 
+	const UNICODE_VARIATION_SELECTOR_16: char = '\u{FE0F}'; // Indicates that emojis should be made colored
+	const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+	// This is a rough check, which covers the miscellaneous symbols, dingbats, and the emoji blocks
+	const fn is_emoji(c: char) -> bool {
+		matches!(c, '\u{2600}'..='\u{27BF}' | '\u{1F000}'..='\u{1FAFF}')
+	}
+
+	// With color emoji enabled, these characters are always rendered with the fallback font (so that emoji sequences stay together)
+	const fn is_emoji_sequence_char(c: char) -> bool {
+		Self::is_emoji(c) || matches!(c, Self::UNICODE_VARIATION_SELECTOR_16 | Self::ZERO_WIDTH_JOINER)
+	}
+
+	/* This removes zero-width and other invisible characters (see https://invisible-characters.com/),
+	which can make odd gaps in the rendered text, or show up as boxes. Zero-width joiners are kept
+	when they join two emojis together though, since they are part of emoji sequences. The variation
+	selectors for colored emojis are only kept with color emoji enabled (they are not rendered correctly on the Pi).
+
+	TODO: stop 'nonavailable' character variants from appearing too (although this would be hard to verify) */
+	fn remove_invisible_chars(text: &str, keep_variation_selectors: bool) -> String {
+		let is_invisible = |c: char| matches!(c,
+			'\u{00AD}' // Soft hyphen
+			| '\u{034F}' // Combining grapheme joiner
+			| '\u{115F}' | '\u{1160}' | '\u{3164}' | '\u{FFA0}' // Hangul fillers
+			| '\u{180E}' // Mongolian vowel separator
+			| '\u{200B}'..='\u{200F}' // Zero-width space, non-joiner, and joiner, and left-to-right and right-to-left marks
+			| '\u{202A}'..='\u{202E}' // Bidirectional embeddings and overrides
+			| '\u{2060}'..='\u{206F}' // Word joiner, invisible math operators, bidirectional isolates, and deprecated format characters
+			| '\u{FEFF}' // Byte order mark (or zero-width no-break space)
+		);
+
+		// If they are not kept, the variation selectors are removed first, so that they do not get between joiners and emojis
+		let chars: Vec<char> = text.chars().filter(|&c| keep_variation_selectors || c != Self::UNICODE_VARIATION_SELECTOR_16).collect();
+
+		// A kept variation selector right after an emoji is part of it, so a joiner after the two is still between two emojis
+		let ends_emoji_at = |i: usize| match chars[i] {
+			Self::UNICODE_VARIATION_SELECTOR_16 => i != 0 && Self::is_emoji(chars[i - 1]),
+			c => Self::is_emoji(c)
+		};
+
+		chars.iter().enumerate().filter(|&(i, &c)| {
+			if c == Self::ZERO_WIDTH_JOINER {
+				i != 0 && ends_emoji_at(i - 1) && chars.get(i + 1).is_some_and(|&next| Self::is_emoji(next))
+			}
+			else {
+				!is_invisible(c)
+			}
+		}).map(|(_, &c)| c).collect()
+	}
+
 	// This assumes that the inputted padding characters should not be trimmed/preprocessed at all
 	pub fn with_padding(self, left: &str, right: &str) -> Self {
 		let mut text = self.text.to_string();
@@ -109,20 +203,151 @@ impl<'a> DisplayText<'a> {
 
 //////////
 
-/* Input: seed, and if the text fits fully in the box.
+#[derive(Copy, Clone)]
+pub enum TextFill {
+	Solid(ColorSDL),
+
+	// This goes from the top color at the top of the text area to the bottom color at the bottom of it
+	Gradient {top: ColorSDL, bottom: ColorSDL}
+}
+
+impl From<ColorSDL> for TextFill {
+	fn from(color: ColorSDL) -> Self {
+		Self::Solid(color)
+	}
+}
+
+impl TextFill {
+	// Gradient text is rendered in white, and then modulated per-pixel afterwards
+	const fn base_color(self) -> ColorSDL {
+		match self {
+			Self::Solid(color) => color,
+			Self::Gradient {..} => ColorSDL::WHITE
+		}
+	}
+
+	/* This modulates each row of the surface by the gradient color for that row. The row offset is
+	where the surface's first row is within the full text area, which is `total_height` pixels tall. */
+	fn maybe_apply_gradient<'a>(self, surface: Surface<'a>, row_offset: u32, total_height: u32) -> GenericResult<Surface<'a>> {
+		let Self::Gradient {top, bottom} = self else {return Ok(surface);};
+
+		// `RGBA32` has the same byte order on every platform, which makes the channels simple to index
+		let mut surface = surface.convert_format(sdl2::pixels::PixelFormatEnum::RGBA32).to_generic()?;
+		let (width, pitch) = (surface.width() as usize, surface.pitch() as usize);
+		let last_row_index = total_height.saturating_sub(1).max(1) as f32;
+
+		surface.with_lock_mut(|pixels| {
+			for (row_index, row) in pixels.chunks_exact_mut(pitch).enumerate() {
+				let t = ((row_index as u32 + row_offset) as f32 / last_row_index).min(1.0);
+				let lerp = |a: u8, b: u8| a as f32 + (b as f32 - a as f32) * t;
+				let row_color = [lerp(top.r, bottom.r), lerp(top.g, bottom.g), lerp(top.b, bottom.b), lerp(top.a, bottom.a)];
+
+				for pixel in row[..width * 4].chunks_exact_mut(4) {
+					for (channel, modulator) in pixel.iter_mut().zip(row_color) {
+						*channel = (*channel as f32 * modulator / 255.0) as u8;
+					}
+				}
+			}
+		});
+
+		Ok(surface)
+	}
+}
+
+/* Input: seed, if the text fits fully in the box, and the fraction of each scroll cycle to pause for.
 Output: scroll amount (in [0, 1]), and if the text should wrap or not. */
-pub type TextTextureScrollFn = fn(f64, bool) -> (f64, bool);
+pub type TextTextureScrollFn = fn(f64, bool, f64) -> (f64, bool);
+
+/* These tune how a scroll function runs for one text window. The speed scales the seed passed to the scroll
+function (so 0.5 scrolls half as fast), and the pause fraction is passed through to it (scroll functions
+that do not pause can ignore it). The defaults keep the scroll function's own speed, with no pause. */
+#[derive(Copy, Clone)]
+pub struct TextScrollParams {
+	pub speed: f64,
+	pub pause_fraction: f64
+}
+
+impl TextScrollParams {
+	pub const DEFAULT: Self = Self {speed: 1.0, pause_fraction: 0.0};
+}
+
+/* This goes from 0 to 1 over the scrolling part of each cycle, and then stays at 0 for the rest of it.
+It is a building block for scroll functions that repeat, and that should pause at the start of the text. */
+pub fn scroll_with_pause(seed: f64, cycle_secs: f64, pause_fraction: f64) -> f64 {
+	let scroll_secs = cycle_secs * (1.0 - pause_fraction.clamp(0.0, 1.0));
+	let time_in_cycle = seed % cycle_secs;
+
+	if time_in_cycle < scroll_secs {time_in_cycle / scroll_secs} else {0.0}
+}
+
+/* These are how a texture is transformed when it is drawn. The rotation is in degrees, clockwise, and the flip is
+horizontal and vertical mirroring. The opacity is multiplied with the texture's own alpha mod for that draw only.
+The corner radius is in pixels (zero gives square corners). */
+#[derive(Copy, Clone)]
+pub struct TextureDrawParams {
+	pub maybe_rotation_degrees: Option<f64>,
+	pub flip: (bool, bool),
+	pub opacity: f64,
+	pub corner_radius: u32
+}
+
+impl TextureDrawParams {
+	pub const DEFAULT: Self = Self {maybe_rotation_degrees: None, flip: (false, false), opacity: 1.0, corner_radius: 0};
+}
+
+/* These inset text from the edges of its window, so that it does not touch the window's border. The margin on each side
+is the fraction of the window's size on that axis, plus the pixels. The text is made to fit the inset area (so its height
+and scrolling width both come from it), and it is drawn centered in the full window area. */
+#[derive(Copy, Clone)]
+pub struct TextMargins {
+	pub fraction: (f64, f64),
+	pub pixels: (u32, u32)
+}
+
+impl TextMargins {
+	pub const NONE: Self = Self {fraction: (0.0, 0.0), pixels: (0, 0)};
+
+	// This is the margin on each side, in pixels (at least one pixel is always left between the margins)
+	fn get_pixel_margins(self, (width, height): (u32, u32)) -> (u32, u32) {
+		let get_margin = |size: u32, fraction: f64, pixels: u32|
+			((size as f64 * fraction.max(0.0)) as u32 + pixels).min(size.saturating_sub(1) / 2);
+
+		(get_margin(width, self.fraction.0, self.pixels.0), get_margin(height, self.fraction.1, self.pixels.1))
+	}
+
+	fn inset_size(self, size: (u32, u32)) -> (u32, u32) {
+		let (margin_x, margin_y) = self.get_pixel_margins(size);
+		(size.0 - margin_x * 2, size.1 - margin_y * 2)
+	}
+
+	fn inset_rect(self, rect: Rect) -> Rect {
+		let (margin_x, margin_y) = self.get_pixel_margins((rect.width(), rect.height()));
+
+		Rect::new(
+			rect.x() + margin_x as i32, rect.y() + margin_y as i32,
+			rect.width() - margin_x * 2, rect.height() - margin_y * 2
+		)
+	}
+}
 
 // TODO: make a constructor for this, instead of making everything `pub`.
 #[derive(Clone)]
 pub struct TextDisplayInfo<'a> {
 	pub text: DisplayText<'a>,
-	pub color: ColorSDL, // TODO: change the name of this to `text_color`, perhaps
+	pub color: TextFill, // TODO: change the name of this to `text_fill`, perhaps
+
+	/* If this is set (and the font has an outline width), the outline is drawn
+	in this color, beneath the fill. Otherwise, the outline is drawn in the fill color. */
+	pub maybe_outline_color: Option<ColorSDL>,
+
 	pub pixel_area: (u32, u32),
 
 	/* Maps the unix time in secs to a scroll fraction
 	(0 to 1), and if the scrolling should wrap. */
-	pub scroll_fn: TextTextureScrollFn
+	pub scroll_fn: TextTextureScrollFn,
+	pub scroll_params: TextScrollParams,
+
+	pub margins: TextMargins
 }
 
 #[derive(Clone)]
@@ -146,8 +371,8 @@ type TextureCreator = render::TextureCreator<sdl2::video::WindowContext>;
 
 type FontPointSize = u16;
 
-// Font path for default, font path for fallback, point size for default, point size for fallback
-type FontCacheKey = (&'static str, &'static str, FontPointSize, FontPointSize);
+// Font source for default, font source for fallback, point size for default, point size for fallback
+type FontCacheKey = (FontSourceCacheKey, FontSourceCacheKey, FontPointSize, FontPointSize);
 type FontPair<'a> = (ttf::Font<'a, 'a>, ttf::Font<'a, 'a>);
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -158,7 +383,42 @@ pub struct TextureHandle {
 pub struct SideScrollingTextMetadata {
 	size: (u32, u32),
 	scroll_fn: TextTextureScrollFn,
-	text: String
+	scroll_params: TextScrollParams,
+	margins: TextMargins,
+	text: String,
+
+	// This is the scroll offset and wrapping from the last check for scrolling (see `text_scroll_moved`)
+	maybe_last_scroll: Option<(u32, bool)>
+}
+
+impl SideScrollingTextMetadata {
+	/* This returns the pixel offset into the texture that is drawn from, and if the text should wrap,
+	for text drawn in an area of the given width (which should already be inset by the margins). */
+	fn get_scroll(&self, dest_width: u32) -> GenericResult<(u32, bool)> {
+		// TODO: compute the time since the unix epoch outside this fn, somehow (or, use the SDL timer)
+		let time_since_unix_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+
+		/* The seed is still about 10^9 seconds after scaling by any reasonable speed,
+		and an `f64` keeps sub-millisecond precision at that size, so the modulos
+		in the scroll functions stay smooth. A speed that is not positive and
+		finite would make the scrolling jump around, so that falls back to 1. */
+		let scroll_params = &self.scroll_params;
+		let speed = if scroll_params.speed.is_finite() && scroll_params.speed > 0.0 {scroll_params.speed} else {1.0};
+
+		let time_seed = (time_since_unix_epoch.as_millis() as f64 / 1000.0) * (dest_width as f64 / self.size.0 as f64) * speed;
+
+		let mut x = self.size.0;
+
+		let (scroll_fract, should_wrap) = (self.scroll_fn)(
+			time_seed, x <= dest_width, scroll_params.pause_fraction
+		);
+
+		assert_in_unit_interval(scroll_fract as f32);
+
+		if !should_wrap {x -= dest_width;}
+
+		Ok(((x as f64 * scroll_fract) as u32, should_wrap))
+	}
 }
 
 /* TODO:
@@ -171,11 +431,75 @@ Otherwise, try to find some way to verify that it's a singleton.
 the `unsafe_textures` feature help this?
 */
 
+/* This decides what happens when a window's texture changes again while
+it is still transitioning to a previous change (see `Window::update_texture_with_fade_through`). */
+#[derive(serde::Deserialize, Copy, Clone, Debug, Default)]
+pub enum TextureTransitionOverlapPolicy {
+	// The newest texture replaces the one being transitioned to, and the transition keeps going
+	#[default]
+	RetargetToLatest,
+
+	// The transition is dropped, and the newest texture is shown right away (so that the display never lags behind)
+	SnapToLatest
+}
+
+/* These are the blend mode and alpha mod set through `set_blend_mode_for` and `set_alpha_mod_for`. They are kept apart
+from the texture, so that they can be reapplied when it is remade (otherwise, a remade texture would be back to an alpha mod
+of 255), and so that they can be carried over when another texture takes its place (see `copy_texture_mods`). */
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+struct TextureMods {
+	maybe_blend_mode: Option<render::BlendMode>,
+	maybe_alpha_mod: Option<u8>
+}
+
+impl TextureMods {
+	// The mods that were set for the other texture replace these ones (and the ones that were not set are left as they are)
+	fn take_set_mods_from(&mut self, other: Self) {
+		self.maybe_blend_mode = other.maybe_blend_mode.or(self.maybe_blend_mode);
+		self.maybe_alpha_mod = other.maybe_alpha_mod.or(self.maybe_alpha_mod);
+	}
+
+	fn apply_to(self, texture: &mut Texture) {
+		if let Some(blend_mode) = self.maybe_blend_mode {
+			texture.set_blend_mode(blend_mode);
+		}
+
+		if let Some(alpha_mod) = self.maybe_alpha_mod {
+			texture.set_alpha_mod(alpha_mod);
+		}
+	}
+}
+
+/* The aspect ratio is computed once, when the texture is made or remade,
+so that aspect ratio correction does not query the texture every frame */
+struct TextureEntry<'a> {
+	texture: Texture<'a>,
+	aspect_ratio: f32,
+	mods: TextureMods
+}
+
+impl<'a> TextureEntry<'a> {
+	fn new(mut texture: Texture<'a>, mods: TextureMods) -> Self {
+		mods.apply_to(&mut texture);
+		let query = texture.query();
+		Self {aspect_ratio: query.width as f32 / query.height as f32, texture, mods}
+	}
+}
+
 pub struct TexturePool<'a> {
 	max_texture_size: (u32, u32),
-	textures: Vec<Texture<'a>>,
+	textures: Vec<TextureEntry<'a>>,
 	texture_creator: &'a TextureCreator,
 
+	// This is a 1x1 white texture, which is color-modded to draw rotated colors (since `fill_rect` can't rotate)
+	solid_color_texture: Texture<'a>,
+
+	// This is used whenever a texture can't be loaded, and it is set by the dashboard
+	maybe_fallback_texture_creation_info: Option<&'static TextureCreationInfo<'static>>,
+
+	// This is set from the app config
+	transition_overlap_policy: TextureTransitionOverlapPolicy,
+
 	//////////
 
 	ttf_context: &'a ttf::Sdl2TtfContext,
@@ -184,7 +508,19 @@ pub struct TexturePool<'a> {
 	font_cache: HashMap<FontCacheKey, FontPair<'a>>,
 
 	// This maps texture handles of side-scrolling text textures to metadata about that scrolling text
-	text_metadata: HashMap<TextureHandle, SideScrollingTextMetadata>
+	text_metadata: HashMap<TextureHandle, SideScrollingTextMetadata>,
+
+	/* This maps texture handles to copies of their textures with rounded corners (along with the size and
+	corner radius, in pixels, that each copy was made for). There is one copy per handle, and it is removed
+	when its texture is remade. */
+	rounded_texture_cache: HashMap<TextureHandle, ((u32, u32), u32, Texture<'a>)>,
+
+	/* These are the handles of freed textures, which are reused by the next textures made
+	(a freed texture's slot holds a 1x1 placeholder until then, so that the other handles stay valid) */
+	free_handles: Vec<TextureHandle>,
+
+	// This is set whenever a texture is made or remade, and reset when it is taken
+	textures_changed: bool
 }
 
 //////////
@@ -200,42 +536,94 @@ impl<'a> TexturePool<'a> {
 
 	pub fn new(texture_creator: &'a TextureCreator,
 		ttf_context: &'a ttf::Sdl2TtfContext,
-		max_texture_size: (u32, u32)) -> Self {
+		max_texture_size: (u32, u32)) -> GenericResult<Self> {
+
+		let mut solid_color_texture = texture_creator.create_texture_static(sdl2::pixels::PixelFormatEnum::RGBA32, 1, 1)?;
+		solid_color_texture.update(None, &[255; 4], 4)?;
+		solid_color_texture.set_blend_mode(render::BlendMode::Blend);
 
-		Self {
+		Ok(Self {
 			max_texture_size,
 			textures: Vec::new(),
 			texture_creator,
+			solid_color_texture,
+			maybe_fallback_texture_creation_info: None,
+			transition_overlap_policy: TextureTransitionOverlapPolicy::default(),
 
 			ttf_context,
 			text_metadata: HashMap::new(),
-			font_cache: HashMap::new()
-		}
+			rounded_texture_cache: HashMap::new(),
+			font_cache: HashMap::new(),
+			free_handles: Vec::new(),
+			textures_changed: false
+		})
+	}
+
+	pub fn set_fallback_texture_creation_info(&mut self, fallback_texture_creation_info: &'static TextureCreationInfo<'static>) {
+		self.maybe_fallback_texture_creation_info = Some(fallback_texture_creation_info);
+	}
+
+	pub fn set_transition_overlap_policy(&mut self, transition_overlap_policy: TextureTransitionOverlapPolicy) {
+		self.transition_overlap_policy = transition_overlap_policy;
+	}
+
+	pub const fn get_transition_overlap_policy(&self) -> TextureTransitionOverlapPolicy {
+		self.transition_overlap_policy
+	}
+
+	pub fn get_fallback_texture_creation_info(&self) -> GenericResult<&'static TextureCreationInfo<'static>> {
+		self.maybe_fallback_texture_creation_info.context("No fallback texture creation info was set for the texture pool")
+	}
+
+	/* This removes all textures (but keeps the font cache). It is for when
+	every window using this pool is remade, since all of the old handles become invalid. */
+	pub fn clear_textures(&mut self) {
+		self.textures.clear();
+		self.text_metadata.clear();
+		self.rounded_texture_cache.clear();
+		self.free_handles.clear();
+		self.maybe_fallback_texture_creation_info = None;
+		self.textures_changed = true;
+	}
+
+	// This returns if any texture was made or remade since the last call to this
+	pub fn take_textures_changed_flag(&mut self) -> bool {
+		std::mem::take(&mut self.textures_changed)
 	}
 
 	pub fn is_text_texture(&self, handle: &TextureHandle) -> bool {
 		self.text_metadata.contains_key(handle)
 	}
 
-	// TODO: cache this
+	/* This returns if a text texture drawn to the given area scrolled to a different pixel offset since the last call
+	to this for it (and so if it needs to be redrawn). Text that fits, or that is pausing between scrolls, does not move.
+	This is false for textures that are not text textures. */
+	pub fn text_scroll_moved(&mut self, handle: &TextureHandle, screen_dest: Rect) -> GenericResult<bool> {
+		let Some(text_metadata) = self.text_metadata.get_mut(handle) else {
+			return Ok(false);
+		};
+
+		let scroll = text_metadata.get_scroll(text_metadata.margins.inset_rect(screen_dest).width())?;
+		let moved = text_metadata.maybe_last_scroll != Some(scroll);
+		text_metadata.maybe_last_scroll = Some(scroll);
+
+		Ok(moved)
+	}
+
 	pub fn get_aspect_ratio_for(&self, handle: &TextureHandle) -> f32 {
-		let texture = self.get_texture_from_handle(handle);
-		let query = texture.query();
-		query.width as f32 / query.height as f32
+		self.textures[handle.handle as usize].aspect_ratio
 	}
 
-	/*
 	pub fn size(&self) -> usize {
 		self.textures.len()
 	}
-	*/
 
 	/* This returns the left/righthand screen dest, and a possible other texture
-	src and screen dest that may wrap around to the left side of the screen */
+	src and screen dest that may wrap around to the left side of the screen.
+	If the texture is not wider than its screen dest, this returns `None`. */
 	fn split_overflowing_scrolled_rect(
 		texture_src: Rect, screen_dest: Rect,
-		texture_size: (u32, u32),
-		text: &str) -> (Rect, Option<(Rect, Rect)>) {
+		texture_size: (u32, u32)) -> Option<(Rect, Option<(Rect, Rect)>)> {
 
 		/* Input data notes:
 		- `texture_src.width == screen_dest.width`
@@ -245,22 +633,20 @@ impl<'a> TexturePool<'a> {
 
 		//////////
 
-		/* TODO: why does this bug still happen on MacOS with the multi-monitor setup?
-		Perhaps from monitor shutoff -> app moves to being displayed on the laptop screen -> resolution change?
-		Test this overnight with no automatic standby, and with automatic standby, to track the time at which this happened. */
+		/* This happens on MacOS with the multi-monitor setup, after a resolution change (e.g. from a
+		monitor shutting off, and the app moving to being displayed on the laptop screen). The text texture
+		was made for the old screen dest size, so it can be narrower than the new one until it is remade. */
 		let how_much_wider_the_texture_is_than_its_screen_dest =
 			texture_size.0 as i32 - screen_dest.width() as i32;
 
 		if how_much_wider_the_texture_is_than_its_screen_dest < 0 {
-			panic!("The texture was not wider than its screen dest, which will yield incorrect results.\n\
-				Difference = {how_much_wider_the_texture_is_than_its_screen_dest}. Texture src = {:?}, \
-				screen dest = {:?}. The text was '{text}'.", texture_src, screen_dest);
+			return None;
 		}
 
 		/* If the texture can be cropped so that it ends up fully
 		on the left side, without spilling onto the right */
 		if texture_src.x() <= how_much_wider_the_texture_is_than_its_screen_dest {
-			return (screen_dest, None);
+			return Some((screen_dest, None));
 		}
 
 		//////////
@@ -281,7 +667,7 @@ impl<'a> TexturePool<'a> {
 			0, 0, texture_right_side_spill_amount, texture_size.1
 		);
 
-		(righthand_dest_rect, Some((lefthand_texture_clip_rect, lefthand_screen_dest)))
+		Some((righthand_dest_rect, Some((lefthand_texture_clip_rect, lefthand_screen_dest))))
 	}
 
 	/* TODO:
@@ -290,14 +676,58 @@ impl<'a> TexturePool<'a> {
 	- Would it be possible to manipulate the canvas scale to be able to only pass normalized coordinates to the renderer?
 	- Use `copy_ex` eventually, and the special canvas functions for things like rounded rectangles
 	*/
-	pub fn draw_texture_to_canvas(&self, handle: &TextureHandle,
-		canvas: &mut CanvasSDL, screen_dest: Rect) -> MaybeError {
+	/* If a rotation is given, the texture is rotated about the center of `screen_dest`.
+	Side-scrolling text may be drawn in two pieces, so each piece is rotated about that same shared center.
+	The flip is ignored for text textures (since mirrored text is unreadable). If render targets are not supported
+	(or this is a text texture, which may scroll), the texture is drawn with square corners, whatever its corner radius. */
+	pub fn draw_texture_to_canvas(&mut self, handle: &TextureHandle,
+		canvas: &mut CanvasSDL, screen_dest: Rect, params: TextureDrawParams) -> MaybeError {
+
+		let TextureDrawParams {maybe_rotation_degrees, flip, opacity, corner_radius} = params;
+
+		if corner_radius != 0 && !self.is_text_texture(handle) && canvas.render_target_supported() {
+			return self.draw_texture_with_rounded_corners_to_canvas(handle, canvas, screen_dest, params);
+		}
+
+		if opacity == 1.0 {
+			return self.draw_texture_to_canvas_with_own_alpha(handle, canvas, screen_dest, maybe_rotation_degrees, flip);
+		}
+
+		let texture = self.get_texture_from_handle_mut(handle);
+		let own_alpha_mod = texture.alpha_mod();
+		texture.set_alpha_mod((own_alpha_mod as f64 * opacity) as u8);
+
+		let result = self.draw_texture_to_canvas_with_own_alpha(handle, canvas, screen_dest, maybe_rotation_degrees, flip);
+		self.get_texture_from_handle_mut(handle).set_alpha_mod(own_alpha_mod);
+		result
+	}
+
+	fn draw_texture_to_canvas_with_own_alpha(&self, handle: &TextureHandle,
+		canvas: &mut CanvasSDL, screen_dest: Rect, maybe_rotation_degrees: Option<f64>,
+		flip: (bool, bool)) -> MaybeError {
 
 		let texture = self.get_texture_from_handle(handle);
 		let possible_text_metadata = self.text_metadata.get(handle);
+		let rotation_center = screen_dest.center();
+
+		let (flip_horizontal, flip_vertical) = if possible_text_metadata.is_some() {(false, false)} else {flip};
+		let needs_copy_ex = maybe_rotation_degrees.is_some() || flip_horizontal || flip_vertical;
+
+		let copy = |canvas: &mut CanvasSDL, texture_src: Option<Rect>, dest: Rect| {
+			if needs_copy_ex {
+				canvas.copy_ex(
+					texture, texture_src, dest, maybe_rotation_degrees.unwrap_or(0.0),
+					Point::new(rotation_center.x() - dest.x(), rotation_center.y() - dest.y()),
+					flip_horizontal, flip_vertical
+				)
+			}
+			else {
+				canvas.copy(texture, texture_src, dest)
+			}.to_generic()
+		};
 
 		if possible_text_metadata.is_none() {
-			return canvas.copy(texture, None, screen_dest).to_generic();
+			return copy(canvas, None, screen_dest);
 		}
 
 		//////////
@@ -305,50 +735,132 @@ impl<'a> TexturePool<'a> {
 		let text_metadata = possible_text_metadata.context("Expected text metadata")?;
 		let texture_size = text_metadata.size;
 
-		// TODO: compute the time since the unix epoch outside this fn, somehow (or, use the SDL timer)
-
+		// The text texture was made for the inset area, so it is scrolled and drawn within that
+		let screen_dest = text_metadata.margins.inset_rect(screen_dest);
 		let dest_width = screen_dest.width();
-		let time_since_unix_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
-		let time_seed = (time_since_unix_epoch.as_millis() as f64 / 1000.0) * (dest_width as f64 / texture_size.0 as f64);
-
-		let mut x = texture_size.0;
 
-		let (scroll_fract, should_wrap) = (text_metadata.scroll_fn)(
-			time_seed, x <= dest_width
-		);
+		let (scroll_offset, should_wrap) = text_metadata.get_scroll(dest_width)?;
+		let texture_src = Rect::new(scroll_offset as i32, 0, dest_width, texture_size.1);
 
-		assert_in_unit_interval(scroll_fract as f32);
+		if !should_wrap {
+			return copy(canvas, Some(texture_src), screen_dest);
+		}
 
 		//////////
 
-		if !should_wrap {x -= dest_width;}
-
-		//////////
+		let Some((right_screen_dest, possible_left_rects)) = Self::split_overflowing_scrolled_rect(
+			texture_src, screen_dest, texture_size
+		) else {
+			// This can happen on every frame until the texture is remade, so it is only logged every so often
+			const WARNING_INTERVAL: Duration = Duration::from_secs(10);
+			static MAYBE_LAST_WARNING_TIME: Mutex<Option<Instant>> = Mutex::new(None);
 
-		let texture_src = Rect::new(
-			(x as f64 * scroll_fract) as i32,
-			0, dest_width, texture_size.1
-		);
+			let mut maybe_last_warning_time = MAYBE_LAST_WARNING_TIME.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
 
-		if !should_wrap {
-			return canvas.copy(texture, texture_src, screen_dest).to_generic();
-		}
+			if maybe_last_warning_time.is_none_or(|last_warning_time| last_warning_time.elapsed() >= WARNING_INTERVAL) {
+				*maybe_last_warning_time = Some(Instant::now());
 
-		//////////
+				log::warn!("The texture was not wider than its screen dest, so it will be drawn without scrolling. \
+					Texture size = {texture_size:?}, screen dest = {screen_dest:?}. The text was '{}'.", text_metadata.text);
+			}
 
-		let (right_screen_dest, possible_left_rects) = Self::split_overflowing_scrolled_rect(
-			texture_src, screen_dest, texture_size, &text_metadata.text
-		);
+			// Falling back to stretching the whole texture to the screen dest
+			return copy(canvas, None, screen_dest);
+		};
 
-		canvas.copy(texture, texture_src, right_screen_dest).to_generic()?;
+		copy(canvas, Some(texture_src), right_screen_dest)?;
 
 		if let Some((left_texture_src, left_screen_dest)) = possible_left_rects {
-			canvas.copy(texture, left_texture_src, left_screen_dest).to_generic()?;
+			copy(canvas, Some(left_texture_src), left_screen_dest)?;
 		}
 
 		Ok(())
 	}
 
+	/* The rounded copy is made by rendering the texture into a render target, and then clearing the pixels outside of
+	the rounded corners. It is cached until the texture is remade, or drawn at a different size. */
+	fn draw_texture_with_rounded_corners_to_canvas(&mut self, handle: &TextureHandle,
+		canvas: &mut CanvasSDL, screen_dest: Rect, params: TextureDrawParams) -> MaybeError {
+
+		let TextureDrawParams {maybe_rotation_degrees, flip, opacity, corner_radius} = params;
+
+		let size = (screen_dest.width(), screen_dest.height());
+		let corner_radius = corner_radius.min(size.0.min(size.1) / 2);
+
+		let cache_is_valid = matches!(self.rounded_texture_cache.get(handle),
+			Some((cached_size, cached_corner_radius, _)) if *cached_size == size && *cached_corner_radius == corner_radius);
+
+		if !cache_is_valid {
+			let rounded_texture = self.make_rounded_texture(handle, canvas, size, corner_radius)?;
+			self.rounded_texture_cache.insert(handle.clone(), (size, corner_radius, rounded_texture));
+		}
+
+		let (_, _, rounded_texture) = self.rounded_texture_cache.get_mut(handle).context("Expected a rounded texture")?;
+
+		// The source texture's own alpha mod was already applied when the rounded copy was made
+		rounded_texture.set_alpha_mod((255.0 * opacity) as u8);
+
+		// The corners are symmetric, so the flip can be done here, rather than when making the rounded copy
+		canvas.copy_ex(rounded_texture, None, screen_dest, maybe_rotation_degrees.unwrap_or(0.0), None, flip.0, flip.1).to_generic()
+	}
+
+	fn make_rounded_texture(&self, handle: &TextureHandle, canvas: &mut CanvasSDL,
+		(width, height): (u32, u32), corner_radius: u32) -> GenericResult<Texture<'a>> {
+
+		let mut rounded_texture = self.texture_creator.create_texture_target(
+			sdl2::pixels::PixelFormatEnum::RGBA8888, width, height
+		)?;
+
+		rounded_texture.set_blend_mode(render::BlendMode::Blend);
+
+		let source_texture = self.get_texture_from_handle(handle);
+		let mut result: MaybeError = Ok(());
+
+		canvas.with_texture_canvas(&mut rounded_texture, |texture_canvas| {
+			result = (|| -> MaybeError {
+				// Without blending, drawing with a transparent color clears pixels (rather than doing nothing)
+				let prev_blend_mode = texture_canvas.blend_mode();
+				texture_canvas.set_blend_mode(render::BlendMode::None);
+				texture_canvas.set_draw_color(ColorSDL::RGBA(0, 0, 0, 0));
+
+				texture_canvas.clear();
+				texture_canvas.copy(source_texture, None, None).to_generic()?;
+
+				// For each row within the corner radius, this clears the pixels outside of the quarter circles
+				let radius = corner_radius as f32;
+
+				for row in 0..corner_radius {
+					let dy = radius - row as f32 - 0.5;
+					let cut_width = (radius - (radius * radius - dy * dy).max(0.0).sqrt()).round() as u32;
+
+					if cut_width == 0 {
+						continue;
+					}
+
+					for y in [row, height - 1 - row] {
+						for x in [0, width - cut_width] {
+							texture_canvas.fill_rect(Rect::new(x as i32, y as i32, cut_width, 1)).to_generic()?;
+						}
+					}
+				}
+
+				texture_canvas.set_blend_mode(prev_blend_mode);
+				Ok(())
+			})();
+		}).to_generic()?;
+
+		result.map(|_| rounded_texture)
+	}
+
+	// This draws a color rotated about the center of `screen_dest` (in degrees, clockwise)
+	pub fn draw_rotated_color_to_canvas(&mut self, color: ColorSDL,
+		canvas: &mut CanvasSDL, screen_dest: Rect, rotation_degrees: f64) -> MaybeError {
+
+		self.solid_color_texture.set_color_mod(color.r, color.g, color.b);
+		self.solid_color_texture.set_alpha_mod(color.a);
+		canvas.copy_ex(&self.solid_color_texture, None, screen_dest, rotation_degrees, None, false, false).to_generic()
+	}
+
 	fn possibly_update_text_metadata(&mut self, new_texture: &Texture,
 		handle: &TextureHandle, creation_info: &TextureCreationInfo) {
 
@@ -360,7 +872,10 @@ impl<'a> TexturePool<'a> {
 				let metadata = SideScrollingTextMetadata {
 					size: (query.width, query.height),
 					scroll_fn: text_display_info.scroll_fn,
-					text: text_display_info.text.text.to_string() // TODO: maybe copy it with a reference count instead?
+					scroll_params: text_display_info.scroll_params,
+					margins: text_display_info.margins,
+					text: text_display_info.text.text.to_string(), // TODO: maybe copy it with a reference count instead?
+					maybe_last_scroll: None
 				};
 
 				self.text_metadata.insert(handle.clone(), metadata);
@@ -380,11 +895,23 @@ impl<'a> TexturePool<'a> {
 	//////////
 
 	pub fn make_texture(&mut self, creation_info: &TextureCreationInfo) -> GenericResult<TextureHandle> {
+		if let Some(handle) = self.free_handles.pop() {
+			return match self.remake_texture(creation_info, &handle) {
+				Ok(()) => Ok(handle),
+
+				Err(err) => {
+					self.free_handles.push(handle);
+					Err(err)
+				}
+			};
+		}
+
 		let handle = TextureHandle {handle: self.textures.len() as InnerTextureHandle};
 		let texture = self.make_raw_texture(creation_info)?;
 
 		self.possibly_update_text_metadata(&texture, &handle, creation_info);
-		self.textures.push(texture);
+		self.textures.push(TextureEntry::new(texture, TextureMods::default()));
+		self.textures_changed = true;
 
 		Ok(handle)
 	}
@@ -394,12 +921,27 @@ impl<'a> TexturePool<'a> {
 		let new_texture = self.make_raw_texture(creation_info)?;
 
 		self.possibly_update_text_metadata(&new_texture, handle, creation_info);
-		*self.get_texture_from_handle_mut(handle) = new_texture;
+		// This replaces the whole entry, so that the cached aspect ratio is refreshed too (the blend mode and alpha mod are kept though)
+		let entry = &mut self.textures[handle.handle as usize];
+		*entry = TextureEntry::new(new_texture, entry.mods);
+		self.rounded_texture_cache.remove(handle);
+		self.textures_changed = true;
 
 		Ok(())
 	}
 
-	// TODO: allow for texture deletion too
+	/* This destroys a texture that is no longer needed, and its handle is reused by the next texture made.
+	The handle (and any copies of it) must not be used after this, until it is given out again. */
+	pub fn free_texture(&mut self, handle: TextureHandle) -> MaybeError {
+		let placeholder_texture = self.texture_creator.create_texture_static(sdl2::pixels::PixelFormatEnum::RGBA32, 1, 1)?;
+
+		self.textures[handle.handle as usize] = TextureEntry::new(placeholder_texture, TextureMods::default());
+		self.text_metadata.remove(&handle);
+		self.rounded_texture_cache.remove(&handle);
+		self.free_handles.push(handle);
+
+		Ok(())
+	}
 
 	////////// TODO: use these
 
@@ -408,63 +950,192 @@ impl<'a> TexturePool<'a> {
 		let texture = self.get_texture_from_handle_mut(handle);
 		texture.set_color_mod(r, g, b);
 	}
+	*/
 
+	/* The alpha mod stays set when the texture is remade, and it is multiplied with the opacity when drawing,
+	so it fades a window's texture independently of any fade-through transition (see `copy_texture_mods` too). */
 	pub fn set_alpha_mod_for(&mut self, handle: &TextureHandle, a: u8) {
-		let texture = self.get_texture_from_handle_mut(handle);
-		texture.set_alpha_mod(a);
+		let entry = &mut self.textures[handle.handle as usize];
+		entry.texture.set_alpha_mod(a);
+		entry.mods.maybe_alpha_mod = Some(a);
+
+		// The rounded copy has the old alpha mod baked into it
+		self.rounded_texture_cache.remove(handle);
+		self.textures_changed = true;
 	}
-	*/
 
+	// The blend mode stays set when the texture is remade
 	pub fn set_blend_mode_for(&mut self, handle: &TextureHandle, blend_mode: render::BlendMode) {
-		let texture = self.get_texture_from_handle_mut(handle);
-		texture.set_blend_mode(blend_mode);
+		let entry = &mut self.textures[handle.handle as usize];
+		entry.texture.set_blend_mode(blend_mode);
+		entry.mods.maybe_blend_mode = Some(blend_mode);
+		self.textures_changed = true;
+	}
+
+	/* This gives one texture the blend mode and alpha mod that were set for another (if they were set). It is for when
+	a texture takes the place of another one (e.g. at the midpoint of a fade-through transition), so that the intended
+	alpha mod is kept after the transition, rather than being whatever the incoming texture was made with. */
+	pub fn copy_texture_mods(&mut self, from: &TextureHandle, to: &TextureHandle) {
+		let mut to_mods = self.textures[to.handle as usize].mods;
+		to_mods.take_set_mods_from(self.textures[from.handle as usize].mods);
+
+		if let Some(blend_mode) = to_mods.maybe_blend_mode {
+			self.set_blend_mode_for(to, blend_mode);
+		}
+
+		if let Some(alpha_mod) = to_mods.maybe_alpha_mod {
+			self.set_alpha_mod_for(to, alpha_mod);
+		}
 	}
 
 	////////// TODO: eliminate the repetition here (perhaps inline, or make to a macro - or is there some other way?)
 
 	fn get_texture_from_handle_mut(&mut self, handle: &TextureHandle) -> &mut Texture<'a> {
-		&mut self.textures[handle.handle as usize]
+		&mut self.textures[handle.handle as usize].texture
 	}
 
 	fn get_texture_from_handle(&self, handle: &TextureHandle) -> &Texture {
-		&self.textures[handle.handle as usize]
+		&self.textures[handle.handle as usize].texture
 	}
 
 	//////////
 
-	fn get_font_pair(&mut self, key: FontCacheKey, maybe_options: Option<&FontInfo>) -> &FontPair {
-		let fonts = self.font_cache.entry(key).or_insert_with(
-			|| {
-				// TODO: don't unwrap
-				let make_font = |path, point_size| self.ttf_context.load_font(path, point_size).unwrap();
-				let (default_path, fallback_path, default_point_size, fallback_point_size) = key;
-				(make_font(default_path, default_point_size), make_font(fallback_path, fallback_point_size))
-			}
+	// The font info's options (style, hinting, and outline width) are only applied if `set_options` is true
+	fn get_font_pair(&mut self, font_info: &FontInfo,
+		(default_point_size, fallback_point_size): (FontPointSize, FontPointSize),
+		set_options: bool) -> GenericResult<&mut FontPair<'a>> {
+
+		let key = (
+			font_info.source.get_cache_key(), font_info.unusual_chars_fallback_source.get_cache_key(),
+			default_point_size, fallback_point_size
 		);
 
-		if let Some(options) = maybe_options {
-			let set_options = |font: &mut ttf::Font| {
-				font.set_style(options.style);
-				font.set_hinting(options.hinting.clone());
+		// The coverage is the same for every point size, so it is only validated for the first one loaded
+		let should_validate_coverage = font_info.validate_font_coverage && !self.font_cache.contains_key(&key)
+			&& !self.font_cache.keys().any(|other_key| other_key.0 == key.0 && other_key.1 == key.1);
+
+		let fonts = match self.font_cache.entry(key) {
+			Entry::Occupied(entry) => entry.into_mut(),
+
+			Entry::Vacant(entry) => {
+				let make_font = |source: &FontSource, point_size| match source {
+					FontSource::Path(path) => self.ttf_context.load_font(path as &str, point_size),
+
+					FontSource::Bytes(bytes) => RWops::from_bytes(bytes).and_then(
+						|rwops| self.ttf_context.load_font_from_rwops(rwops, point_size)
+					)
+				}.to_generic().context(format!("Could not load the font from {source}"));
+
+				let fonts = (make_font(&font_info.source, default_point_size)?, make_font(&font_info.unusual_chars_fallback_source, fallback_point_size)?);
+
+				if should_validate_coverage {
+					Self::log_font_coverage(font_info, &fonts);
+				}
+
+				entry.insert(fonts)
+			}
+		};
+
+		if set_options {
+			let apply_options = |font: &mut ttf::Font| {
+				font.set_style(font_info.style);
+				font.set_hinting(font_info.hinting.clone());
 
-				if let Some(outline_width) = options.maybe_outline_width {
+				if let Some(outline_width) = font_info.maybe_outline_width {
 					font.set_outline_width(outline_width);
 				}
 			};
 
-			set_options(&mut fonts.0);
-			set_options(&mut fonts.1);
+			apply_options(&mut fonts.0);
+			apply_options(&mut fonts.1);
 		}
 
-		fonts
+		Ok(fonts)
+	}
+
+	/* This checks an evenly spaced sample of codepoints from each block, rather than every codepoint,
+	so that it stays cheap. Note that a few sampled codepoints may be unassigned in Unicode,
+	so a small number of missing glyphs in a block is not always a problem.
+
+	TODO: check blocks in the supplementary multilingual plane too (e.g. emojis), once `find_glyph`
+	handles codepoints above 0xFFFF (in this version of the sdl2 bindings, it truncates them to 16 bits). */
+	fn log_font_coverage(font_info: &FontInfo, (default_font, fallback_font): &FontPair) {
+		const NUM_SAMPLES_PER_BLOCK: u32 = 8;
+
+		const UNICODE_BLOCKS: [(&str, u32, u32); 24] = [
+			("Basic Latin", 0x0020, 0x007E),
+			("Latin-1 Supplement", 0x00A0, 0x00FF),
+			("Latin Extended-A", 0x0100, 0x017F),
+			("Greek and Coptic", 0x0391, 0x03C9),
+			("Cyrillic", 0x0400, 0x04FF),
+			("Hebrew", 0x05D0, 0x05EA),
+			("Arabic", 0x0621, 0x064A),
+			("Devanagari", 0x0905, 0x0939),
+			("Thai", 0x0E01, 0x0E2E),
+			("General Punctuation", 0x2010, 0x2027),
+			("Currency Symbols", 0x20A0, 0x20BF),
+			("Arrows", 0x2190, 0x21FF),
+			("Mathematical Operators", 0x2200, 0x22FF),
+			("Box Drawing", 0x2500, 0x257F),
+			("Block Elements", 0x2580, 0x259F),
+			("Geometric Shapes", 0x25A0, 0x25FF),
+			("Miscellaneous Symbols", 0x2600, 0x26FF),
+			("Dingbats", 0x2701, 0x27BF),
+			("CJK Symbols and Punctuation", 0x3000, 0x303F),
+			("Hiragana", 0x3041, 0x3096),
+			("Katakana", 0x30A1, 0x30FA),
+			("CJK Unified Ideographs", 0x4E00, 0x9FFF),
+			("Hangul Syllables", 0xAC00, 0xD7A3),
+			("Halfwidth and Fullwidth Forms", 0xFF01, 0xFF5E)
+		];
+
+		//////////
+
+		let get_num_missing = |font: &ttf::Font, first: u32, last: u32| {
+			let step = ((last - first) / (NUM_SAMPLES_PER_BLOCK - 1)).max(1);
+
+			(first..=last).step_by(step as usize).take(NUM_SAMPLES_PER_BLOCK as usize)
+				.filter_map(char::from_u32)
+				.filter(|&c| font.find_glyph(c).is_none())
+				.count()
+		};
+
+		for (font, font_name, source) in [
+			(default_font, "default", &font_info.source),
+			(fallback_font, "fallback", &font_info.unusual_chars_fallback_source)] {
+
+			let missing_blocks: Vec<String> = UNICODE_BLOCKS.iter().filter_map(|&(block_name, first, last)| {
+				let num_missing = get_num_missing(font, first, last);
+				(num_missing != 0).then(|| format!("{block_name} ({num_missing}/{NUM_SAMPLES_PER_BLOCK} missing)"))
+			}).collect();
+
+			if missing_blocks.is_empty() {
+				log::info!("The {font_name} font {source} covers all of the sampled Unicode blocks.");
+			}
+			else {
+				log::info!("The {font_name} font {source} is missing glyphs in these sampled Unicode blocks: {}.", missing_blocks.join(", "));
+			}
+		}
+
+		let missing_from_both: Vec<&str> = UNICODE_BLOCKS.iter().filter(|&&(_, first, last)|
+			get_num_missing(default_font, first, last) != 0 && get_num_missing(fallback_font, first, last) != 0
+		).map(|(block_name, _, _)| *block_name).collect();
+
+		if !missing_from_both.is_empty() {
+			log::warn!("Neither the default nor the fallback font fully covers these sampled Unicode blocks \
+				(so some of their characters may render as boxes): {}.", missing_from_both.join(", "));
+		}
 	}
 
 	fn get_point_and_surface_size_for_initial_font(initial_font: &ttf::Font,
-		text_display_info: &TextDisplayInfo) -> GenericResult<(FontPointSize, (u32, u32))> {
+		text_display_info: &TextDisplayInfo, outline_width: u16) -> GenericResult<(FontPointSize, (u32, u32))> {
 
 		let initial_output_size = initial_font.size_of(&text_display_info.text.text)?;
 
-		let height_ratio_from_expected_size = text_display_info.pixel_area.1 as f64 / initial_output_size.1 as f64;
+		// An outline adds its width to both the top and the bottom of the rendered text
+		let height_without_outline = text_display_info.pixel_area.1.saturating_sub(outline_width as u32 * 2).max(1);
+
+		let height_ratio_from_expected_size = height_without_outline as f64 / initial_output_size.1 as f64;
 		let adjusted_point_size = Self::INITIAL_POINT_SIZE as f64 * height_ratio_from_expected_size;
 
 		// TODO: would it work better if I used `round` or `ceil` for the adjsuted point size instead?
@@ -473,31 +1144,139 @@ impl<'a> TexturePool<'a> {
 
 	//////////
 
+	/* This renders some text with the font's outline in the outline color (if there is one),
+	and then the fill on top of that. The outlined surface is `2 * outline_width` larger than
+	the fill surface on each axis, so the fill is blitted at an offset of `outline_width`.
+	Any fill gradient is only applied to the fill, so that the outline color stays intact.
+	If the glyph colors are kept (for color emojis), no fill gradient is applied at all. */
+	fn render_text_with_maybe_outline(font: &mut ttf::Font, text: &str,
+		text_display_info: &TextDisplayInfo, keep_glyph_colors: bool) -> GenericResult<Surface<'a>> {
+
+		let outline_width = font.get_outline_width();
+		let (mut fill, total_height) = (text_display_info.color, text_display_info.pixel_area.1);
+
+		// Color glyphs keep their own colors when rendered (only the alpha of the fill color is used for them)
+		if keep_glyph_colors {
+			fill = TextFill::Solid(fill.base_color());
+		}
+
+		let Some(outline_color) = text_display_info.maybe_outline_color.filter(|_| outline_width != 0) else {
+			let fill_surface = font.render(text).blended(fill.base_color())?;
+			return fill.maybe_apply_gradient(fill_surface, 0, total_height);
+		};
+
+		let mut outline_surface = font.render(text).blended(outline_color)?;
+
+		font.set_outline_width(0);
+		let fill_surface = font.render(text).blended(fill.base_color());
+		font.set_outline_width(outline_width);
+
+		let mut fill_surface = fill.maybe_apply_gradient(fill_surface?, outline_width as u32, total_height)?;
+		fill_surface.set_blend_mode(render::BlendMode::Blend).to_generic()?;
+
+		let fill_dest = Rect::new(outline_width as i32, outline_width as i32, fill_surface.width(), fill_surface.height());
+		fill_surface.blit(None, &mut outline_surface, fill_dest).to_generic()?;
+
+		Ok(outline_surface)
+	}
+
+	/* This returns how many characters of a span (in a supposedly monospace font) are left after
+	cutting off enough of them to make up for the pixel overstep. If the font turns out to not be monospaced
+	per character (going by the width of the span's first character), or the cut would be too large,
+	this logs that and returns `None`, so that the span can be cut manually instead. */
+	fn get_monospace_cut_span_len(orig_span_len: usize, first_char_pixel_width: u32,
+		span_pixel_width: u32, pixel_overstep: u32) -> Option<usize> {
+
+		// Checking that the monospace property holds
+		if first_char_pixel_width * orig_span_len as u32 != span_pixel_width {
+			log::warn!("A fixed-width font had a span of {orig_span_len} characters with a width of {span_pixel_width} pixels \
+				(while its first character was {first_char_pixel_width} pixels wide); falling back to manual text span cutting");
+
+			return None;
+		}
+
+		let approx_char_overstep = pixel_overstep as f64 / span_pixel_width as f64 * orig_span_len as f64;
+		let char_overstep = approx_char_overstep.ceil() as usize;
+
+		// Checking that the cut text amount is not too large for this span
+		if char_overstep > orig_span_len {
+			log::warn!("A monospace text span cut of {char_overstep} characters was too large for a span of {orig_span_len}; \
+				falling back to manual text span cutting");
+
+			return None;
+		}
+
+		Some(orig_span_len - char_overstep)
+	}
+
+	/* This binary-searches for the longest part of a span (of `span_len` characters) that fits,
+	which takes a logarithmic number of measurements. The full span is assumed to not fit. Like cutting off
+	one character at a time would, it assumes that the width of a span grows with its number of characters. */
+	fn get_longest_fitting_span_len(span_len: usize, mut fits: impl FnMut(usize) -> GenericResult<bool>) -> GenericResult<usize> {
+		let (mut fitting_len, mut overflowing_len) = (0, span_len);
+
+		while overflowing_len - fitting_len > 1 {
+			let middle_len = (fitting_len + overflowing_len) / 2;
+
+			if fits(middle_len)? {
+				fitting_len = middle_len;
+			}
+			else {
+				overflowing_len = middle_len;
+			}
+		}
+
+		Ok(fitting_len)
+	}
+
 	/* Assuming that the passed-in text will not result in a zero-width
 	surface (that is handled in `make_text_surface`). */
 	fn inner_make_text_surface(text_display_info: &TextDisplayInfo,
-		font_pair: &FontPair, font_has_char: fn(&ttf::Font, char) -> bool,
+		font_pair: &mut FontPair, font_has_char: fn(&ttf::Font, char) -> bool,
 		max_texture_width: u32) -> GenericResult<Surface<'a>> {
 
 		let chars: Vec<char> = text_display_info.text.text.chars().collect();
 		let num_chars = chars.len();
 
-		let (default_font, fallback_font) = font_pair;
+		/* Cutting a span can measure the same parts of it more than once, so the measured widths are kept here.
+		This only lasts for one call, and its size is bounded. Spans are keyed on their text alone, since
+		a span's characters decide which font it uses. */
+		const MAX_NUM_MEMOIZED_SPAN_WIDTHS: usize = 64;
+		let mut span_width_memo: HashMap<String, u32> = HashMap::new();
+
+		// With color emoji enabled, emojis are always rendered with the fallback font (which should then be a color emoji font)
+		let color_emoji = color_emoji_enabled();
+		let use_plain_font_for = |font: &ttf::Font, c: char| !(color_emoji && DisplayText::is_emoji_sequence_char(c)) && font_has_char(font, c);
 
 		let (mut i, mut total_surface_width, mut max_surface_height, mut subsurfaces) = (0, 0, 0, Vec::new());
 
 		while i != num_chars {
-			let (use_plain_font, start) = (font_has_char(default_font, chars[i]), i);
+			let (default_font, fallback_font) = (&font_pair.0, &font_pair.1);
+			let (use_plain_font, start) = (use_plain_font_for(default_font, chars[i]), i);
 
-			while i != num_chars && font_has_char(default_font, chars[i]) == use_plain_font {
+			while i != num_chars && use_plain_font_for(default_font, chars[i]) == use_plain_font {
 				i += 1;
 			}
 
 			let chosen_font = if use_plain_font {default_font} else {fallback_font};
 
-			let compute_span_data = |span: &[char]| -> GenericResult<(String, u32, u32)> {
+			let mut compute_span_data = |span: &[char]| -> GenericResult<(String, u32, u32)> {
 				let span_as_string = span.iter().collect::<String>();
-				let subsurface_width = chosen_font.size_of(&span_as_string)?.0;
+
+				let subsurface_width = match span_width_memo.get(&span_as_string) {
+					Some(&width) => width,
+
+					None => {
+						let width = chosen_font.size_of(&span_as_string)?.0;
+
+						if span_width_memo.len() < MAX_NUM_MEMOIZED_SPAN_WIDTHS {
+							span_width_memo.insert(span_as_string.clone(), width);
+						}
+
+						width
+					}
+				};
+
 				let next_total_width = total_surface_width + subsurface_width;
 
 				Ok((span_as_string, subsurface_width, next_total_width))
@@ -525,42 +1304,44 @@ impl<'a> TexturePool<'a> {
 				max texture width, cut off enough characters to make it fit in one texture.
 				I am not running this branch for italicized fonts since italicized fonts are
 				not really monospaced per character. */
-				if chosen_font.face_is_fixed_width() && !chosen_font.get_style().intersects(ttf::FontStyle::ITALIC) {
+				let maybe_monospace_cut = if chosen_font.face_is_fixed_width() && !chosen_font.get_style().intersects(ttf::FontStyle::ITALIC) {
 					log::debug!("Doing optimized monospace text span cutting");
-
-					let orig_span_len = span.len();
 					let first_char_pixel_width = chosen_font.size_of_char(span[0])?.0;
 
-					// Checking that the monospace property holds
-					assert!(first_char_pixel_width * orig_span_len as u32 == subsurface_width);
-
-					let pixel_overstep = next_total_width - max_texture_width;
-					let approx_char_overstep = pixel_overstep as f64 / subsurface_width as f64 * orig_span_len as f64;
-					let char_overstep = approx_char_overstep.ceil() as usize;
-
-					// Checking that the cut text amount is not too large for this span
-					assert!(char_overstep <= orig_span_len);
+					Self::get_monospace_cut_span_len(span.len(), first_char_pixel_width, subsurface_width, next_total_width - max_texture_width)
+						.map(|cut_span_len| (cut_span_len, first_char_pixel_width))
+				}
+				else {
+					None
+				};
 
-					span = &span[0..orig_span_len - char_overstep];
+				if let Some((cut_span_len, char_pixel_width)) = maybe_monospace_cut {
+					let orig_span = span;
+					span = &span[0..cut_span_len];
 					(span_as_string, subsurface_width, next_total_width) = compute_span_data(span)?;
 
 					// Double-checking that the monospace property holds
-					assert!(subsurface_width == first_char_pixel_width * span.len() as u32);
-				}
-				else {
-					log::debug!("Font was not monospaced; doing manual text span cutting");
-
-					while next_total_width > max_texture_width {
-						log::debug!("Doing an iteration of manual inefficient text span cutting");
-						span = &span[0..span.len() - 1];
+					if subsurface_width != char_pixel_width * span.len() as u32 {
+						log::warn!("The monospace property did not hold after cutting a text span; falling back to manual text span cutting");
+						span = orig_span;
 						(span_as_string, subsurface_width, next_total_width) = compute_span_data(span)?;
 					}
 				}
 
+				if next_total_width > max_texture_width {
+					log::debug!("Doing manual text span cutting");
+
+					let fitting_len = Self::get_longest_fitting_span_len(span.len(),
+						|len| Ok(compute_span_data(&span[0..len])?.2 <= max_texture_width))?;
+
+					span = &span[0..fitting_len];
+					(span_as_string, subsurface_width, next_total_width) = compute_span_data(span)?;
+				}
+
 				/////////
 
 				log::debug!("Final cut width = {next_total_width} (checking if it is under or equal to the limit of {max_texture_width})");
-				assert!(next_total_width <= max_texture_width);
+				debug_assert!(next_total_width <= max_texture_width);
 
 				if subsurface_width == 0 {
 					log::debug!("Zero-width subsurface width after text cutting; ignoring it");
@@ -570,7 +1351,9 @@ impl<'a> TexturePool<'a> {
 
 			//////////
 
-			let subsurface = chosen_font.render(&span_as_string).blended(text_display_info.color)?;
+			let keep_glyph_colors = color_emoji && !use_plain_font && span.iter().any(|&c| DisplayText::is_emoji(c));
+			let chosen_font_mut = if use_plain_font {&mut font_pair.0} else {&mut font_pair.1};
+			let subsurface = Self::render_text_with_maybe_outline(chosen_font_mut, &span_as_string, text_display_info, keep_glyph_colors)?;
 			assert!(subsurface_width == subsurface.width());
 
 			total_surface_width += subsurface_width;
@@ -626,21 +1409,23 @@ impl<'a> TexturePool<'a> {
 		let max_texture_width = self.max_texture_size.0;
 
 		let (initial_default_font, initial_fallback_font) = self.get_font_pair(
-			(font_info.path, font_info.unusual_chars_fallback_path, Self::INITIAL_POINT_SIZE, Self::INITIAL_POINT_SIZE), None
-		);
+			font_info, (Self::INITIAL_POINT_SIZE, Self::INITIAL_POINT_SIZE), false
+		)?;
+
+		let outline_width = font_info.maybe_outline_width.unwrap_or(0);
 
 		let ((default_point_size, initial_default_output_size),
 			(fallback_point_size, initial_fallback_output_size)) = (
 
-			Self::get_point_and_surface_size_for_initial_font(initial_default_font, text_display_info)?,
-			Self::get_point_and_surface_size_for_initial_font(initial_fallback_font, text_display_info)?
+			Self::get_point_and_surface_size_for_initial_font(initial_default_font, text_display_info, outline_width)?,
+			Self::get_point_and_surface_size_for_initial_font(initial_fallback_font, text_display_info, outline_width)?
 		);
 
 		////////// Second, making a font pair
 
 		let font_pair = self.get_font_pair(
-			(font_info.path, font_info.unusual_chars_fallback_path, default_point_size, fallback_point_size), Some(font_info)
-		);
+			font_info, (default_point_size, fallback_point_size), true
+		)?;
 
 		////////// Early exit point: if the font turned out to have zero width, then make a blank text surface
 
@@ -650,7 +1435,7 @@ impl<'a> TexturePool<'a> {
 		if initial_default_output_size.0 == 0 || initial_fallback_output_size.0 == 0 {
 			log::debug!("Making a blank-text-default text texture");
 
-			let mut blank_surface = font_pair.0.render(Self::BLANK_TEXT_DEFAULT).blended(text_display_info.color)?;
+			let mut blank_surface = Self::render_text_with_maybe_outline(&mut font_pair.0, Self::BLANK_TEXT_DEFAULT, text_display_info, false)?;
 
 			Ok(if blank_surface.width() < max_width || blank_surface.height() != needed_height {
 				let mut corrected = Surface::new(max_width, needed_height, blank_surface.pixel_format_enum()).to_generic()?;
@@ -669,28 +1454,259 @@ impl<'a> TexturePool<'a> {
 
 	//////////
 
+	/* With linear filtering, the fully transparent pixels next to the opaque areas of an image get sampled too.
+	Those are usually black, which leaves a dark halo around the opaque areas. To avoid that, this gives each fully
+	transparent pixel the average color of its non-transparent neighbors (keeping it fully transparent).
+	Premultiplying the alpha would also fix it, but then alpha modding (for opacity) would not dim the colors.
+	This is skipped for images without any fully transparent pixels (like photos, and most album art), since
+	it would not change them; that leaves it for overlay images like the dashboard foreground. */
+	fn maybe_bleed_colors_into_transparent_pixels(surface: Surface) -> GenericResult<Surface> {
+		const BYTES_PER_PIXEL: usize = 4;
+
+		// Images with neither an alpha channel nor a color key can't have transparent pixels
+		if !surface.pixel_format_enum().supports_alpha() && surface.color_key().is_err() {
+			return Ok(surface);
+		}
+
+		let mut surface = surface.convert_format(PixelFormatEnum::RGBA32).to_generic()?;
+		let (width, height, pitch) = (surface.width() as usize, surface.height() as usize, surface.pitch() as usize);
+
+		// Scanning for a transparent pixel is a lot cheaper than the bleeding itself
+		let has_transparent_pixels = surface.with_lock(|pixels| pixels.chunks_exact(pitch).take(height)
+			.any(|row| row[..width * BYTES_PER_PIXEL].chunks_exact(BYTES_PER_PIXEL).any(|pixel| pixel[3] == 0)));
+
+		if !has_transparent_pixels {
+			return Ok(surface);
+		}
+
+		surface.with_lock_mut(|pixels| {
+			let orig_pixels = pixels.to_vec();
+			let pixel_at = |x: usize, y: usize| &orig_pixels[y * pitch + x * BYTES_PER_PIXEL..][..BYTES_PER_PIXEL];
+
+			for y in 0..height {
+				for x in 0..width {
+					if pixel_at(x, y)[3] != 0 {
+						continue;
+					}
+
+					let (mut color_sum, mut num_neighbors) = ([0_u32; 3], 0);
+
+					for neighbor_y in y.saturating_sub(1)..(y + 2).min(height) {
+						for neighbor_x in x.saturating_sub(1)..(x + 2).min(width) {
+							let neighbor = pixel_at(neighbor_x, neighbor_y);
+
+							if neighbor[3] != 0 {
+								color_sum.iter_mut().zip(neighbor).for_each(|(sum, &channel)| *sum += channel as u32);
+								num_neighbors += 1;
+							}
+						}
+					}
+
+					if num_neighbors != 0 {
+						let dest = &mut pixels[y * pitch + x * BYTES_PER_PIXEL..][..3];
+						dest.iter_mut().zip(color_sum).for_each(|(channel, sum)| *channel = (sum / num_neighbors) as u8);
+					}
+				}
+			}
+		});
+
+		Ok(surface)
+	}
+
 	fn make_raw_texture(&mut self, creation_info: &TextureCreationInfo) -> GenericResult<Texture<'a>> {
-		match creation_info {
+		let load_image_surface_from_bytes = |bytes: &[u8]| RWops::from_bytes(bytes).and_then(|rwops| rwops.load());
+
+		let image_surface = match creation_info {
 			// Use this whenever possible (whenever you can preload data into byte form)!
 			TextureCreationInfo::RawBytes(bytes) =>
-				self.texture_creator.load_texture_bytes(bytes),
+				load_image_surface_from_bytes(bytes),
 
 			TextureCreationInfo::Path(path) =>
-				self.texture_creator.load_texture(path as &str),
+				Surface::from_file(path as &str),
 
 			TextureCreationInfo::Url(url) => {
 				let response = request::get(url)?;
-				self.texture_creator.load_texture_bytes(response.as_bytes())
+				load_image_surface_from_bytes(response.as_bytes())
 			}
 
 			TextureCreationInfo::Text((font_info, text_display_info)) => {
+				// The text is made to fit within the margins (and it is drawn within them too)
+				let text_display_info = &TextDisplayInfo {
+					pixel_area: text_display_info.margins.inset_size(text_display_info.pixel_area),
+					..text_display_info.clone()
+				};
+
 				let surface = self.make_text_surface(font_info, text_display_info)?;
 
 				assert!(surface.width() >= text_display_info.pixel_area.0);
 				assert!(surface.height() == text_display_info.pixel_area.1);
 
-				Ok(self.texture_creator.create_texture_from_surface(surface)?)
+				return self.texture_creator.create_texture_from_surface(surface).to_generic();
 			}
-		}.to_generic()
+		}.to_generic()?;
+
+		let bled_image_surface = Self::maybe_bleed_colors_into_transparent_pixels(image_surface)?;
+		self.texture_creator.create_texture_from_surface(bled_image_surface).to_generic()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn split_overflowing_scrolled_rect() {
+		let texture_size = (300, 20);
+		let screen_dest = Rect::new(10, 5, 100, 20);
+		let texture_src_at = |x| Rect::new(x, 0, screen_dest.width(), texture_size.1);
+		let split = |texture_src, texture_size| TexturePool::split_overflowing_scrolled_rect(texture_src, screen_dest, texture_size);
+
+		// The texture is 200 pixels wider than the screen dest, so scrolling up to 200 pixels in does not wrap around
+		assert_eq!(split(texture_src_at(0), texture_size), Some((screen_dest, None)));
+		assert_eq!(split(texture_src_at(200), texture_size), Some((screen_dest, None)));
+
+		// Past that, the texture's start is drawn after its end, in the part of the screen dest that its end leaves empty
+		assert_eq!(split(texture_src_at(250), texture_size), Some((
+			Rect::new(10, 5, 50, 20),
+			Some((Rect::new(0, 0, 50, 20), Rect::new(60, 5, 50, 20)))
+		)));
+
+		// The two pieces always fill the screen dest exactly
+		for x in 201..300 {
+			let Some((right_screen_dest, Some((left_texture_src, left_screen_dest)))) = split(texture_src_at(x), texture_size)
+			else {panic!("Expected the texture to wrap around when scrolled {x} pixels in")};
+
+			assert_eq!(right_screen_dest.width() + left_screen_dest.width(), screen_dest.width());
+			assert_eq!(right_screen_dest.right(), left_screen_dest.left());
+			assert_eq!(left_screen_dest.right(), screen_dest.right());
+			assert_eq!(left_texture_src.width(), left_screen_dest.width());
+		}
+
+		// A texture that is narrower than its screen dest (e.g. after a resolution change) is not split
+		assert_eq!(split(texture_src_at(0), (80, 20)), None);
+	}
+
+	#[test]
+	fn remove_invisible_chars() {
+		let remove = DisplayText::remove_invisible_chars;
+		let (zwj, vs16) = (DisplayText::ZERO_WIDTH_JOINER, DisplayText::UNICODE_VARIATION_SELECTOR_16);
+
+		// Soft hyphens, BOMs, and zero-width spaces are always removed
+		for keep_variation_selectors in [false, true] {
+			assert_eq!(remove("\u{FEFF}soft\u{00AD}ware\u{200B}", keep_variation_selectors), "software");
+		}
+
+		// A joiner between two emojis is kept (e.g. man + ZWJ + computer = man technologist)
+		let joined = format!("\u{1F468}{zwj}\u{1F4BB}");
+		assert_eq!(remove(&joined, false), joined);
+		assert_eq!(remove(&joined, true), joined);
+
+		// Stray joiners are removed (at the start, at the end, and between non-emojis or an emoji and a non-emoji)
+		assert_eq!(remove(&format!("{zwj}a{zwj}b\u{1F468}{zwj}c{zwj}"), false), "ab\u{1F468}c");
+		assert_eq!(remove(&format!("\u{1F468}{zwj}{zwj}\u{1F4BB}"), false), "\u{1F468}\u{1F4BB}");
+
+		// Variation selectors are only kept with color emoji, and a joiner after one is still between two emojis
+		let joined_with_selector = format!("\u{2764}{vs16}{zwj}\u{1F525}");
+		assert_eq!(remove(&joined_with_selector, true), joined_with_selector);
+		assert_eq!(remove(&joined_with_selector, false), format!("\u{2764}{zwj}\u{1F525}"));
+
+		// A variation selector after a non-emoji does not make a following joiner count as between emojis
+		assert_eq!(remove(&format!("a{vs16}{zwj}\u{1F525}"), true), format!("a{vs16}\u{1F525}"));
+	}
+
+	#[test]
+	fn get_monospace_cut_span_len() {
+		let cut = TexturePool::get_monospace_cut_span_len;
+
+		// A truly monospace span of 10 characters, each 8 pixels wide, overstepping by 20 pixels loses 3 characters
+		assert_eq!(cut(10, 8, 80, 20), Some(7));
+		assert_eq!(cut(10, 8, 80, 80), Some(0));
+
+		/* A 'fixed-width' face that is not actually monospaced (e.g. with one wider glyph in the span) does not panic,
+		and it falls back to manual cutting. This is the case for a first character that is narrower or wider than the rest. */
+		assert_eq!(cut(10, 8, 83, 20), None);
+		assert_eq!(cut(10, 9, 80, 20), None);
+
+		// A cut that would be larger than the span falls back to manual cutting too
+		assert_eq!(cut(10, 8, 80, 81), None);
+	}
+
+	#[test]
+	fn get_longest_fitting_span_len() {
+		// This is how spans used to be cut, one character at a time
+		fn linear_cut(char_widths: &[u32], max_width: u32) -> usize {
+			let mut len = char_widths.len();
+			while len != 0 && char_widths[..len].iter().sum::<u32>() > max_width {len -= 1;}
+			len
+		}
+
+		let char_width_sets: [&[u32]; 4] = [&[8; 40], &[3, 9, 1, 12, 7, 7, 2, 15, 4, 6, 10, 1, 1, 8], &[20, 1, 1, 1, 1], &[5]];
+
+		for char_widths in char_width_sets {
+			let full_width: u32 = char_widths.iter().sum();
+
+			// The full span is assumed to not fit, so the max width stays under its width
+			for max_width in 0..full_width {
+				let mut num_measurements = 0;
+
+				let binary_searched_len = TexturePool::get_longest_fitting_span_len(char_widths.len(), |len| {
+					num_measurements += 1;
+					Ok(char_widths[..len].iter().sum::<u32>() <= max_width)
+				}).unwrap();
+
+				assert_eq!(binary_searched_len, linear_cut(char_widths, max_width), "For {char_widths:?} and a max width of {max_width}");
+				assert!(num_measurements <= char_widths.len().ilog2() as usize + 1);
+			}
+		}
+	}
+
+	#[test]
+	fn scroll_with_pause() {
+		// These are the scroll functions that `scroll_with_pause` replaced (the ticker's, and the Twilio messages' with their built-in pause)
+		let old_ticker_scroll = |seed: f64| (seed % 15.0) / 15.0;
+
+		let old_twilio_scroll = |seed: f64| {
+			let (total_cycle_time, scroll_time_percent) = (4.0, 0.75);
+			let wait_boundary = total_cycle_time * scroll_time_percent;
+			let scroll_value = seed % total_cycle_time;
+			if scroll_value < wait_boundary {scroll_value / wait_boundary} else {0.0}
+		};
+
+		for step in 0..4000 {
+			let seed = step as f64 * 0.0137;
+			assert!((super::scroll_with_pause(seed, 15.0, 0.0) - old_ticker_scroll(seed)).abs() < 1e-9, "For a seed of {seed}");
+			assert!((super::scroll_with_pause(seed, 4.0, 0.25) - old_twilio_scroll(seed)).abs() < 1e-9, "For a seed of {seed}");
+		}
+
+		// Out-of-range pause fractions are clamped, and a full pause never scrolls
+		assert_eq!(super::scroll_with_pause(3.0, 4.0, -1.0), super::scroll_with_pause(3.0, 4.0, 0.0));
+		assert_eq!(super::scroll_with_pause(3.0, 4.0, 1.0), 0.0);
+		assert_eq!(super::scroll_with_pause(3.0, 4.0, 2.0), 0.0);
+	}
+
+	#[test]
+	fn texture_mods_set_during_a_transition() {
+		use render::BlendMode;
+
+		// Before the transition, the window's texture has an alpha mod, and the incoming texture is made without any mods
+		let mut outgoing = TextureMods {maybe_blend_mode: None, maybe_alpha_mod: Some(200)};
+		let mut incoming = TextureMods::default();
+
+		// Partway through the transition (before the swap), a blend mode is set for the window's texture
+		outgoing.maybe_blend_mode = Some(BlendMode::Add);
+
+		// At the swap, the incoming texture takes the mods that were set for the outgoing one (like in `copy_texture_mods`)
+		incoming.take_set_mods_from(outgoing);
+		assert_eq!(incoming, TextureMods {maybe_blend_mode: Some(BlendMode::Add), maybe_alpha_mod: Some(200)});
+
+		// Mods that were not set for the outgoing texture do not clear the incoming texture's own ones
+		let mut incoming_with_own_mods = TextureMods {maybe_blend_mode: Some(BlendMode::Mod), maybe_alpha_mod: Some(50)};
+		incoming_with_own_mods.take_set_mods_from(TextureMods {maybe_blend_mode: None, maybe_alpha_mod: Some(100)});
+		assert_eq!(incoming_with_own_mods, TextureMods {maybe_blend_mode: Some(BlendMode::Mod), maybe_alpha_mod: Some(100)});
+
+		// Nothing is changed when no mods were set
+		let mut untouched = TextureMods::default();
+		untouched.take_set_mods_from(TextureMods::default());
+		assert_eq!(untouched, TextureMods::default());
 	}
 }
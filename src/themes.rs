@@ -0,0 +1,177 @@
+use chrono::NaiveTime;
+
+use crate::{
+	palette::Palette,
+	texture::TexturePool,
+	dashboard_defs::dashboard,
+
+	window_tree::{
+		Window,
+		WindowContents,
+		PossibleSharedWindowStateUpdater,
+		PerFrameConstantRenderingParams
+	},
+
+	utility_types::{
+		vec2f::Vec2f,
+		generic_result::*,
+		update_rate::UpdateRateCreator,
+		dynamic_optional::DynamicOptional
+	}
+};
+
+//////////
+
+/* A theme makes a top-level window, shared window state, and a shared window state updater.
+It is given a palette to pick its colors from (see `palette.rs`). It is also given the previous shared window state
+when it is being remade (like for a palette change), so that it can keep the parts of it that are slow to make
+(like data states that make blocking requests). Otherwise, that is `DynamicOptional::NONE`. */
+pub type ThemeCreator = fn(&mut TexturePool, UpdateRateCreator, Palette, DynamicOptional)
+	-> GenericResult<(Window, DynamicOptional, PossibleSharedWindowStateUpdater)>;
+
+/* This lists every asset that a theme loads from a fixed path, so that missing assets can all be found
+before the theme is made (rather than when a window that uses one is first drawn) */
+pub type RequiredAssetsGetter = fn() -> GenericResult<Vec<String>>;
+
+// More themes can be added here
+const THEMES: [(&str, ThemeCreator, RequiredAssetsGetter); 1] = [
+	(dashboard::THEME_NAME, dashboard::make_dashboard, dashboard::get_required_assets)
+];
+
+pub type ThemeIndex = usize;
+
+pub const DEFAULT_THEME_INDEX: ThemeIndex = 0;
+
+pub const fn get_num_themes() -> usize {
+	THEMES.len()
+}
+
+pub const fn get_theme_name(index: ThemeIndex) -> &'static str {
+	THEMES[index].0
+}
+
+pub const fn get_theme_creator(index: ThemeIndex) -> ThemeCreator {
+	THEMES[index].1
+}
+
+fn get_theme_index(name: &str) -> Option<ThemeIndex> {
+	THEMES.iter().position(|(theme_name, ..)| *theme_name == name)
+}
+
+// This only checks that the assets exist (without loading them), so that it is fast
+pub fn check_required_assets(index: ThemeIndex) -> MaybeError {
+	let missing_paths: Vec<String> = (THEMES[index].2)()?.into_iter()
+		.filter(|path| !std::path::Path::new(path).exists()).collect();
+
+	if !missing_paths.is_empty() {
+		return error_msg!("The '{}' theme is missing these assets: {}", get_theme_name(index), missing_paths.join(", "));
+	}
+
+	Ok(())
+}
+
+/* This replaces the top-level window and shared window state with the ones from the given theme.
+The old ones are dropped first, so that their resources (like IPC sockets) are freed before being remade.
+Switching to the current theme remakes it (which is how palette changes are applied). When `remaking_current_theme`
+is true, the old shared window state is passed to the theme creator instead, so that it can keep its data states. */
+pub fn switch_to_theme(index: ThemeIndex, remaking_current_theme: bool, top_level_window: &mut Window,
+	rendering_params: &mut PerFrameConstantRenderingParams,
+	update_rate_creator: UpdateRateCreator, palette: Palette) -> MaybeError {
+
+	// This is checked first, so that the current theme stays up if the new one is missing assets
+	check_required_assets(index)?;
+
+	*top_level_window = Window::new(None, DynamicOptional::NONE, WindowContents::Nothing, None, Vec2f::ZERO, Vec2f::ONE, None);
+	let prev_shared_window_state = if remaking_current_theme {
+		std::mem::replace(&mut rendering_params.shared_window_state, DynamicOptional::NONE)
+	}
+	else {
+		rendering_params.shared_window_state = DynamicOptional::NONE;
+		DynamicOptional::NONE
+	};
+
+	rendering_params.shared_window_state_updater = None;
+	rendering_params.texture_pool.clear_textures();
+
+	let (new_top_level_window, shared_window_state, shared_window_state_updater) = get_theme_creator(index)(
+		&mut rendering_params.texture_pool, update_rate_creator, palette, prev_shared_window_state
+	)?;
+
+	*top_level_window = new_top_level_window;
+	rendering_params.shared_window_state = shared_window_state;
+	rendering_params.shared_window_state_updater = shared_window_state_updater;
+
+	log::info!("Switched to the '{}' theme.", get_theme_name(index));
+	Ok(())
+}
+
+////////// Automatic time-of-day theme switching
+
+pub type HourAndMinute = (u32, u32);
+
+pub fn to_time((hour, minute): HourAndMinute) -> Option<NaiveTime> {
+	NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/* The start is inclusive, and the end is exclusive. If the start is after the end, the time range wraps around midnight.
+If they are equal, it covers the whole day. This is also used for the screen dimming hours. */
+pub fn time_range_contains(start: NaiveTime, end: NaiveTime, time: NaiveTime) -> bool {
+	if start < end {
+		time >= start && time < end
+	}
+	else {
+		time >= start || time < end
+	}
+}
+
+/* Each scheduled theme applies from its start time (inclusive) to its end time (exclusive), in local time.
+If the start is after the end, the time range wraps around midnight. If they are equal, it covers the whole day.
+When time ranges overlap, the first matching one wins. */
+#[derive(serde::Deserialize)]
+pub struct ScheduledThemeConfig {
+	theme: String,
+	start: HourAndMinute,
+	end: HourAndMinute
+}
+
+struct ScheduledTheme {
+	index: ThemeIndex,
+	start: NaiveTime,
+	end: NaiveTime
+}
+
+impl ScheduledTheme {
+	fn contains(&self, time: NaiveTime) -> bool {
+		time_range_contains(self.start, self.end, time)
+	}
+}
+
+pub struct ThemeSchedule {
+	scheduled_themes: Vec<ScheduledTheme>
+}
+
+impl ThemeSchedule {
+	// Unknown theme names and invalid times are logged and skipped, rather than failing
+	pub fn new(config: &[ScheduledThemeConfig]) -> Self {
+		let scheduled_themes = config.iter().filter_map(|scheduled| {
+			let Some(index) = get_theme_index(&scheduled.theme) else {
+				log::warn!("Unknown theme '{}' in the theme schedule! Skipping it.", scheduled.theme);
+				return None;
+			};
+
+			let (Some(start), Some(end)) = (to_time(scheduled.start), to_time(scheduled.end)) else {
+				log::warn!("Invalid start or end time for the '{}' theme in the theme schedule! Skipping it.", scheduled.theme);
+				return None;
+			};
+
+			Some(ScheduledTheme {index, start, end})
+		}).collect();
+
+		Self {scheduled_themes}
+	}
+
+	pub fn get_scheduled_theme_index(&self) -> Option<ThemeIndex> {
+		let now = chrono::Local::now().time();
+		self.scheduled_themes.iter().find(|scheduled| scheduled.contains(now)).map(|scheduled| scheduled.index)
+	}
+}
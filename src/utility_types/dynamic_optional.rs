@@ -20,21 +20,20 @@ impl DynamicOptional {
 		);
 	}
 
+	// This is `None` if there is no inner value, or if it has a different type
+	pub fn try_get<T: 'static>(&self) -> Option<&T> {
+		self.inner.as_ref().and_then(|boxed_inner_value| boxed_inner_value.downcast_ref::<T>())
+	}
+
+	pub fn try_get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+		self.inner.as_mut().and_then(|boxed_inner_value| boxed_inner_value.downcast_mut::<T>())
+	}
+
 	pub fn get<T: 'static>(&self) -> &T {
-		if let Some(boxed_inner_value) = &self.inner {
-			if let Some(value) = boxed_inner_value.downcast_ref::<T>() {
-				return value;
-			}
-		}
-		DynamicOptional::fail_for_inner_access::<T>()
+		self.try_get::<T>().unwrap_or_else(|| DynamicOptional::fail_for_inner_access::<T>())
 	}
 
 	pub fn get_mut<T: 'static>(&mut self) -> &mut T {
-		if let Some(boxed_inner_value) = &mut self.inner {
-			if let Some(value) = boxed_inner_value.downcast_mut::<T>() {
-				return value;
-			}
-		}
-		DynamicOptional::fail_for_inner_access::<T>()
+		self.try_get_mut::<T>().unwrap_or_else(|| DynamicOptional::fail_for_inner_access::<T>())
 	}
 }
@@ -0,0 +1,32 @@
+use crate::utility_types::generic_result::*;
+
+/* These map a linear progress fraction in [0, 1] to an eased one in [0, 1].
+See https://easings.net/ for visualizations of them. */
+pub type EasingFn = fn(f64) -> f64;
+
+pub fn linear(t: f64) -> f64 {
+	t
+}
+
+pub fn ease_in_out_sine(t: f64) -> f64 {
+	-((std::f64::consts::PI * t).cos() - 1.0) * 0.5
+}
+
+//////////
+
+// This maps names to all of the easing functions above, so that they can be chosen from config files
+const ALL_EASING_FNS: [(&str, EasingFn); 2] = [
+	("linear", linear),
+	("ease_in_out_sine", ease_in_out_sine)
+];
+
+pub fn get_by_name(name: &str) -> GenericResult<EasingFn> {
+	match ALL_EASING_FNS.iter().find(|(easing_fn_name, _)| *easing_fn_name == name) {
+		Some((_, easing_fn)) => Ok(*easing_fn),
+
+		None => {
+			let all_names: Vec<&str> = ALL_EASING_FNS.iter().map(|(easing_fn_name, _)| *easing_fn_name).collect();
+			error_msg!("Unknown easing function '{name}'! The known ones are: {}.", all_names.join(", "))
+		}
+	}
+}
@@ -0,0 +1,94 @@
+use std::{collections::{HashMap, hash_map::Entry}, time::{Duration, Instant}};
+use crate::error_webhook::ErrorWebhookNotifier;
+
+//////////
+
+/* This collects errors from around the dashboard, so that they can be shown on the screen
+(rather than only being logged). Errors are keyed by their source and their message, since some
+sources are shared (e.g. every window reports under "Window updater"), and two different errors from
+the same source should not keep replacing each other. If an error is not reported again for a while,
+it expires, so that errors from sources that recover eventually disappear from the screen. Errors can optionally be sent to a webhook too (see `error_webhook.rs`). */
+
+pub type ErrorTimestamp = chrono::DateTime<chrono::Local>;
+
+pub struct ReportedError {
+	pub first_seen: ErrorTimestamp,
+	pub last_seen: ErrorTimestamp,
+
+	last_reported: Instant,
+	last_logged: Instant,
+	was_sent_to_webhook: bool
+}
+
+pub struct ErrorState {
+	errors: HashMap<(String, String), ReportedError>, // This is keyed by the source and the message
+	maybe_webhook_notifier: Option<ErrorWebhookNotifier>
+}
+
+impl ErrorState {
+	// Identical errors are not logged more often than this
+	const RELOG_COOLDOWN: Duration = Duration::from_secs(30);
+
+	// Errors that have not been reported again within this duration are removed
+	const EXPIRY_DURATION: Duration = Duration::from_secs(60);
+
+	pub fn new(maybe_webhook_notifier: Option<ErrorWebhookNotifier>) -> Self {
+		Self {errors: HashMap::new(), maybe_webhook_notifier}
+	}
+
+	pub fn report(&mut self, source: &str, message: String) {
+		let (now, timestamp) = (Instant::now(), chrono::Local::now());
+
+		let error = match self.errors.entry((source.to_owned(), message.clone())) {
+			Entry::Occupied(entry) => {
+				let already_error = entry.into_mut();
+				already_error.last_reported = now;
+				already_error.last_seen = timestamp;
+
+				if now.duration_since(already_error.last_logged) >= Self::RELOG_COOLDOWN {
+					log::error!("{source}: {message} (still happening)");
+					already_error.last_logged = now;
+				}
+
+				already_error
+			}
+
+			Entry::Vacant(entry) => {
+				log::error!("{source}: {message}");
+
+				entry.insert(ReportedError {
+					first_seen: timestamp, last_seen: timestamp,
+					last_reported: now, last_logged: now, was_sent_to_webhook: false
+				})
+			}
+		};
+
+		if let Some(notifier) = &mut self.maybe_webhook_notifier {
+			if !error.was_sent_to_webhook {
+				error.was_sent_to_webhook = notifier.send_if_due(source, &message, error.first_seen, error.last_seen);
+			}
+		}
+	}
+
+	// This is for errors that are known to have been resolved (and not just unreported for a while)
+	pub fn clear(&mut self, source: &str) {
+		self.errors.retain(|(error_source, _), _| error_source != source);
+	}
+
+	/* This returns up to the given number of unexpired errors (with their sources and messages), with the newest ones first.
+	They are ordered by when they were first seen (and not last seen), so that the order stays stable while
+	several sources keep reporting errors. */
+	pub fn get_recent_errors(&mut self, max_num_errors: usize) -> Vec<(&str, &str, &ReportedError)> {
+		let now = Instant::now();
+		self.errors.retain(|_, error| now.duration_since(error.last_reported) < Self::EXPIRY_DURATION);
+
+		let mut recent_errors: Vec<_> = self.errors.iter().map(|((source, message), error)| (source.as_str(), message.as_str(), error)).collect();
+
+		recent_errors.sort_by(|(source_a, message_a, a), (source_b, message_b, b)|
+			b.first_seen.cmp(&a.first_seen).then(source_a.cmp(source_b)).then(message_a.cmp(message_b))
+		);
+
+		recent_errors.truncate(max_num_errors);
+		recent_errors
+	}
+}
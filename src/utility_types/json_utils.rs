@@ -2,14 +2,54 @@
 
 use crate::utility_types::generic_result::*;
 
+/* If the file is malformed, the error names the field with the problem (as a path like
+`screen_option.Windowed[2]`), says what was expected there, and shows the offending line.
+This is for config files that are edited by hand. */
 pub fn load_from_file<T: for <'de> serde::Deserialize<'de>>(path: &str) -> GenericResult<T> {
 	let file_contents = match std::fs::read_to_string(path) {
 		Ok(contents) => Ok(contents),
 
 		Err(err) => error_msg!(
-			"The API key file at path '{path}' could not be found. Official error: '{err}'."
+			"The JSON file at path '{path}' could not be read. Official error: '{err}'."
 		)
 	}?;
 
-	serde_json::from_str(&file_contents).to_generic()
+	let deserializer = &mut serde_json::Deserializer::from_str(&file_contents);
+
+	serde_path_to_error::deserialize(deserializer).or_else(|err| {
+		// Serde's error includes the line and column (lines start at 1, and are 0 if the error is not tied to a spot in the file)
+		let inner_err = err.inner();
+		let offending_line = file_contents.lines().nth(inner_err.line().saturating_sub(1)).unwrap_or("").trim();
+
+		error_msg!(
+			"The JSON file at path '{path}' has a problem with the field '{}': {inner_err}. The offending line is: '{offending_line}'.",
+			err.path()
+		)
+	})
+}
+
+//////////
+
+/* A string value of the form `"${NAME}"` is replaced with the value of the environment variable `NAME`
+(and it is an error if that variable is not set). Other strings are kept as they are. This is for secrets
+like API keys, so that they can come from the environment. Use it with `#[serde(deserialize_with = ...)]`. */
+pub fn deserialize_with_env_var<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+	use serde::Deserialize;
+	substitute_env_var(String::deserialize(deserializer)?)
+}
+
+// This is like the function above, but for optional values (use it with `#[serde(default)]` too)
+pub fn deserialize_maybe_with_env_var<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+	use serde::Deserialize;
+	Option::<String>::deserialize(deserializer)?.map(substitute_env_var).transpose()
+}
+
+fn substitute_env_var<Error: serde::de::Error>(value: String) -> Result<String, Error> {
+	let Some(env_var_name) = value.strip_prefix("${").and_then(|rest| rest.strip_suffix('}')) else {
+		return Ok(value);
+	};
+
+	std::env::var(env_var_name).map_err(|err| Error::custom(
+		format!("the environment variable '{env_var_name}' could not be read ({err})")
+	))
 }
@@ -1,5 +1,7 @@
 pub mod vec2f;
 pub mod json_utils;
+pub mod easing_fns;
+pub mod error_state;
 pub mod update_rate;
 pub mod thread_task;
 pub mod generic_result;
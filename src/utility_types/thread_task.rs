@@ -1,7 +1,8 @@
 use std::thread;
-use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use std::sync::{mpsc, Arc, Mutex, atomic::{AtomicBool, Ordering}};
 
-use crate::utility_types::generic_result::*;
+use crate::{metrics, utility_types::generic_result::*};
 
 //////////
 
@@ -24,7 +25,22 @@ pub struct ContinuallyUpdated<T: Updatable> {
 	curr_data: T,
 	param_sender: mpsc::SyncSender<T::Param>,
 	data_receiver: mpsc::Receiver<Result<T, String>>,
-	name: &'static str
+	name: &'static str,
+
+	// This is only set when new data arrives from a successful update (and when this is made, with the initial data)
+	last_success_time: Instant,
+
+	// This is set once the first successful update arrives (the initial data may just be a placeholder)
+	has_gotten_new_data: bool,
+
+	/* This is when the thread was last given a parameter (so when the update that it is running, or that is waiting
+	to be received, started). A refresh is pending until an update that started after the refresh request finishes. */
+	last_iteration_start_time: Instant,
+	maybe_refresh_request_time: Option<Instant>,
+
+	// The thread handle is taken when this is dropped
+	stop_flag: Arc<AtomicBool>,
+	maybe_thread_handle: Option<thread::JoinHandle<()>>
 }
 
 impl<T: Updatable + 'static> ContinuallyUpdated<T> {
@@ -33,17 +49,25 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 		let (param_sender, param_receiver) = mpsc::sync_channel(1);
 
 		let mut cloned_data = data.clone();
+		let stop_flag = Arc::new(AtomicBool::new(false));
+		let thread_stop_flag = stop_flag.clone();
+		let source_metrics = metrics::register_source(name);
 
-		thread::spawn(move || {
+		let thread_handle = thread::spawn(move || {
 			loop {
 				fn handle_channel_error<Error: std::fmt::Display>(err: Error, name: &str, transfer_description: &str) {
 					log::warn!("Problem from {name} with {transfer_description} main thread (probably harmless, at program shutdown): {err}");
 				}
 
-				/* `recv` will block until it receives the parameter! The parameters will
-				only be passed once the data has been received on the main thread. */
-				let param = match param_receiver.recv() {
+				if thread_stop_flag.load(Ordering::Relaxed) {
+					return;
+				}
+
+				/* This will block until it receives the parameter (or until it is time to check the stop flag again)!
+				The parameters will only be passed once the data has been received on the main thread. */
+				let param = match param_receiver.recv_timeout(STOP_CHECK_INTERVAL) {
 					Ok(inner_param) => inner_param,
+					Err(mpsc::RecvTimeoutError::Timeout) => continue,
 
 					Err(err) => {
 						handle_channel_error(err, name, "receiving parameter from");
@@ -51,11 +75,15 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 					}
 				};
 
+				let update_start_time = Instant::now();
+
 				let result = match cloned_data.update(&param) {
 					Ok(_) => Ok(cloned_data.clone()),
 					Err(err) => Err(err.to_string())
 				};
 
+				source_metrics.record_update(result.is_ok(), update_start_time.elapsed());
+
 				if let Err(err) = data_sender.send(result) {
 					handle_channel_error(err, name, "sending data back to the");
 					return;
@@ -63,9 +91,14 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 			}
 		});
 
-		let continually_updated = Self {
+		let mut continually_updated = Self {
 			curr_data: data.clone(), param_sender,
-			data_receiver, name
+			data_receiver, name,
+			last_success_time: Instant::now(),
+			has_gotten_new_data: false,
+			last_iteration_start_time: Instant::now(),
+			maybe_refresh_request_time: None,
+			stop_flag, maybe_thread_handle: Some(thread_handle)
 		};
 
 		if let Err(err) = continually_updated.run_new_update_itetation(initial_param) {
@@ -76,17 +109,39 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 	}
 
 	// This unblocks the param receiver and starts a new update iteration with a new param
-	fn run_new_update_itetation(&self, param: &T::Param) -> MaybeError {
+	fn run_new_update_itetation(&mut self, param: &T::Param) -> MaybeError {
+		self.last_iteration_start_time = Instant::now();
 		self.param_sender.send(param.clone()).to_generic()
 	}
 
+	// This is called when an update iteration finishes (successfully or not), before the next one starts
+	fn finish_update_iteration(&mut self) {
+		if self.maybe_refresh_request_time.is_some_and(|request_time| self.last_iteration_start_time >= request_time) {
+			self.maybe_refresh_request_time = None;
+		}
+	}
+
+	/* This makes the data be fetched again right away. The thread starts a new update as soon as its current one
+	is received (so `update` should be called on every frame while `is_refresh_pending` is true, rather than at its
+	usual rate). If the current update started before this request, its data is shown, and then another one is run. */
+	pub fn request_refresh(&mut self) {
+		self.maybe_refresh_request_time = Some(Instant::now());
+	}
+
+	pub const fn is_refresh_pending(&self) -> bool {
+		self.maybe_refresh_request_time.is_some()
+	}
+
 	// This returns false if a thread failed to complete its operation.
 	pub fn update(&mut self, param: &T::Param) -> GenericResult<bool> {
 		let mut error: Option<String> = None;
 
 		match self.data_receiver.try_recv() {
 			Ok(Ok(new_data)) => {
+				self.finish_update_iteration();
 				self.curr_data = new_data;
+				self.last_success_time = Instant::now();
+				self.has_gotten_new_data = true;
 				self.run_new_update_itetation(param)?;
 			}
 
@@ -99,6 +154,7 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 		}
 
 		if let Some(err) = error {
+			self.finish_update_iteration();
 			log::error!("Updating the {} data on this iteration failed. Error: '{err}'.", self.name);
 			self.run_new_update_itetation(param)?;
 			return Ok(false);
@@ -110,4 +166,58 @@ impl<T: Updatable + 'static> ContinuallyUpdated<T> {
 	pub const fn get_data(&self) -> &T {
 		&self.curr_data
 	}
+
+	pub const fn has_gotten_new_data(&self) -> bool {
+		self.has_gotten_new_data
+	}
+
+	pub fn time_since_last_success(&self) -> Duration {
+		self.last_success_time.elapsed()
+	}
+}
+
+// This is how often a thread checks its stop flag while waiting for a new parameter
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/* These are the threads that were told to stop, but that may not have finished yet (along with their names).
+They are only waited on at program shutdown, so that dropping a continual updater (e.g. when switching themes)
+never blocks the main thread on a slow request. */
+static STOPPING_THREADS: Mutex<Vec<(&'static str, thread::JoinHandle<()>)>> = Mutex::new(Vec::new());
+
+/* This waits for the stopping threads to finish, for up to the timeout in total. The threads
+that do not finish in time are left to be ended when the program exits. */
+pub fn join_stopping_threads(timeout: Duration) {
+	let stopping_threads = std::mem::take(&mut *STOPPING_THREADS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+
+	let deadline = Instant::now() + timeout;
+
+	for (name, thread_handle) in stopping_threads {
+		while !thread_handle.is_finished() && Instant::now() < deadline {
+			thread::sleep(STOP_CHECK_INTERVAL);
+		}
+
+		if !thread_handle.is_finished() {
+			log::warn!("The {name} thread did not stop within {timeout:?}, so it is being left to end with the program.");
+			continue;
+		}
+
+		match thread_handle.join() {
+			Ok(()) => log::info!("Stopped the {name} thread."),
+			Err(_) => log::warn!("The {name} thread panicked before stopping.")
+		}
+	}
+}
+
+impl<T: Updatable> Drop for ContinuallyUpdated<T> {
+	// This tells the thread to stop, without waiting for it (see `join_stopping_threads`)
+	fn drop(&mut self) {
+		let Some(thread_handle) = self.maybe_thread_handle.take() else {return};
+		self.stop_flag.store(true, Ordering::Relaxed);
+
+		let mut stopping_threads = STOPPING_THREADS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+		// The threads that already finished are forgotten here, so that the list does not grow over many theme switches
+		stopping_threads.retain(|(_, thread_handle)| !thread_handle.is_finished());
+		stopping_threads.push((self.name, thread_handle));
+	}
 }
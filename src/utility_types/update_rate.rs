@@ -1,4 +1,6 @@
-use std::num::Wrapping;
+use std::{num::Wrapping, time::Instant};
+
+use rand::Rng;
 
 pub type Seconds = f64;
 type FrameIndex = u32; // Intended to wrap, so no bigger type is needed
@@ -8,14 +10,15 @@ type Fps = u32;
 
 #[derive(Copy, Clone)]
 pub struct UpdateRate {
-	every_n_frames: FrameIndex
+	every_n_frames: FrameIndex,
+	phase_offset: FrameIndex // This is always less than `every_n_frames`
 }
 
 impl UpdateRate {
-	pub const ONCE_PER_FRAME: Self = Self {every_n_frames: 1};
+	pub const ONCE_PER_FRAME: Self = Self {every_n_frames: 1, phase_offset: 0};
 
 	// TODO: make an option for only running something once instead
-	pub const ALMOST_NEVER: Self = Self {every_n_frames: FrameIndex::MAX};
+	pub const ALMOST_NEVER: Self = Self {every_n_frames: FrameIndex::MAX, phase_offset: 0};
 
 	fn new(num_seconds_between_updates: Seconds, fps: Fps) -> Self {
 		let max_frame_index = FrameIndex::MAX;
@@ -38,11 +41,11 @@ impl UpdateRate {
 		//////////
 
 		// This is floored
-		Self {every_n_frames: num_frames_between_updates as FrameIndex}
+		Self {every_n_frames: num_frames_between_updates as FrameIndex, phase_offset: 0}
 	}
 
 	pub const fn is_time_to_update(self, frame_counter: FrameCounter) -> bool {
-		frame_counter.wrapping_frame_index.0 % self.every_n_frames == 0
+		frame_counter.wrapping_frame_index.0.wrapping_add(self.phase_offset) % self.every_n_frames == 0
 	}
 }
 
@@ -50,32 +53,78 @@ impl UpdateRate {
 
 #[derive(Copy, Clone)]
 pub struct FrameCounter {
-	wrapping_frame_index: Wrapping<FrameIndex>
+	wrapping_frame_index: Wrapping<FrameIndex>,
+
+	// This is the real time that the last frame took (it is zero before the first tick)
+	delta_time: Seconds,
+
+	// This is the real time since the first tick (it is not capped, unlike the delta time)
+	total_time: Seconds,
+
+	maybe_first_and_last_tick_times: Option<(Instant, Instant)>
 }
 
 impl FrameCounter {
+	/* Long gaps between frames (e.g. from pausing while the window is unfocused)
+	are capped to this, so that time-based motion does not jump after them */
+	const MAX_DELTA_TIME: Seconds = 0.25;
+
 	pub const fn new() -> Self {
-		Self {wrapping_frame_index: Wrapping(0)}
+		Self {wrapping_frame_index: Wrapping(0), delta_time: 0.0, total_time: 0.0, maybe_first_and_last_tick_times: None}
 	}
 
+	// The times are from a monotonic clock, and they are computed here so that reading them is free
 	pub fn tick(&mut self) {
+		let now = Instant::now();
+
+		let first_tick_time = if let Some((first_tick_time, last_tick_time)) = self.maybe_first_and_last_tick_times {
+			self.delta_time = now.duration_since(last_tick_time).as_secs_f64().min(Self::MAX_DELTA_TIME);
+			self.total_time = now.duration_since(first_tick_time).as_secs_f64();
+			first_tick_time
+		}
+		else {
+			now
+		};
+
+		self.maybe_first_and_last_tick_times = Some((first_tick_time, now));
 		self.wrapping_frame_index += 1;
 	}
+
+	pub const fn delta_seconds(self) -> Seconds {
+		self.delta_time
+	}
+
+	pub const fn total_seconds(self) -> Seconds {
+		self.total_time
+	}
 }
 
 //////////
 
+/* Each new update rate can be given a random phase offset of up to the max jitter, so that windows
+with the same update rate (like ones that make API requests) do not all update on the same frame.
+This only shifts when the updates happen, so the time between updates stays the same. */
 #[derive(Copy, Clone)]
 pub struct UpdateRateCreator {
-	fps: Fps
+	fps: Fps,
+	max_jitter_secs: Seconds
 }
 
 impl UpdateRateCreator {
-	pub const fn new(fps: Fps) -> Self {
-		Self {fps}
+	pub const fn new(fps: Fps, max_jitter_secs: Seconds) -> Self {
+		Self {fps, max_jitter_secs}
 	}
 
 	pub fn new_instance(self, num_seconds_between_updates: Seconds) -> UpdateRate {
-		UpdateRate::new(num_seconds_between_updates, self.fps)
+		let mut update_rate = UpdateRate::new(num_seconds_between_updates, self.fps);
+
+		// This is also false for a NaN jitter
+		if self.max_jitter_secs > 0.0 {
+			let max_jitter_frames = (self.max_jitter_secs * self.fps as Seconds).min(FrameIndex::MAX as Seconds) as FrameIndex;
+			let max_phase_offset = max_jitter_frames.min(update_rate.every_n_frames - 1);
+			update_rate.phase_offset = rand::thread_rng().gen_range(0..=max_phase_offset);
+		}
+
+		update_rate
 	}
 }
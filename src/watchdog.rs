@@ -0,0 +1,75 @@
+use std::{
+	thread,
+	time::{Duration, Instant},
+	sync::{Arc, atomic::{AtomicU64, Ordering}}
+};
+
+//////////
+
+/* This is an optional watchdog for kiosk setups, for when the main loop gets stuck (e.g. because of a driver hiccup).
+The main loop sends it a heartbeat on every iteration, and if no heartbeat arrives within the timeout, it logs that,
+and optionally exits the process with a nonzero code (so that something like a systemd unit can restart the app).
+It is off by default, so that pausing the app in a debugger does not make it exit. */
+
+#[derive(serde::Deserialize)]
+pub struct WatchdogConfig {
+	// This should be longer than the slowest expected frame (e.g. a theme switch, which makes some API requests)
+	timeout_secs: u64,
+	exit_on_timeout: bool
+}
+
+pub struct Watchdog {
+	start_time: Instant,
+	last_heartbeat_ms: Arc<AtomicU64> // This is the time since the start time
+}
+
+impl Watchdog {
+	const EXIT_CODE: i32 = 70; // This is `EX_SOFTWARE` (an internal software error)
+
+	// The heartbeat is checked this many times per timeout
+	const NUM_CHECKS_PER_TIMEOUT: u32 = 4;
+
+	pub fn new(config: &WatchdogConfig) -> Self {
+		let start_time = Instant::now();
+		let last_heartbeat_ms = Arc::new(AtomicU64::new(0));
+
+		let timeout = Duration::from_secs(config.timeout_secs.max(1));
+		let exit_on_timeout = config.exit_on_timeout;
+		let thread_last_heartbeat_ms = last_heartbeat_ms.clone();
+
+		thread::spawn(move || {
+			let mut was_stuck = false;
+
+			loop {
+				thread::sleep(timeout / Self::NUM_CHECKS_PER_TIMEOUT);
+
+				let last_heartbeat_time = start_time + Duration::from_millis(thread_last_heartbeat_ms.load(Ordering::Relaxed));
+				let time_since_heartbeat = last_heartbeat_time.elapsed();
+				let is_stuck = time_since_heartbeat >= timeout;
+
+				if is_stuck && !was_stuck {
+					log::error!("The main loop has not run for {time_since_heartbeat:?}, so it seems to be stuck!");
+
+					if exit_on_timeout {
+						log::error!("Exiting with code {} so that the app can be restarted.", Self::EXIT_CODE);
+						std::process::exit(Self::EXIT_CODE);
+					}
+				}
+				else if !is_stuck && was_stuck {
+					log::info!("The main loop is running again.");
+				}
+
+				was_stuck = is_stuck;
+			}
+		});
+
+		log::info!("Started the watchdog, with a timeout of {timeout:?}.");
+
+		Self {start_time, last_heartbeat_ms}
+	}
+
+	// This is cheap enough to call on every iteration of the main loop
+	pub fn send_heartbeat(&self) {
+		self.last_heartbeat_ms.store(self.start_time.elapsed().as_millis() as u64, Ordering::Relaxed);
+	}
+}
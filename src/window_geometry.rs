@@ -0,0 +1,72 @@
+use sdl2::{rect::Rect, VideoSubsystem, video::Window as WindowSDL};
+
+use crate::utility_types::generic_result::*;
+
+//////////
+
+/* This is where the position and size of a windowed app are saved when it quits,
+so that the next windowed launch can open where the last one was (if that is turned on) */
+const WINDOW_GEOMETRY_PATH: &str = "window_geometry.json";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct WindowGeometry {
+	pub x: i32,
+	pub y: i32,
+	pub width: u32,
+	pub height: u32
+}
+
+impl WindowGeometry {
+	/* This returns `None` if nothing was saved yet, or if loading it failed (which is logged).
+	The geometry is fit onto the display that it overlaps the most (or the first display, if it does not
+	overlap any of them), so that a window saved on a since-disconnected monitor does not open off-screen. */
+	pub fn load(sdl_video_subsystem: &VideoSubsystem) -> Option<Self> {
+		if !std::path::Path::new(WINDOW_GEOMETRY_PATH).exists() {
+			return None;
+		}
+
+		let result = crate::utility_types::json_utils::load_from_file::<Self>(WINDOW_GEOMETRY_PATH)
+			.and_then(|geometry| geometry.fit_onto_displays(sdl_video_subsystem));
+
+		match result {
+			Ok(geometry) => Some(geometry),
+
+			Err(err) => {
+				log::warn!("Could not restore the window geometry from '{WINDOW_GEOMETRY_PATH}': '{err}'.");
+				None
+			}
+		}
+	}
+
+	pub fn save(sdl_window: &WindowSDL) -> MaybeError {
+		let ((x, y), (width, height)) = (sdl_window.position(), sdl_window.size());
+		let json = serde_json::to_string(&Self {x, y, width, height})?;
+		std::fs::write(WINDOW_GEOMETRY_PATH, json).context(format!("Could not save the window geometry to '{WINDOW_GEOMETRY_PATH}'"))
+	}
+
+	fn fit_onto_displays(self, sdl_video_subsystem: &VideoSubsystem) -> GenericResult<Self> {
+		let num_displays = sdl_video_subsystem.num_video_displays().to_generic()?;
+
+		let all_display_bounds = (0..num_displays)
+			.map(|display_index| sdl_video_subsystem.display_bounds(display_index).to_generic())
+			.collect::<GenericResult<Vec<Rect>>>()?;
+
+		let window_rect = Rect::new(self.x, self.y, self.width.max(1), self.height.max(1));
+
+		let get_overlap_area = |display_bounds: &Rect| window_rect.intersection(*display_bounds)
+			.map_or(0, |overlap| overlap.width() as u64 * overlap.height() as u64);
+
+		let display_bounds = all_display_bounds.iter().max_by_key(|display_bounds| get_overlap_area(display_bounds))
+			.filter(|display_bounds| get_overlap_area(display_bounds) != 0)
+			.or(all_display_bounds.first())
+			.context("There are no displays to put the window on")?;
+
+		let (width, height) = (self.width.clamp(1, display_bounds.width()), self.height.clamp(1, display_bounds.height()));
+
+		Ok(Self {
+			x: self.x.clamp(display_bounds.x(), display_bounds.x() + (display_bounds.width() - width) as i32),
+			y: self.y.clamp(display_bounds.y(), display_bounds.y() + (display_bounds.height() - height) as i32),
+			width, height
+		})
+	}
+}
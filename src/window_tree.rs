@@ -1,21 +1,24 @@
+use std::borrow::Cow;
 use sdl2::{self, rect::Rect};
 
 use crate::{
 	utility_types::{
-		vec2f::Vec2f,
 		generic_result::*,
+		vec2f::{Vec2f, assert_in_unit_interval},
+		easing_fns::EasingFn,
+		error_state::ErrorState,
 		dynamic_optional::DynamicOptional,
-		update_rate::{UpdateRate, FrameCounter}
+		update_rate::{Seconds, UpdateRate, FrameCounter}
 	},
 
-	texture::{TexturePool, TextureHandle, TextureCreationInfo}
+	texture::{TexturePool, TextureHandle, TextureCreationInfo, TextureDrawParams, TextureTransitionOverlapPolicy}
 };
 
 ////////// These are some general utility types
 
 /* TODO: make this more similar to `Rect`, in terms of operations.
 Also make a constructor for this. */
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, serde::Serialize)]
 struct FRect {
 	pub x: f32,
 	pub y: f32,
@@ -38,13 +41,24 @@ pub type CanvasSDL = sdl2::render::Canvas<sdl2::video::Window>;
 
 /* TODO: can I pass a current time parameter in here,
 in order to allow for timing-based effects like texture fade-in? */
-pub struct WindowUpdaterParams<'a, 'b, 'c, 'd> {
+pub struct WindowUpdaterParams<'a, 'b, 'c, 'd, 'e> {
 	pub window: &'a mut Window,
 	pub texture_pool: &'b mut TexturePool<'c>,
 	pub shared_window_state: &'d mut DynamicOptional,
+	pub error_state: &'e mut ErrorState,
+	pub frame_counter: FrameCounter, // This is useful for FPS-independent motion (see `Window::move_by`)
+	pub window_is_focused: bool, // This is true unless the platform reported that the app window lost focus
 	pub area_drawn_to_screen: (u32, u32)
 }
 
+// This is passed to a window's click handler
+pub struct WindowClickParams<'a, 'b> {
+	pub window: &'a mut Window,
+	pub shared_window_state: &'b mut DynamicOptional
+}
+
+pub type WindowClickHandler = fn(WindowClickParams) -> MaybeError;
+
 // TODO: genericize these two over one typedef
 
 pub type PossibleWindowUpdater = Option<(
@@ -53,7 +67,7 @@ pub type PossibleWindowUpdater = Option<(
 )>;
 
 pub type PossibleSharedWindowStateUpdater = Option<(
-	fn(&mut DynamicOptional, &mut TexturePool) -> MaybeError,
+	fn(&mut DynamicOptional, &mut TexturePool, &mut ErrorState) -> MaybeError,
 	UpdateRate
 )>;
 
@@ -63,7 +77,19 @@ pub struct PerFrameConstantRenderingParams<'a> {
 	pub texture_pool: TexturePool<'a>,
 	pub frame_counter: FrameCounter,
 	pub shared_window_state: DynamicOptional,
-	pub shared_window_state_updater: PossibleSharedWindowStateUpdater
+	pub shared_window_state_updater: PossibleSharedWindowStateUpdater,
+
+	// If this is set, rounded borders are drawn with anti-aliasing (otherwise, their corners are crisp, but jagged)
+	pub use_anti_aliased_borders: bool,
+
+	// This is set from focus events (and it stays true if the platform never sends them)
+	pub window_is_focused: bool,
+
+	// The whole window tree is moved by this many pixels (this is for reducing burn-in, and it is usually zero)
+	pub pixel_shift: (i32, i32),
+
+	// Errors from window updating and drawing are reported here, so that they can be shown on the screen
+	pub error_state: ErrorState
 }
 
 //////////
@@ -71,19 +97,61 @@ pub struct PerFrameConstantRenderingParams<'a> {
 pub type GeneralLine<T> = (ColorSDL, Vec<T>);
 pub type Line = GeneralLine<Vec2f>;
 
+// This is a line with a thickness in pixels (these are drawn anti-aliased, unlike plain lines)
+pub type ThickLine = (ColorSDL, u8, Vec<Vec2f>);
+
+// This is the direction that a gradient goes from its start color to its end color in
+#[derive(Copy, Clone, Debug)]
+pub enum GradientDirection {
+	Vertical, // Top to bottom
+	Horizontal, // Left to right
+	Diagonal // Top left to bottom right
+}
+
+/* A border radius is either in pixels, or a fraction of the smaller side of the window
+(which is computed when drawing, so that the rounding scales with the layout and the screen resolution). */
+#[derive(Copy, Clone)]
+pub enum WindowBorderRadius {
+	Pixels(i16),
+	FractionOfSmallerSide(f32)
+}
+
+// A path can be chosen at runtime, and bytes are for images embedded with `include_bytes!`
+#[derive(Clone)]
+pub enum LazyTextureSource {
+	Path(Cow<'static, str>),
+	Bytes(&'static [u8])
+}
+
 // TODO: make the border color a part of this
 #[derive(Clone)]
 pub enum WindowContents {
 	Nothing,
 	Color(ColorSDL),
+
+	/* This is drawn one line of pixels at a time, over the window's uncorrected rect.
+	Like lines, it is not rotated with the window. */
+	Gradient {start: ColorSDL, end: ColorSDL, direction: GradientDirection},
+
+	// These are kept for thin lines that do not need a thickness (no window uses them at the moment)
+	#[allow(dead_code)]
 	Lines(Vec<Line>),
+
+	ThickLines(Vec<ThickLine>),
 	Texture(TextureHandle),
-	Many(Vec<WindowContents>) // Note: recursive `Many` items here are allowed.
+
+	/* This is loaded from its source into a `Texture` the first time that its window is drawn
+	(so that textures that are never or rarely shown don't slow down startup). */
+	LazyTexture(LazyTextureSource),
+
+	/* Note: recursive `Many` items here are allowed. The window keeps a flat draw order for them
+	(see `get_flattened_draw_order`), so nesting does not cost anything per frame. */
+	Many(Vec<WindowContents>)
 }
 
 impl WindowContents {
 	pub fn make_texture_contents(path: &str, texture_pool: &mut TexturePool) -> GenericResult<Self> {
-		let creation_info = TextureCreationInfo::Path(std::borrow::Cow::Borrowed(path));
+		let creation_info = TextureCreationInfo::Path(Cow::Borrowed(path));
 		Ok(Self::Texture(texture_pool.make_texture(&creation_info)?))
 	}
 
@@ -125,12 +193,183 @@ impl WindowContents {
 		*self = WindowContents::Texture(updated_texture);
 		Ok(())
 	}
+
+	// This is for debug dumps of the window tree
+	fn describe(&self) -> String {
+		match self {
+			WindowContents::Nothing => "Nothing".to_owned(),
+			WindowContents::Color(color) => format!("Color ({}, {}, {}, {})", color.r, color.g, color.b, color.a),
+
+			WindowContents::Gradient {start, end, direction} => format!(
+				"Gradient ({}, {}, {}, {}) to ({}, {}, {}, {}), {direction:?}",
+				start.r, start.g, start.b, start.a, end.r, end.g, end.b, end.a
+			),
+
+			WindowContents::Lines(lines) => format!("Lines ({} lines)", lines.len()),
+			WindowContents::ThickLines(lines) => format!("ThickLines ({} lines)", lines.len()),
+			WindowContents::Texture(_) => "Texture".to_owned(),
+			WindowContents::LazyTexture(LazyTextureSource::Path(path)) => format!("LazyTexture ({path})"),
+			WindowContents::LazyTexture(LazyTextureSource::Bytes(bytes)) => format!("LazyTexture ({} embedded bytes)", bytes.len()),
+			WindowContents::Many(many) => format!("Many [{}]", many.iter().map(Self::describe).collect::<Vec<_>>().join(", "))
+		}
+	}
+
+	// Every text texture is checked (and not just until one moved), so that each one's last scroll offset stays current
+	fn text_scroll_moved(&self, texture_pool: &mut TexturePool, screen_dest: Rect) -> GenericResult<bool> {
+		match self {
+			WindowContents::Texture(texture) => texture_pool.text_scroll_moved(texture, screen_dest),
+
+			WindowContents::Many(many) => many.iter().try_fold(false, |moved, nested_contents|
+				Ok(nested_contents.text_scroll_moved(texture_pool, screen_dest)? || moved)
+			),
+
+			_ => Ok(false)
+		}
+	}
+
+	/* For `Many` contents, this lists the path of indices to each item that is not itself a `Many`, in draw order.
+	The contents are not changed, so that updaters can still index into them the way that they were made. */
+	fn get_flattened_draw_order(&self) -> Vec<Vec<usize>> {
+		fn push_paths(contents: &WindowContents, path: &mut Vec<usize>, draw_order: &mut Vec<Vec<usize>>) {
+			match contents {
+				WindowContents::Many(many) => {
+					for (index, nested_contents) in many.iter().enumerate() {
+						path.push(index);
+						push_paths(nested_contents, path, draw_order);
+						path.pop();
+					}
+				},
+
+				_ => draw_order.push(path.clone())
+			}
+		}
+
+		let mut draw_order = Vec::new();
+		push_paths(self, &mut Vec::new(), &mut draw_order);
+		draw_order
+	}
+
+	// This follows a path from `get_flattened_draw_order` down to the contents that it points to
+	fn get_nested(&self, path: &[usize]) -> &Self {
+		path.iter().fold(self, |contents, &index| match contents {
+			WindowContents::Many(many) => &many[index],
+			_ => contents
+		})
+	}
+
+	/* This turns any lazy textures (including ones nested in `Many`) into real textures.
+	If loading one fails, the texture pool's fallback texture is used in its place. */
+	fn load_lazy_textures(&mut self, texture_pool: &mut TexturePool) -> MaybeError {
+		match self {
+			WindowContents::LazyTexture(source) => {
+				let creation_info = match source {
+					LazyTextureSource::Path(path) => TextureCreationInfo::Path(Cow::Owned(path.to_string())),
+					LazyTextureSource::Bytes(bytes) => TextureCreationInfo::RawBytes(bytes)
+				};
+
+				let fallback_texture_creation_info = texture_pool.get_fallback_texture_creation_info()?;
+				self.update_as_texture(true, texture_pool, &creation_info, fallback_texture_creation_info)
+			},
+
+			WindowContents::Many(many) => many.iter_mut().try_for_each(
+				|nested_contents| nested_contents.load_lazy_textures(texture_pool)
+			),
+
+			_ => Ok(())
+		}
+	}
+}
+
+//////////
+
+// This eases a window's opacity from one value to another, starting at a certain time
+#[derive(Clone)]
+struct OpacityAnimation {
+	from: f64,
+	to: f64,
+	duration: Seconds,
+	start_time: std::time::Instant,
+	easing_fn: EasingFn
+}
+
+impl OpacityAnimation {
+	// This returns the current opacity, and if the animation is done
+	fn evaluate(&self) -> (f64, bool) {
+		let fract_done = (self.start_time.elapsed().as_secs_f64() / self.duration).min(1.0);
+		let eased_fract_done = (self.easing_fn)(fract_done);
+		(self.from + (self.to - self.from) * eased_fract_done, fract_done == 1.0)
+	}
+}
+
+/* A fade-through transition fades a window's old texture out, swaps in the new one, and then fades that in.
+While the texture is faded out, whatever is behind the window shows through (like its parent's background),
+unless a color to fade through is given (like black, for a crossfade to black). That color fades in as
+the texture fades out, and it covers the whole window. The dark fraction is the fraction of the duration
+for which the texture is fully faded out, in the middle of the transition. */
+#[derive(Copy, Clone)]
+pub struct FadeThroughTransition {
+	pub duration: Seconds,
+	pub dark_fraction: f64,
+	pub easing_fn: EasingFn,
+	pub maybe_through_color: Option<ColorSDL>
+}
+
+struct ActiveFadeThroughTransition {
+	params: FadeThroughTransition,
+	start_time: std::time::Instant,
+	swapped_textures: bool
+}
+
+impl ActiveFadeThroughTransition {
+	fn get_fract_done(&self) -> f64 {
+		(self.start_time.elapsed().as_secs_f64() / self.params.duration).min(1.0)
+	}
+
+	// This returns the texture's opacity, if the new texture should be shown by now, and if the transition is done
+	fn evaluate(&self) -> (f64, bool, bool) {
+		let fract_done = self.get_fract_done();
+		let dark_fraction = self.params.dark_fraction.clamp(0.0, 1.0);
+		let fade_fraction = (1.0 - dark_fraction) * 0.5;
+
+		let dist_past_dark_part = ((fract_done - 0.5).abs() - dark_fraction * 0.5).max(0.0);
+		let fade_fract_done = if fade_fraction == 0.0 {1.0} else {(dist_past_dark_part / fade_fraction).min(1.0)};
+
+		((self.params.easing_fn)(fade_fract_done), fract_done >= 0.5, fract_done == 1.0)
+	}
 }
 
 //////////
 
+/* This describes one window, for debug dumps of the window tree. The windows are listed in depth-first order,
+and each one refers to its parent by its index in that list, so that the tree can be rebuilt for visualizing. */
+#[derive(serde::Serialize)]
+pub struct WindowDebugInfo {
+	index: usize,
+	maybe_parent_index: Option<usize>,
+	depth: usize,
+
+	screen_rect: FRect, // This is in pixels (or logical pixels), before aspect ratio correction
+	is_partly_off_screen: bool,
+
+	contents: String,
+	maybe_border_color: Option<(u8, u8, u8, u8)>,
+	has_updater: bool,
+	skip_drawing: bool,
+	skip_aspect_ratio_correction: bool,
+	opacity: f64,
+	z_index: i32,
+
+	// This is for finding out which windows keep the screen from skipping redraws
+	subtree_changed_on_last_update: bool
+}
+
+const WINDOW_TREE_DUMP_DIRECTORY: &str = "window_tree_dumps";
+
+//////////
+
 pub struct Window {
 	possible_updater: PossibleWindowUpdater,
+	maybe_click_handler: Option<WindowClickHandler>, // Clicks are only handled if this is set (see `handle_click`)
 	state: DynamicOptional,
 	contents: WindowContents,
 
@@ -142,8 +381,52 @@ pub struct Window {
 	skip_aspect_ratio_correction: bool,
 
 	maybe_border_color: Option<ColorSDL>,
+	maybe_border_radius: Option<WindowBorderRadius>, // If this is not set, the border has square corners
+
+	/* If this is set, this color fills the window's whole (uncorrected) area before its contents are drawn,
+	so that the letterbox bars around aspect-ratio-corrected contents (and any transparent parts of textures)
+	show this color, rather than what is behind the window. Like the border, it stays axis-aligned. */
+	maybe_background_color: Option<ColorSDL>,
+
+	/* This is in degrees (clockwise), about the window's center. Textures and colors are rotated,
+	but borders and lines stay axis-aligned. Aspect ratio correction is done before rotating. */
+	maybe_rotation_degrees: Option<f64>,
+
+	/* This mirrors textures horizontally and vertically (in that order). It does not change where they are drawn,
+	so it composes with aspect ratio correction. Text textures are never flipped, and colors and lines are unaffected. */
+	flip: (bool, bool),
+
+	/* If this is set, the corners of textures (but not text textures) are rounded off with this radius,
+	which is a fraction of the smaller side of the drawn texture. If render targets are not supported, the corners stay square. */
+	maybe_texture_corner_radius: Option<f32>,
+
+	/* This is in [0, 1], and it is multiplied into the alpha of the window's
+	contents (and border). It is not inherited by child windows. */
+	opacity: f64,
+	maybe_opacity_animation: Option<OpacityAnimation>,
+
+	/* For fade-through transitions, the new texture is made in a second texture that the window keeps,
+	so that the old one can still be drawn while it fades out. The two are swapped halfway through,
+	and then the second texture holds the old one, which is reused for the next transition. */
+	maybe_fade_through_transition: Option<ActiveFadeThroughTransition>,
+	maybe_incoming_texture: Option<TextureHandle>,
+	texture_fade_opacity: f64, // This is multiplied into the opacity of textures (and not colors or lines)
+
+	/* Sibling windows are drawn in order of increasing z-index.
+	Siblings with the same z-index are drawn in the order that they were given in. */
+	z_index: i32,
+
+	/* This is set whenever something that affects how the window is drawn is modified
+	(and it starts out as true, so that the first frame is always drawn). */
+	contents_changed: bool,
+
+	/* This is the cached result of `get_flattened_draw_order` for `Many` contents. It is cleared
+	whenever the contents may be changed (see `get_contents_mut`), and rebuilt on the next draw. */
+	maybe_flattened_draw_order: Option<Vec<Vec<usize>>>,
+
+	// This is if this window or any of its descendants changed on the last update
+	subtree_changed: bool,
 
-	// TODO: Make a fn to move a window in some direction (in a FPS-independent way)
 	top_left: Vec2f,
 	size: Vec2f,
 
@@ -172,7 +455,10 @@ pub struct Window {
 	Maybe a K-D-B tree is the solution?
 	*/
 
-	children: Option<Vec<Self>>
+	children: Option<Vec<Self>>,
+
+	// These are indices into `children`, sorted by z-index (and then by index)
+	children_draw_order: Vec<usize>
 }
 
 impl Window {
@@ -192,11 +478,26 @@ impl Window {
 		};
 
 		Self {
-			possible_updater, state, contents,
+			possible_updater, maybe_click_handler: None, state, contents,
 			skip_drawing: false,
 			skip_aspect_ratio_correction: false,
 			maybe_border_color,
+			maybe_border_radius: None,
+			maybe_background_color: None,
+			maybe_rotation_degrees: None,
+			flip: (false, false),
+			maybe_texture_corner_radius: None,
+			opacity: 1.0,
+			maybe_opacity_animation: None,
+			maybe_fade_through_transition: None,
+			maybe_incoming_texture: None,
+			texture_fade_opacity: 1.0,
+			z_index: 0,
+			contents_changed: true,
+			maybe_flattened_draw_order: None,
+			subtree_changed: true,
 			top_left, size,
+			children_draw_order: (0..none_if_children_vec_is_empty.as_ref().map_or(0, Vec::len)).collect(),
 			children: none_if_children_vec_is_empty
 		}
 	}
@@ -208,6 +509,7 @@ impl Window {
 	}
 
 	pub fn get_state_mut<T: 'static>(&mut self) -> &mut T {
+		self.contents_changed = true;
 		self.state.get_mut()
 	}
 
@@ -216,10 +518,18 @@ impl Window {
 	}
 
 	pub fn get_contents_mut(&mut self) -> &mut WindowContents {
+		self.contents_changed = true;
+		self.maybe_flattened_draw_order = None;
 		&mut self.contents
 	}
 
+	// Changes to the children are tracked by the children themselves
+	pub fn get_children_mut(&mut self) -> Option<&mut [Self]> {
+		self.children.as_deref_mut()
+	}
+
 	pub fn set_draw_skipping(&mut self, skip_drawing: bool) {
+		self.contents_changed |= self.skip_drawing != skip_drawing;
 		self.skip_drawing = skip_drawing;
 	}
 
@@ -228,65 +538,391 @@ impl Window {
 	}
 
 	pub fn set_aspect_ratio_correction_skipping(&mut self, skip_aspect_ratio_correction: bool) {
+		self.contents_changed = true;
 		self.skip_aspect_ratio_correction = skip_aspect_ratio_correction;
 	}
 
-	////////// These are the window rendering functions (both public and private)
+	pub fn set_rotation(&mut self, maybe_rotation_degrees: Option<f64>) {
+		self.contents_changed = true;
+		self.maybe_rotation_degrees = maybe_rotation_degrees;
+	}
+
+	pub fn set_flip(&mut self, flip_horizontal: bool, flip_vertical: bool) {
+		self.contents_changed = true;
+		self.flip = (flip_horizontal, flip_vertical);
+	}
+
+	pub fn set_texture_corner_radius(&mut self, maybe_texture_corner_radius: Option<f32>) {
+		self.contents_changed = true;
+		self.maybe_texture_corner_radius = maybe_texture_corner_radius;
+	}
+
+	pub fn set_click_handler(&mut self, maybe_click_handler: Option<WindowClickHandler>) {
+		self.maybe_click_handler = maybe_click_handler;
+	}
+
+	pub fn set_border_radius(&mut self, maybe_border_radius: Option<WindowBorderRadius>) {
+		self.contents_changed = true;
+		self.maybe_border_radius = maybe_border_radius;
+	}
+
+	pub fn set_border_color(&mut self, maybe_border_color: Option<ColorSDL>) {
+		self.contents_changed = true;
+		self.maybe_border_color = maybe_border_color;
+	}
+
+	pub fn set_background_color(&mut self, maybe_background_color: Option<ColorSDL>) {
+		self.contents_changed = true;
+		self.maybe_background_color = maybe_background_color;
+	}
+
+	pub const fn get_opacity(&self) -> f64 {
+		self.opacity
+	}
+
+	/* This moves the window by a velocity (in units of its parent's size per second, and either sign),
+	scaled by the real time that the last frame took, so that motion looks the same at any frame rate.
+	The window is clamped to stay within its parent. This returns if it hit the parent's edges on the x and y axes
+	(which is useful for bouncing). Note that the velocity is not a `Vec2f`, since that cannot be negative. */
+	pub fn move_by(&mut self, velocity: (f32, f32), frame_counter: &FrameCounter) -> (bool, bool) {
+		let delta_time = frame_counter.delta_seconds() as f32;
+
+		let move_on_axis = |position: f32, velocity: f32, size: f32| {
+			let unclamped_position = position + velocity * delta_time;
+			let max_position = (1.0 - size).max(0.0);
+			let clamped_position = unclamped_position.clamp(0.0, max_position);
+			(clamped_position, clamped_position != unclamped_position)
+		};
+
+		let (x, hit_x_edge) = move_on_axis(self.top_left.x(), velocity.0, self.size.x());
+		let (y, hit_y_edge) = move_on_axis(self.top_left.y(), velocity.1, self.size.y());
+
+		self.top_left = Vec2f::new(x, y);
+		self.contents_changed = true;
+
+		(hit_x_edge, hit_y_edge)
+	}
+
+	pub fn set_size(&mut self, size: Vec2f) {
+		self.contents_changed |= self.size != size;
+		self.size = size;
+	}
+
+	pub fn set_z_index(&mut self, z_index: i32) {
+		self.contents_changed = true;
+		self.z_index = z_index;
+	}
+
+	// This eases the opacity from `from` to `to` over the given duration, starting now.
+	pub fn animate_opacity(&mut self, from: f64, to: f64, duration: Seconds, easing_fn: EasingFn) {
+		assert_in_unit_interval(from as f32);
+		assert_in_unit_interval(to as f32);
+
+		self.opacity = from;
+		self.contents_changed = true;
+
+		self.maybe_opacity_animation = Some(OpacityAnimation {
+			from, to, duration,
+			start_time: std::time::Instant::now(),
+			easing_fn
+		});
+	}
+
+	/* This remakes the shown texture directly. If a fade-through transition is running, it is stopped first
+	(so that it does not swap its incoming texture in later). The incoming texture is kept, for the next transition. */
+	pub fn update_texture_without_transition(&mut self, texture_pool: &mut TexturePool,
+		texture_creation_info: &TextureCreationInfo, fallback_texture_creation_info: &TextureCreationInfo) -> MaybeError {
+
+		self.maybe_fade_through_transition = None;
+		self.texture_fade_opacity = 1.0;
+		self.get_contents_mut().update_as_texture(true, texture_pool, texture_creation_info, fallback_texture_creation_info)
+	}
+
+	/* This remakes the window's texture with a fade-through transition. If the window does not have a texture yet,
+	the new one is shown right away. If a transition is already running, the texture pool's overlap policy decides
+	between retargeting it and snapping to the new texture. When retargeting a transition that is already fading in
+	a texture, the new transition starts at the point where the old one left off (so that the texture's opacity does not jump). */
+	pub fn update_texture_with_fade_through(&mut self, texture_pool: &mut TexturePool,
+		texture_creation_info: &TextureCreationInfo, fallback_texture_creation_info: &TextureCreationInfo,
+		transition: FadeThroughTransition) -> MaybeError {
+
+		let should_snap = self.maybe_fade_through_transition.is_some()
+			&& matches!(texture_pool.get_transition_overlap_policy(), TextureTransitionOverlapPolicy::SnapToLatest);
+
+		if should_snap || !matches!(self.contents, WindowContents::Texture(_)) {
+			return self.update_texture_without_transition(texture_pool, texture_creation_info, fallback_texture_creation_info);
+		}
+
+		let mut incoming_contents = self.maybe_incoming_texture.take().map_or(WindowContents::Nothing, WindowContents::Texture);
+		incoming_contents.update_as_texture(true, texture_pool, texture_creation_info, fallback_texture_creation_info)?;
+
+		let WindowContents::Texture(incoming_texture) = incoming_contents else {
+			return error_msg!("Expected the incoming texture for a fade-through transition to be a texture");
+		};
+
+		let mut start_time = std::time::Instant::now();
+
+		if let Some(prev_transition) = self.maybe_fade_through_transition.as_ref().filter(|prev| prev.swapped_textures) {
+			let fract_done = prev_transition.get_fract_done();
+			start_time -= std::time::Duration::from_secs_f64((1.0 - fract_done) * transition.duration);
+		}
+
+		self.maybe_incoming_texture = Some(incoming_texture);
+		self.maybe_fade_through_transition = Some(ActiveFadeThroughTransition {params: transition, start_time, swapped_textures: false});
+		self.contents_changed = true;
+
+		Ok(())
+	}
+
+	////////// These are the window updating and rendering functions (both public and private)
+
+	/* This runs all of the window updaters (and opacity animations). It returns true if anything in the tree
+	might look different than it did on the last frame, which means that the tree needs to be redrawn.
+	Note that skipping redraws is done on a whole-frame basis, since the canvas is cleared before each redraw. */
+	pub fn update(&mut self, rendering_params: &mut PerFrameConstantRenderingParams) -> GenericResult<bool> {
+		let sdl_window_bounds = Self::get_sdl_window_bounds(rendering_params)?;
+		let tree_changed = self.inner_update(rendering_params, sdl_window_bounds)?;
+
+		// Textures can also be remade outside of window updaters (e.g. by the shared window state updater)
+		let textures_changed = rendering_params.texture_pool.take_textures_changed_flag();
+
+		Ok(tree_changed || textures_changed)
+	}
 
 	pub fn render(&mut self, rendering_params: &mut PerFrameConstantRenderingParams) -> MaybeError {
-		let output_size = rendering_params.sdl_canvas.output_size().to_generic()?;
-		let sdl_window_bounds = FRect {x: 0.0, y: 0.0, width: output_size.0 as f32, height: output_size.1 as f32};
+		let sdl_window_bounds = Self::get_sdl_window_bounds(rendering_params)?;
 		self.inner_render(rendering_params, sdl_window_bounds)
 	}
 
-	fn transform_vec2_to_parent_scale(v: Vec2f, parent_rect: FRect) -> (f32, f32) {
-		(v.x() * parent_rect.width + parent_rect.x, v.y() * parent_rect.height + parent_rect.y)
+	////////// Click handling
+
+	/* This gives a click (in canvas coordinates) to the topmost visible window under it that has a click handler.
+	Children are above their parents, and siblings are checked in reverse draw order (so higher z-indices come first).
+	Windows that skip drawing (or are fully transparent) can't be clicked, but their children still can be.
+	This returns true if some window handled the click. */
+	pub fn handle_click(&mut self, rendering_params: &mut PerFrameConstantRenderingParams, position: (f32, f32)) -> GenericResult<bool> {
+		let sdl_window_bounds = Self::get_sdl_window_bounds(rendering_params)?;
+		self.inner_handle_click(rendering_params, sdl_window_bounds, position)
 	}
 
-	fn inner_render(&mut self,
-		rendering_params: &mut PerFrameConstantRenderingParams,
-		parent_rect: FRect) -> MaybeError {
+	fn inner_handle_click(&mut self, rendering_params: &mut PerFrameConstantRenderingParams,
+		parent_rect: FRect, position: (f32, f32)) -> GenericResult<bool> {
+
+		let screen_dest = self.get_screen_dest(parent_rect);
+
+		if let Some(children) = &mut self.children {
+			for &index in self.children_draw_order.iter().rev() {
+				if children[index].inner_handle_click(rendering_params, screen_dest, position)? {
+					return Ok(true);
+				}
+			}
+		}
+
+		let Some(click_handler) = self.maybe_click_handler else {return Ok(false)};
+
+		let is_visible = !self.skip_drawing && self.opacity != 0.0;
+
+		let is_inside = position.0 >= screen_dest.x && position.0 < screen_dest.x + screen_dest.width
+			&& position.1 >= screen_dest.y && position.1 < screen_dest.y + screen_dest.height;
+
+		if !is_visible || !is_inside {
+			return Ok(false);
+		}
+
+		click_handler(WindowClickParams {
+			window: self,
+			shared_window_state: &mut rendering_params.shared_window_state
+		})?;
+
+		// The handler may have changed how the window looks
+		self.contents_changed = true;
+		Ok(true)
+	}
+
+	////////// Debug dumps
+
+	// This only reads the tree, and it is only done when a dump is requested (so it costs nothing otherwise)
+	pub fn describe_tree(&self, rendering_params: &PerFrameConstantRenderingParams) -> GenericResult<Vec<WindowDebugInfo>> {
+		let sdl_window_bounds = Self::get_sdl_window_bounds(rendering_params)?;
+		let mut descriptions = Vec::new();
+		self.inner_describe_tree(sdl_window_bounds, sdl_window_bounds, None, 0, &mut descriptions);
+		Ok(descriptions)
+	}
 
-		////////// Getting the new pixel-space bounding box for this window
+	fn inner_describe_tree(&self, parent_rect: FRect, sdl_window_bounds: FRect,
+		maybe_parent_index: Option<usize>, depth: usize, descriptions: &mut Vec<WindowDebugInfo>) {
+
+		// This allows for some float error at the edges of the screen
+		const OFF_SCREEN_TOLERANCE_PIXELS: f32 = 0.5;
+
+		let screen_rect = self.get_screen_dest(parent_rect);
+		let index = descriptions.len();
+
+		let is_partly_off_screen =
+			screen_rect.x < sdl_window_bounds.x - OFF_SCREEN_TOLERANCE_PIXELS
+			|| screen_rect.y < sdl_window_bounds.y - OFF_SCREEN_TOLERANCE_PIXELS
+			|| screen_rect.x + screen_rect.width > sdl_window_bounds.x + sdl_window_bounds.width + OFF_SCREEN_TOLERANCE_PIXELS
+			|| screen_rect.y + screen_rect.height > sdl_window_bounds.y + sdl_window_bounds.height + OFF_SCREEN_TOLERANCE_PIXELS;
+
+		descriptions.push(WindowDebugInfo {
+			index, maybe_parent_index, depth,
+			screen_rect, is_partly_off_screen,
+			contents: self.contents.describe(),
+			maybe_border_color: self.maybe_border_color.map(|color| (color.r, color.g, color.b, color.a)),
+			has_updater: self.possible_updater.is_some(),
+			skip_drawing: self.skip_drawing,
+			skip_aspect_ratio_correction: self.skip_aspect_ratio_correction,
+			opacity: self.opacity,
+			z_index: self.z_index,
+			subtree_changed_on_last_update: self.subtree_changed
+		});
+
+		for child in self.children.iter().flatten() {
+			child.inner_describe_tree(screen_rect, sdl_window_bounds, Some(index), depth + 1, descriptions);
+		}
+	}
+
+	// This saves a description of the whole tree to a JSON file with a timestamped filename, and returns its path
+	pub fn save_debug_dump(&self, rendering_params: &PerFrameConstantRenderingParams) -> GenericResult<String> {
+		let descriptions = self.describe_tree(rendering_params)?;
+
+		std::fs::create_dir_all(WINDOW_TREE_DUMP_DIRECTORY)?;
+
+		let path = format!("{WINDOW_TREE_DUMP_DIRECTORY}/window_tree_{}.json", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+		std::fs::write(&path, serde_json::to_string_pretty(&descriptions)?)?;
+
+		Ok(path)
+	}
+
+	//////////
+
+	// If the canvas has a logical size, drawing happens in logical coordinates (and SDL scales that up to the output)
+	fn get_sdl_window_bounds(rendering_params: &PerFrameConstantRenderingParams) -> GenericResult<FRect> {
+		let canvas = &rendering_params.sdl_canvas;
 
+		let size = match canvas.logical_size() {
+			(0, 0) => canvas.output_size().to_generic()?,
+			logical_size => logical_size
+		};
+
+		let (shift_x, shift_y) = rendering_params.pixel_shift;
+		Ok(FRect {x: shift_x as f32, y: shift_y as f32, width: size.0 as f32, height: size.1 as f32})
+	}
+
+	fn transform_vec2_to_parent_scale(v: Vec2f, parent_rect: FRect) -> (f32, f32) {
+		(v.x() * parent_rect.width + parent_rect.x, v.y() * parent_rect.height + parent_rect.y)
+	}
+
+	// This gets the new pixel-space bounding box for this window
+	fn get_screen_dest(&self, parent_rect: FRect) -> FRect {
 		let rect_origin = Self::transform_vec2_to_parent_scale(self.top_left, parent_rect);
 
-		let screen_dest = FRect {
+		FRect {
 			x: rect_origin.0,
 			y: rect_origin.1,
 			width: self.size.x() * parent_rect.width,
 			height: self.size.y() * parent_rect.height
-		};
+		}
+	}
 
-		////////// Updating the window
+	fn inner_update(&mut self,
+		rendering_params: &mut PerFrameConstantRenderingParams,
+		parent_rect: FRect) -> GenericResult<bool> {
 
-		/* TODO: if no updaters were called, then don't redraw anything
-		(or if the updaters had no effect on the window).
-		- Draw everything the first time around, without an updater.
-		- The second time around + all other times, first check all the updaters.
-		- If no updaters are called, don't redraw anything.
-		- For any specific node, if that updater doesn't have an effect, then don't draw for that node. */
+		let screen_dest = self.get_screen_dest(parent_rect);
+
+		////////// Updating the window
 
 		if let Some((updater, update_rate)) = self.possible_updater {
 			if update_rate.is_time_to_update(rendering_params.frame_counter) {
-				updater(WindowUpdaterParams {
+				let result = updater(WindowUpdaterParams {
 					window: self,
 					texture_pool: &mut rendering_params.texture_pool,
 					shared_window_state: &mut rendering_params.shared_window_state,
+					error_state: &mut rendering_params.error_state,
+					frame_counter: rendering_params.frame_counter,
+					window_is_focused: rendering_params.window_is_focused,
 					area_drawn_to_screen: (screen_dest.width as u32, screen_dest.height as u32)
-				})?;
+				});
+
+				// A failing updater does not stop the rest of the tree from updating
+				if let Err(err) = result {
+					rendering_params.error_state.report("Window updater", err.to_string());
+				}
 			}
 		}
 
-		if !self.skip_drawing {
-			self.draw_window_contents(rendering_params, screen_dest)?;
+		if let Some(opacity_animation) = &self.maybe_opacity_animation {
+			let (opacity, animation_is_done) = opacity_animation.evaluate();
+			self.opacity = opacity;
+			self.contents_changed = true;
+			if animation_is_done {self.maybe_opacity_animation = None;}
+		}
+
+		if let Some(transition) = &mut self.maybe_fade_through_transition {
+			let (texture_fade_opacity, should_swap_textures, transition_is_done) = transition.evaluate();
+
+			if should_swap_textures && !transition.swapped_textures {
+				transition.swapped_textures = true;
+
+				/* A blend mode or alpha mod set on the old texture (even during the transition) is carried over to the new one.
+				Note that after the swap, the incoming texture is the old one. */
+				if let (WindowContents::Texture(curr_texture), Some(incoming_texture)) = (&mut self.contents, &mut self.maybe_incoming_texture) {
+					std::mem::swap(curr_texture, incoming_texture);
+					rendering_params.texture_pool.copy_texture_mods(incoming_texture, curr_texture);
+				}
+			}
+
+			self.texture_fade_opacity = texture_fade_opacity;
+			self.contents_changed = true;
+			if transition_is_done {self.maybe_fade_through_transition = None;}
 		}
 
+		// Text textures only need a redraw when their scrolling moves them by at least a pixel
+		let is_visible = !self.skip_drawing && self.opacity != 0.0;
+		let text_scroll_moved = is_visible && self.contents.text_scroll_moved(&mut rendering_params.texture_pool, screen_dest.into())?;
+
+		let mut changed = self.contents_changed || text_scroll_moved;
+		self.contents_changed = false;
+
 		////////// Updating all child windows
 
 		if let Some(children) = &mut self.children {
 			for child in children {
-				child.inner_render(rendering_params, screen_dest)?;
+				changed |= child.inner_update(rendering_params, screen_dest)?;
+			}
+		}
+
+		self.subtree_changed = changed;
+		Ok(changed)
+	}
+
+	fn inner_render(&mut self,
+		rendering_params: &mut PerFrameConstantRenderingParams,
+		parent_rect: FRect) -> MaybeError {
+
+		let screen_dest = self.get_screen_dest(parent_rect);
+
+		if !self.skip_drawing && self.opacity != 0.0 {
+			// A window that fails to draw does not stop the rest of the tree from drawing
+			if let Err(err) = self.draw_window_contents(rendering_params, screen_dest) {
+				rendering_params.error_state.report("Window drawing", err.to_string());
+			}
+		}
+
+		////////// Rendering all child windows
+
+		if let Some(children) = &mut self.children {
+			let draw_order = &mut self.children_draw_order;
+			let draw_key = |index: usize| (children[index].z_index, index);
+
+			// The draw order is only resorted if a child's z-index changed since the last frame
+			if !draw_order.is_sorted_by_key(|&index| draw_key(index)) {
+				draw_order.sort_by_key(|&index| draw_key(index));
+			}
+
+			for &index in draw_order.iter() {
+				children[index].inner_render(rendering_params, screen_dest)?;
 			}
 		}
 
@@ -299,41 +935,123 @@ impl Window {
 
 		//////////
 
-		draw_contents(
-			&self.contents, rendering_params,
-			uncorrected_screen_dest,
-			self.skip_aspect_ratio_correction
-		)?;
+		self.contents.load_lazy_textures(&mut rendering_params.texture_pool)?;
+
+		if let Some(background_color) = &self.maybe_background_color {
+			possibly_draw_with_transparency(&with_opacity(*background_color, self.opacity), &mut rendering_params.sdl_canvas,
+				|canvas| canvas.fill_rect::<Rect>(uncorrected_screen_dest.into()).to_generic())?;
+		}
+
+		if matches!(self.contents, WindowContents::Many(_)) {
+			if self.maybe_flattened_draw_order.is_none() {
+				self.maybe_flattened_draw_order = Some(self.contents.get_flattened_draw_order());
+			}
+
+			for path in self.maybe_flattened_draw_order.iter().flatten() {
+				draw_contents(self.contents.get_nested(path), self, rendering_params, uncorrected_screen_dest)?;
+			}
+		}
+		else {
+			draw_contents(&self.contents, self, rendering_params, uncorrected_screen_dest)?;
+		}
 
 		if let Some(border_color) = &self.maybe_border_color {
-			possibly_draw_with_transparency(border_color, &mut rendering_params.sdl_canvas,
-				|canvas| canvas.draw_rect(uncorrected_screen_dest.into()).to_generic())?;
+			let border_color = with_opacity(*border_color, self.opacity);
+			let rect: Rect = uncorrected_screen_dest.into();
+
+			let radius = match self.maybe_border_radius {
+				None => 0,
+				Some(WindowBorderRadius::Pixels(radius)) => radius,
+
+				Some(WindowBorderRadius::FractionOfSmallerSide(fraction)) =>
+					(fraction * rect.width().min(rect.height()) as f32).round() as i16
+			};
+
+			// The corners overlap if the radius is over half of the smaller side
+			let radius = radius.min((rect.width().min(rect.height()) / 2) as i16);
+			let use_anti_aliasing = rendering_params.use_anti_aliased_borders;
+
+			/* SDL2_gfx sets the blend mode itself (and leaves it set), so it is still drawn
+			through this, so that the blend mode is put back afterwards like for the other drawing */
+			possibly_draw_with_transparency(&border_color, &mut rendering_params.sdl_canvas, |canvas| {
+				use sdl2::gfx::primitives::DrawRenderer;
+
+				if radius <= 0 {
+					canvas.draw_rect(rect).to_generic()
+				}
+				else if use_anti_aliasing {
+					let (xs, ys) = make_rounded_rect_outline(rect, radius);
+					canvas.aa_polygon(&xs, &ys, border_color).to_generic()
+				}
+				else {
+					canvas.rounded_rectangle(rect.left() as i16, rect.top() as i16,
+						(rect.right() - 1) as i16, (rect.bottom() - 1) as i16, radius, border_color).to_generic()
+				}
+			})?;
 		}
 
 		return Ok(());
 
 		////////// A function for drawing the contents passed to it
 
+		// The window is passed in for its drawing settings (its own contents may be an outer level of `contents`)
 		fn draw_contents(
 			contents: &WindowContents,
+			window: &Window,
 			rendering_params: &mut PerFrameConstantRenderingParams,
-			uncorrected_screen_dest: FRect,
-			skip_aspect_ratio_correction: bool) -> MaybeError {
+			uncorrected_screen_dest: FRect) -> MaybeError {
+
+			let (maybe_rotation_degrees, opacity) = (window.maybe_rotation_degrees, window.opacity);
 
 			let maybe_corrected_screen_dest = maybe_correct_aspect_ratio(
 				contents, uncorrected_screen_dest, &rendering_params.texture_pool,
-				skip_aspect_ratio_correction);
+				window.skip_aspect_ratio_correction);
 
 			let sdl_canvas = &mut rendering_params.sdl_canvas;
 
 			match contents {
-				WindowContents::Nothing => {},
+				// Lazy textures are always loaded before this point
+				WindowContents::Nothing | WindowContents::LazyTexture(_) => {},
 
-				WindowContents::Color(color) => possibly_draw_with_transparency(
-					color, sdl_canvas, |canvas|
-						canvas.fill_rect::<Rect>(uncorrected_screen_dest.into()).to_generic()
+				WindowContents::Color(color) => match maybe_rotation_degrees {
+					Some(rotation_degrees) => rendering_params.texture_pool.draw_rotated_color_to_canvas(
+						with_opacity(*color, opacity), sdl_canvas, uncorrected_screen_dest.into(), rotation_degrees
 					)?,
 
+					None => possibly_draw_with_transparency(
+						&with_opacity(*color, opacity), sdl_canvas, |canvas|
+							canvas.fill_rect::<Rect>(uncorrected_screen_dest.into()).to_generic()
+						)?
+				},
+
+				WindowContents::Gradient {start, end, direction} => {
+					let rect: Rect = uncorrected_screen_dest.into();
+					let (x, y, width, height) = (rect.x(), rect.y(), rect.width() as i32, rect.height() as i32);
+
+					let num_lines = match direction {
+						GradientDirection::Vertical => height,
+						GradientDirection::Horizontal => width,
+						GradientDirection::Diagonal => width + height - 1
+					};
+
+					for i in 0..num_lines {
+						let color = with_opacity(lerp_color(*start, *end, i as f64 / (num_lines - 1).max(1) as f64), opacity);
+
+						// Diagonal lines go from the top right to the bottom left, and each one is clipped to the rect
+						let (from, to) = match direction {
+							GradientDirection::Vertical => ((x, y + i), (x + width - 1, y + i)),
+							GradientDirection::Horizontal => ((x + i, y), (x + i, y + height - 1)),
+
+							GradientDirection::Diagonal => {
+								let (first_x, last_x) = ((i - (height - 1)).max(0), i.min(width - 1));
+								((x + last_x, y + i - last_x), (x + first_x, y + i - first_x))
+							}
+						};
+
+						possibly_draw_with_transparency(&color, sdl_canvas, |canvas| canvas.draw_line(from, to).to_generic())?;
+					}
+				},
+
 				WindowContents::Lines(line_series) => {
 					use sdl2::rect::Point as PointSDL;
 
@@ -343,26 +1061,49 @@ impl Window {
 							PointSDL::new(xy.0 as i32, xy.1 as i32)
 						}).collect();
 
-						possibly_draw_with_transparency(&series.0, sdl_canvas, |canvas|
+						possibly_draw_with_transparency(&with_opacity(series.0, opacity), sdl_canvas, |canvas|
 							canvas.draw_lines(&*converted_series).to_generic()
 						)?;
 					}
 				},
 
-				/* TODO: eliminate the partially black border around
-				the opaque areas of textures with alpha values */
-				WindowContents::Texture(texture) =>
-					rendering_params.texture_pool.draw_texture_to_canvas(
-						texture, sdl_canvas, maybe_corrected_screen_dest.into()
-					)?,
+				WindowContents::ThickLines(line_series) => {
+					for (color, thickness, points) in line_series {
+						let converted_points: Vec<(f32, f32)> = points.iter().map(|&point|
+							Window::transform_vec2_to_parent_scale(point, maybe_corrected_screen_dest)
+						).collect();
+
+						for segment in converted_points.windows(2) {
+							draw_thick_aa_line(sdl_canvas, segment[0], segment[1], *thickness, with_opacity(*color, opacity))?;
+						}
+					}
+				},
+
+				WindowContents::Texture(texture) => {
+					let maybe_through_color = window.maybe_fade_through_transition.as_ref().and_then(|transition| transition.params.maybe_through_color);
+
+					// The color that is faded through is drawn beneath the texture, and it is opaque while the texture is faded out
+					if let Some(through_color) = maybe_through_color {
+						let alpha = (through_color.a as f64 * (1.0 - window.texture_fade_opacity)) as u8;
+						let faded_through_color = ColorSDL::RGBA(through_color.r, through_color.g, through_color.b, alpha);
+						draw_contents(&WindowContents::Color(faded_through_color), window, rendering_params, uncorrected_screen_dest)?;
+					}
+
+					let (sdl_canvas, opacity) = (&mut rendering_params.sdl_canvas, opacity * window.texture_fade_opacity);
+					let screen_dest: Rect = maybe_corrected_screen_dest.into();
+
+					let corner_radius = window.maybe_texture_corner_radius.map_or(0,
+						|corner_radius| (corner_radius * screen_dest.width().min(screen_dest.height()) as f32) as u32
+					);
+
+					rendering_params.texture_pool.draw_texture_to_canvas(texture, sdl_canvas, screen_dest,
+						TextureDrawParams {maybe_rotation_degrees, flip: window.flip, opacity, corner_radius}
+					)?;
+				},
 
 				WindowContents::Many(many) => {
 					for nested_contents in many {
-						draw_contents(
-							nested_contents, rendering_params,
-							uncorrected_screen_dest,
-							skip_aspect_ratio_correction
-						)?;
+						draw_contents(nested_contents, window, rendering_params, uncorrected_screen_dest)?;
 					}
 				}
 			};
@@ -370,6 +1111,86 @@ impl Window {
 			Ok(())
 		}
 
+		////////// A function for making the outline of a rounded rectangle (for anti-aliased borders)
+
+		/* SDL2_gfx has no anti-aliased arcs, so each corner is approximated with a few line segments,
+		and the whole outline is drawn as one anti-aliased polygon. This returns the x and y coordinates of its points. */
+		fn make_rounded_rect_outline(rect: Rect, radius: i16) -> (Vec<i16>, Vec<i16>) {
+			const MAX_NUM_SEGMENTS_PER_CORNER: i16 = 16;
+
+			let num_segments = (radius / 2).clamp(2, MAX_NUM_SEGMENTS_PER_CORNER);
+			let radius = radius as f64;
+
+			let (left, top) = (rect.left() as f64 + radius, rect.top() as f64 + radius);
+			let (right, bottom) = ((rect.right() - 1) as f64 - radius, (rect.bottom() - 1) as f64 - radius);
+
+			// These are the corner arc centers, and the angles that the arcs start at (clockwise on the screen, since y points down)
+			let corners = [((right, top), -90.0), ((right, bottom), 0.0), ((left, bottom), 90.0), ((left, top), 180.0)];
+			let num_points = corners.len() * (num_segments as usize + 1);
+			let (mut xs, mut ys) = (Vec::with_capacity(num_points), Vec::with_capacity(num_points));
+
+			for ((center_x, center_y), start_degrees) in corners {
+				for i in 0..=num_segments {
+					let angle = f64::to_radians(start_degrees + 90.0 * i as f64 / num_segments as f64);
+					xs.push((center_x + radius * angle.cos()).round() as i16);
+					ys.push((center_y + radius * angle.sin()).round() as i16);
+				}
+			}
+
+			(xs, ys)
+		}
+
+		////////// A function for drawing a thick, anti-aliased line segment
+
+		/* SDL2_gfx's thick lines are not anti-aliased, and its anti-aliased lines are only one pixel thick.
+		So a thick line is drawn first, and then anti-aliased lines are drawn along its two long edges to smooth them.
+		The edges are only smoothed for opaque colors, since a translucent edge would be blended over the
+		thick line's own edge pixels, making them more opaque than the rest of the line. */
+		fn draw_thick_aa_line(canvas: &mut CanvasSDL, from: (f32, f32), to: (f32, f32), thickness: u8, color: ColorSDL) -> MaybeError {
+			use sdl2::gfx::primitives::DrawRenderer;
+
+			let to_i16 = |(x, y): (f32, f32)| (x.round() as i16, y.round() as i16);
+			let ((x1, y1), (x2, y2)) = (to_i16(from), to_i16(to));
+
+			if thickness <= 1 {
+				return canvas.aa_line(x1, y1, x2, y2, color).to_generic();
+			}
+
+			canvas.thick_line(x1, y1, x2, y2, thickness, color).to_generic()?;
+
+			let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+			let length = dx.hypot(dy);
+
+			if length == 0.0 || color.a != 255 {
+				return Ok(());
+			}
+
+			// This is perpendicular to the line, and half of its thickness long
+			let half_thickness = thickness as f32 * 0.5;
+			let (normal_x, normal_y) = (-dy / length * half_thickness, dx / length * half_thickness);
+
+			for sign in [-1.0, 1.0] {
+				let offset = |(x, y): (f32, f32)| (x + normal_x * sign, y + normal_y * sign);
+				let ((edge_x1, edge_y1), (edge_x2, edge_y2)) = (to_i16(offset(from)), to_i16(offset(to)));
+				canvas.aa_line(edge_x1, edge_y1, edge_x2, edge_y2, color).to_generic()?;
+			}
+
+			Ok(())
+		}
+
+		////////// A function for applying a window's opacity to a color
+
+		fn with_opacity(color: ColorSDL, opacity: f64) -> ColorSDL {
+			ColorSDL::RGBA(color.r, color.g, color.b, (color.a as f64 * opacity) as u8)
+		}
+
+		////////// A function for blending between two colors (for gradients)
+
+		fn lerp_color(start: ColorSDL, end: ColorSDL, amount: f64) -> ColorSDL {
+			let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * amount).round() as u8;
+			ColorSDL::RGBA(lerp(start.r, end.r), lerp(start.g, end.g), lerp(start.b, end.b), lerp(start.a, end.a))
+		}
+
 		////////// A function for drawing colors with transparency
 
 		fn possibly_draw_with_transparency(color: &ColorSDL, sdl_canvas: &mut CanvasSDL,